@@ -1,11 +1,46 @@
 use std::fs;
 use std::path::Path;
+use std::process::Command;
 
 fn main() {
     assert_tauri_version_matches_cargo();
+    emit_build_info();
     tauri_build::build()
 }
 
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Build details shown in diagnostics bundles.
+fn emit_build_info() {
+    let commit = git(&["rev-parse", "--short", "HEAD"]).unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=BLOXCHAT_GIT_COMMIT={commit}");
+
+    // Pick up new commits, not only branch switches.
+    let mut watched = vec!["HEAD".to_string()];
+    watched.extend(git(&["symbolic-ref", "-q", "HEAD"]));
+    for reference in watched {
+        if let Some(path) = git(&["rev-parse", "--git-path", &reference]) {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+
+    for (name, var) in [
+        ("BLOXCHAT_BUILD_TARGET", "TARGET"),
+        ("BLOXCHAT_BUILD_PROFILE", "PROFILE"),
+    ] {
+        let value = std::env::var(var).unwrap_or_else(|_| "unknown".to_string());
+        println!("cargo:rustc-env={name}={value}");
+    }
+}
+
 fn assert_tauri_version_matches_cargo() {
     let cargo_version = env!("CARGO_PKG_VERSION");
     let tauri_conf_path = Path::new("tauri.conf.json");
@@ -0,0 +1,311 @@
+use crate::roblox::{RobloxWindowBounds, RobloxWindowLocator};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, PhysicalPosition, WebviewWindow};
+
+const WINDOW_ANCHOR_KEY: &str = "windowAnchor";
+const ANCHOR_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum AnchorCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Keeps the chat window at `corner` of the Roblox window, inset by the
+/// offsets (measured inwards from that corner).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AnchorSettings {
+    enabled: bool,
+    corner: AnchorCorner,
+    offset_x: i32,
+    offset_y: i32,
+}
+
+impl Default for AnchorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            corner: AnchorCorner::BottomLeft,
+            offset_x: 16,
+            offset_y: 16,
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct AnchorState {
+    settings: Mutex<AnchorSettings>,
+}
+
+fn anchored_position(
+    roblox: &RobloxWindowBounds,
+    chat_width: u32,
+    chat_height: u32,
+    settings: &AnchorSettings,
+) -> (i32, i32) {
+    let left = roblox.x + settings.offset_x;
+    let top = roblox.y + settings.offset_y;
+    let right = roblox.x + roblox.width as i32 - chat_width as i32 - settings.offset_x;
+    let bottom = roblox.y + roblox.height as i32 - chat_height as i32 - settings.offset_y;
+
+    match settings.corner {
+        AnchorCorner::TopLeft => (left, top),
+        AnchorCorner::TopRight => (right, top),
+        AnchorCorner::BottomLeft => (left, bottom),
+        AnchorCorner::BottomRight => (right, bottom),
+    }
+}
+
+/// Inverse of [`anchored_position`]: the offsets that keep the chat window
+/// where it currently is relative to `corner`.
+fn offsets_for_position(
+    roblox: &RobloxWindowBounds,
+    chat_x: i32,
+    chat_y: i32,
+    chat_width: u32,
+    chat_height: u32,
+    corner: AnchorCorner,
+) -> (i32, i32) {
+    let from_left = chat_x - roblox.x;
+    let from_top = chat_y - roblox.y;
+    let from_right = roblox.x + roblox.width as i32 - (chat_x + chat_width as i32);
+    let from_bottom = roblox.y + roblox.height as i32 - (chat_y + chat_height as i32);
+
+    match corner {
+        AnchorCorner::TopLeft => (from_left, from_top),
+        AnchorCorner::TopRight => (from_right, from_top),
+        AnchorCorner::BottomLeft => (from_left, from_bottom),
+        AnchorCorner::BottomRight => (from_right, from_bottom),
+    }
+}
+
+fn load_settings(app: &AppHandle) -> AnchorSettings {
+    crate::storage::store(app)
+        .ok()
+        .and_then(|store| store.get(WINDOW_ANCHOR_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings(app: &AppHandle, settings: &AnchorSettings) -> Result<()> {
+    let store = crate::storage::store(app).context("open settings store")?;
+    store.set(
+        WINDOW_ANCHOR_KEY,
+        serde_json::to_value(settings).context("serialize window anchor")?,
+    );
+    Ok(())
+}
+
+fn current_settings(app: &AppHandle) -> Result<AnchorSettings> {
+    Ok(app
+        .state::<AnchorState>()
+        .settings
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock anchor settings: {err}"))?
+        .clone())
+}
+
+pub(crate) fn is_anchored(app: &AppHandle) -> bool {
+    current_settings(app)
+        .map(|settings| settings.enabled)
+        .unwrap_or(false)
+}
+
+pub(crate) fn get_window_anchor(app: &AppHandle) -> Result<AnchorSettings> {
+    current_settings(app)
+}
+
+pub(crate) fn set_window_anchor(
+    app: &AppHandle,
+    settings: AnchorSettings,
+) -> Result<AnchorSettings> {
+    {
+        let state = app.state::<AnchorState>();
+        let mut current = state
+            .settings
+            .lock()
+            .map_err(|err| anyhow::anyhow!("lock anchor settings: {err}"))?;
+        *current = settings.clone();
+    }
+
+    save_settings(app, &settings)?;
+    Ok(settings)
+}
+
+/// Enables anchoring at `corner`, keeping the chat window where the user
+/// placed it relative to the Roblox window.
+pub(crate) fn capture_window_anchor(
+    window: &WebviewWindow,
+    corner: AnchorCorner,
+) -> Result<AnchorSettings> {
+    let Some(roblox) = RobloxWindowLocator::default().bounds() else {
+        anyhow::bail!("Roblox window not found");
+    };
+
+    let position = window.outer_position().context("read window position")?;
+    let size = window.outer_size().context("read window size")?;
+    let (offset_x, offset_y) = offsets_for_position(
+        &roblox,
+        position.x,
+        position.y,
+        size.width,
+        size.height,
+        corner,
+    );
+
+    set_window_anchor(
+        window.app_handle(),
+        AnchorSettings {
+            enabled: true,
+            corner,
+            offset_x,
+            offset_y,
+        },
+    )
+}
+
+fn follow_roblox_window(
+    window: &WebviewWindow,
+    roblox: &RobloxWindowBounds,
+    settings: &AnchorSettings,
+    hidden_with_roblox: &mut bool,
+) -> Result<()> {
+    if roblox.minimized {
+        if !*hidden_with_roblox && window.is_visible().unwrap_or(false) {
+            window.hide().context("hide chat window")?;
+            *hidden_with_roblox = true;
+        }
+        return Ok(());
+    }
+
+    if *hidden_with_roblox {
+        window.show().context("show chat window")?;
+        *hidden_with_roblox = false;
+    }
+
+    let size = window.outer_size().context("read window size")?;
+    let (x, y) = anchored_position(roblox, size.width, size.height, settings);
+    let position = window.outer_position().context("read window position")?;
+    if position.x != x || position.y != y {
+        window
+            .set_position(PhysicalPosition::new(x, y))
+            .context("move chat window")?;
+    }
+
+    Ok(())
+}
+
+/// Loads the saved anchor and polls the Roblox window so the chat window
+/// follows it around and hides while it is minimized.
+pub(crate) fn start_anchor_tracker(app: AppHandle) {
+    let saved = load_settings(&app);
+    if let Ok(mut settings) = app.state::<AnchorState>().settings.lock() {
+        *settings = saved;
+    }
+
+    std::thread::spawn(move || {
+        let mut locator = RobloxWindowLocator::default();
+        let mut hidden_with_roblox = false;
+
+        loop {
+            std::thread::sleep(ANCHOR_POLL_INTERVAL);
+
+            let Some(window) = app.get_webview_window(crate::CHAT_WINDOW_LABEL) else {
+                continue;
+            };
+
+            let settings = match current_settings(&app) {
+                Ok(settings) if settings.enabled => settings,
+                _ => {
+                    if hidden_with_roblox {
+                        let _ = window.show();
+                        hidden_with_roblox = false;
+                    }
+                    continue;
+                }
+            };
+
+            let Some(roblox) = locator.bounds() else {
+                if hidden_with_roblox {
+                    let _ = window.show();
+                    hidden_with_roblox = false;
+                }
+                continue;
+            };
+
+            if let Err(err) =
+                follow_roblox_window(&window, &roblox, &settings, &mut hidden_with_roblox)
+            {
+                tracing::error!("Failed to follow Roblox window: {err:#}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBLOX: RobloxWindowBounds = RobloxWindowBounds {
+        x: 100,
+        y: 50,
+        width: 1280,
+        height: 720,
+        minimized: false,
+    };
+
+    fn settings(corner: AnchorCorner) -> AnchorSettings {
+        AnchorSettings {
+            enabled: true,
+            corner,
+            offset_x: 10,
+            offset_y: 20,
+        }
+    }
+
+    #[test]
+    fn anchored_position_insets_from_each_corner() {
+        assert_eq!(
+            anchored_position(&ROBLOX, 420, 520, &settings(AnchorCorner::TopLeft)),
+            (110, 70)
+        );
+        assert_eq!(
+            anchored_position(&ROBLOX, 420, 520, &settings(AnchorCorner::TopRight)),
+            (950, 70)
+        );
+        assert_eq!(
+            anchored_position(&ROBLOX, 420, 520, &settings(AnchorCorner::BottomLeft)),
+            (110, 230)
+        );
+        assert_eq!(
+            anchored_position(&ROBLOX, 420, 520, &settings(AnchorCorner::BottomRight)),
+            (950, 230)
+        );
+    }
+
+    #[test]
+    fn captured_offsets_round_trip() {
+        for corner in [
+            AnchorCorner::TopLeft,
+            AnchorCorner::TopRight,
+            AnchorCorner::BottomLeft,
+            AnchorCorner::BottomRight,
+        ] {
+            let (offset_x, offset_y) = offsets_for_position(&ROBLOX, 300, 200, 420, 520, corner);
+            let settings = AnchorSettings {
+                enabled: true,
+                corner,
+                offset_x,
+                offset_y,
+            };
+            assert_eq!(anchored_position(&ROBLOX, 420, 520, &settings), (300, 200));
+        }
+    }
+}
@@ -0,0 +1,654 @@
+use crate::chat_client::{ChatMessage, ChatUser};
+use crate::clock::now_ms;
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+const ARCHIVE_RETENTION_KEY: &str = "archiveRetention";
+const ARCHIVE_FILE: &str = "archive.sqlite3";
+const SCHEMA_VERSION: i32 = 1;
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// How long a Job ID whose join line wasn't in the Roblox log is left alone
+/// before the log is read again.
+const PLACE_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS messages (
+    id TEXT PRIMARY KEY,
+    channel TEXT NOT NULL,
+    place_id TEXT,
+    author_id TEXT NOT NULL,
+    author_username TEXT NOT NULL,
+    author_display_name TEXT NOT NULL,
+    author_picture TEXT NOT NULL,
+    content TEXT NOT NULL,
+    reply_to_id TEXT,
+    received_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS messages_channel ON messages(channel);
+CREATE INDEX IF NOT EXISTS messages_received_at ON messages(received_at);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+    content,
+    author_username,
+    author_display_name,
+    content = 'messages',
+    content_rowid = 'rowid'
+);
+
+CREATE TRIGGER IF NOT EXISTS messages_ai AFTER INSERT ON messages BEGIN
+    INSERT INTO messages_fts(rowid, content, author_username, author_display_name)
+    VALUES (new.rowid, new.content, new.author_username, new.author_display_name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS messages_ad AFTER DELETE ON messages BEGIN
+    INSERT INTO messages_fts(messages_fts, rowid, content, author_username, author_display_name)
+    VALUES ('delete', old.rowid, old.content, old.author_username, old.author_display_name);
+END;
+";
+
+const MESSAGE_COLUMNS: &str = "m.rowid, m.id, m.channel, m.place_id, m.author_id, \
+     m.author_username, m.author_display_name, m.author_picture, m.content, \
+     m.reply_to_id, m.received_at";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchivedMessage {
+    /// Pass as `before` to page further back.
+    cursor: i64,
+    pub(crate) channel: String,
+    pub(crate) place_id: Option<String>,
+    pub(crate) received_at: i64,
+    #[serde(flatten)]
+    pub(crate) message: ChatMessage,
+}
+
+#[cfg(test)]
+impl ArchivedMessage {
+    pub(crate) fn new(
+        channel: &str,
+        place_id: Option<&str>,
+        received_at: i64,
+        message: ChatMessage,
+    ) -> Self {
+        Self {
+            cursor: 0,
+            channel: channel.to_string(),
+            place_id: place_id.map(str::to_string),
+            received_at,
+            message,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchivePage {
+    /// Newest first.
+    messages: Vec<ArchivedMessage>,
+    next_before: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchivedChannel {
+    channel: String,
+    place_id: Option<String>,
+    message_count: u64,
+    last_received_at: i64,
+}
+
+/// Limits applied to the archive; `None` keeps everything.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveRetention {
+    max_age_days: Option<u32>,
+    max_messages: Option<u32>,
+}
+
+impl Default for ArchiveRetention {
+    fn default() -> Self {
+        Self {
+            max_age_days: Some(30),
+            max_messages: Some(100_000),
+        }
+    }
+}
+
+fn page_size(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Turns free text into an FTS5 query matching every word (the last one as a
+/// prefix), so user input can never be a syntax error.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", terms.join(" ")))
+}
+
+fn read_message(row: &rusqlite::Row<'_>) -> rusqlite::Result<ArchivedMessage> {
+    Ok(ArchivedMessage {
+        cursor: row.get(0)?,
+        channel: row.get(2)?,
+        place_id: row.get(3)?,
+        received_at: row.get(10)?,
+        message: ChatMessage {
+            id: row.get(1)?,
+            author: ChatUser {
+                roblox_user_id: row.get(4)?,
+                username: row.get(5)?,
+                display_name: row.get(6)?,
+                picture: row.get(7)?,
+            },
+            content: row.get(8)?,
+            reply_to_id: row.get(9)?,
+        },
+    })
+}
+
+pub(crate) struct Archive {
+    conn: Connection,
+}
+
+impl Archive {
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        Self::init(Connection::open(path).context("open chat archive")?)
+    }
+
+    #[cfg(test)]
+    fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .context("enable WAL")?;
+        let version: i32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .context("read archive version")?;
+        crate::storage::check_version("Chat archive", version, SCHEMA_VERSION)?;
+
+        conn.execute_batch(SCHEMA)
+            .context("create archive schema")?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .context("write archive version")?;
+        Ok(Self { conn })
+    }
+
+    /// Stores `messages`, skipping ones already archived. Returns how many
+    /// were new.
+    pub(crate) fn insert(
+        &mut self,
+        channel: &str,
+        place_id: Option<&str>,
+        messages: &[ChatMessage],
+        received_at: i64,
+    ) -> Result<usize> {
+        let tx = self.conn.transaction().context("begin archive write")?;
+        let mut inserted = 0;
+        {
+            let mut statement = tx.prepare_cached(
+                "INSERT OR IGNORE INTO messages (id, channel, place_id, author_id, \
+                 author_username, author_display_name, author_picture, content, \
+                 reply_to_id, received_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for message in messages {
+                inserted += statement.execute(params![
+                    message.id,
+                    channel,
+                    place_id,
+                    message.author.roblox_user_id,
+                    message.author.username,
+                    message.author.display_name,
+                    message.author.picture,
+                    message.content,
+                    message.reply_to_id,
+                    received_at,
+                ])?;
+            }
+        }
+        tx.commit().context("commit archive write")?;
+        Ok(inserted)
+    }
+
+    pub(crate) fn page(
+        &self,
+        channel: &str,
+        before: Option<i64>,
+        limit: Option<u32>,
+    ) -> Result<ArchivePage> {
+        let limit = page_size(limit);
+        let mut statement = self.conn.prepare_cached(&format!(
+            "SELECT {MESSAGE_COLUMNS} FROM messages m \
+             WHERE m.channel = ?1 AND m.rowid < ?2 \
+             ORDER BY m.rowid DESC LIMIT ?3"
+        ))?;
+        let messages = statement
+            .query_map(
+                params![channel, before.unwrap_or(i64::MAX), limit],
+                read_message,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let next_before = (messages.len() == limit as usize)
+            .then(|| messages.last().map(|message| message.cursor))
+            .flatten();
+        Ok(ArchivePage {
+            messages,
+            next_before,
+        })
+    }
+
+    /// Every message of `channel` received within `from..=to`, oldest first.
+    pub(crate) fn range(
+        &self,
+        channel: &str,
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> Result<Vec<ArchivedMessage>> {
+        let mut statement = self.conn.prepare_cached(&format!(
+            "SELECT {MESSAGE_COLUMNS} FROM messages m \
+             WHERE m.channel = ?1 AND m.received_at BETWEEN ?2 AND ?3 \
+             ORDER BY m.rowid ASC"
+        ))?;
+        let messages = statement
+            .query_map(
+                params![channel, from.unwrap_or(i64::MIN), to.unwrap_or(i64::MAX)],
+                read_message,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(messages)
+    }
+
+    pub(crate) fn search(
+        &self,
+        query: &str,
+        channel: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Vec<ArchivedMessage>> {
+        let Some(query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let mut statement = self.conn.prepare_cached(&format!(
+            "SELECT {MESSAGE_COLUMNS} FROM messages_fts \
+             JOIN messages m ON m.rowid = messages_fts.rowid \
+             WHERE messages_fts MATCH ?1 AND (?2 IS NULL OR m.channel = ?2) \
+             ORDER BY rank LIMIT ?3"
+        ))?;
+        let messages = statement
+            .query_map(params![query, channel, page_size(limit)], read_message)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(messages)
+    }
+
+    pub(crate) fn channels(&self) -> Result<Vec<ArchivedChannel>> {
+        let mut statement = self.conn.prepare_cached(
+            "SELECT channel, MAX(place_id), COUNT(*), MAX(received_at) FROM messages \
+             GROUP BY channel ORDER BY MAX(received_at) DESC",
+        )?;
+        let channels = statement
+            .query_map([], |row| {
+                Ok(ArchivedChannel {
+                    channel: row.get(0)?,
+                    place_id: row.get(1)?,
+                    message_count: row.get(2)?,
+                    last_received_at: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(channels)
+    }
+
+    pub(crate) fn delete_channel(&self, channel: &str) -> Result<usize> {
+        Ok(self
+            .conn
+            .execute("DELETE FROM messages WHERE channel = ?1", params![channel])?)
+    }
+
+    /// Deletes messages older than the age limit, then the oldest ones beyond
+    /// the count limit. Returns how many were removed.
+    pub(crate) fn apply_retention(&self, retention: &ArchiveRetention, now: i64) -> Result<usize> {
+        let mut removed = 0;
+        if let Some(days) = retention.max_age_days {
+            let cutoff = now - i64::from(days) * DAY_MS;
+            removed += self.conn.execute(
+                "DELETE FROM messages WHERE received_at < ?1",
+                params![cutoff],
+            )?;
+        }
+
+        if let Some(max_messages) = retention.max_messages {
+            let newest_to_drop: Option<i64> = self
+                .conn
+                .query_row(
+                    "SELECT rowid FROM messages ORDER BY rowid DESC LIMIT 1 OFFSET ?1",
+                    params![max_messages],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(rowid) = newest_to_drop {
+                removed += self
+                    .conn
+                    .execute("DELETE FROM messages WHERE rowid <= ?1", params![rowid])?;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+pub(crate) struct ArchiveState {
+    inner: Mutex<ArchiveInner>,
+    /// Place ids looked up in the Roblox logs, by Job ID. Kept apart from
+    /// the archive so reading the log doesn't hold it up.
+    places: Mutex<HashMap<String, PlaceLookup>>,
+}
+
+struct ArchiveInner {
+    archive: Option<Archive>,
+    retention: ArchiveRetention,
+    last_pruned: Instant,
+}
+
+enum PlaceLookup {
+    Found(String),
+    /// Not in the log when last read; the join line may not be written yet.
+    Missing(Instant),
+}
+
+impl Default for ArchiveState {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(ArchiveInner {
+                archive: None,
+                retention: ArchiveRetention::default(),
+                last_pruned: Instant::now(),
+            }),
+            places: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+fn lock_inner(app: &AppHandle) -> Result<std::sync::MutexGuard<'_, ArchiveInner>> {
+    app.state::<ArchiveState>()
+        .inner()
+        .inner
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock chat archive: {err}"))
+}
+
+fn with_archive<T>(app: &AppHandle, f: impl FnOnce(&mut Archive) -> Result<T>) -> Result<T> {
+    let mut inner = lock_inner(app)?;
+    let archive = inner
+        .archive
+        .as_mut()
+        .context("Chat archive is unavailable")?;
+    f(archive)
+}
+
+fn load_retention(app: &AppHandle) -> ArchiveRetention {
+    crate::storage::store(app)
+        .ok()
+        .and_then(|store| store.get(ARCHIVE_RETENTION_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Opens the archive in the data dir and applies the saved retention.
+pub(crate) fn open_archive(app: &AppHandle) -> Result<()> {
+    let dir = crate::storage::data_dir(app)?;
+    std::fs::create_dir_all(&dir).context("create app data dir")?;
+    let archive = Archive::open(&dir.join(ARCHIVE_FILE))?;
+    let retention = load_retention(app);
+    if let Err(err) = archive.apply_retention(&retention, now_ms() as i64) {
+        tracing::error!("Failed to prune chat archive: {err:#}");
+    }
+
+    let mut inner = lock_inner(app)?;
+    inner.archive = Some(archive);
+    inner.retention = retention;
+    inner.last_pruned = Instant::now();
+    Ok(())
+}
+
+fn place_id_for(app: &AppHandle, channel: &str) -> Option<String> {
+    if !crate::roblox::is_valid_job_id(channel) {
+        return None;
+    }
+    let places = &app.state::<ArchiveState>().inner().places;
+    match places.lock().ok()?.get(channel) {
+        Some(PlaceLookup::Found(place_id)) => return Some(place_id.clone()),
+        Some(PlaceLookup::Missing(at)) if at.elapsed() < PLACE_RETRY_INTERVAL => return None,
+        _ => {}
+    }
+
+    let state = app.state::<crate::roblox::LogSettingsState>();
+    let place_id = crate::roblox::get_place_id(&state, channel).ok().flatten();
+    let lookup = match &place_id {
+        Some(place_id) => PlaceLookup::Found(place_id.clone()),
+        None => PlaceLookup::Missing(Instant::now()),
+    };
+    places.lock().ok()?.insert(channel.to_string(), lookup);
+    place_id
+}
+
+pub(crate) fn archive_messages(
+    app: &AppHandle,
+    channel: &str,
+    messages: &[ChatMessage],
+) -> Result<usize> {
+    if !crate::roblox::is_valid_channel(channel) {
+        anyhow::bail!("Invalid channel: {channel}");
+    }
+
+    let place_id = place_id_for(app, channel);
+    let mut inner = lock_inner(app)?;
+    let prune = inner.last_pruned.elapsed() >= PRUNE_INTERVAL;
+    let retention = inner.retention.clone();
+    let archive = inner
+        .archive
+        .as_mut()
+        .context("Chat archive is unavailable")?;
+
+    let inserted = archive.insert(channel, place_id.as_deref(), messages, now_ms() as i64)?;
+    if prune {
+        archive.apply_retention(&retention, now_ms() as i64)?;
+        inner.last_pruned = Instant::now();
+    }
+    Ok(inserted)
+}
+
+pub(crate) fn get_archived_messages(
+    app: &AppHandle,
+    channel: &str,
+    before: Option<i64>,
+    limit: Option<u32>,
+) -> Result<ArchivePage> {
+    with_archive(app, |archive| archive.page(channel, before, limit))
+}
+
+pub(crate) fn archived_range(
+    app: &AppHandle,
+    channel: &str,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<Vec<ArchivedMessage>> {
+    with_archive(app, |archive| archive.range(channel, from, to))
+}
+
+pub(crate) fn search_archive(
+    app: &AppHandle,
+    query: &str,
+    channel: Option<&str>,
+    limit: Option<u32>,
+) -> Result<Vec<ArchivedMessage>> {
+    with_archive(app, |archive| archive.search(query, channel, limit))
+}
+
+pub(crate) fn list_archived_channels(app: &AppHandle) -> Result<Vec<ArchivedChannel>> {
+    with_archive(app, |archive| archive.channels())
+}
+
+pub(crate) fn delete_archived_channel(app: &AppHandle, channel: &str) -> Result<usize> {
+    with_archive(app, |archive| archive.delete_channel(channel))
+}
+
+pub(crate) fn get_archive_retention(app: &AppHandle) -> Result<ArchiveRetention> {
+    Ok(lock_inner(app)?.retention.clone())
+}
+
+pub(crate) fn set_archive_retention(
+    app: &AppHandle,
+    retention: ArchiveRetention,
+) -> Result<ArchiveRetention> {
+    let store = crate::storage::store(app).context("open settings store")?;
+    store.set(
+        ARCHIVE_RETENTION_KEY,
+        serde_json::to_value(&retention).context("serialize archive retention")?,
+    );
+
+    let mut inner = lock_inner(app)?;
+    inner.retention = retention.clone();
+    if let Some(archive) = inner.archive.as_ref() {
+        archive.apply_retention(&retention, now_ms() as i64)?;
+    }
+    inner.last_pruned = Instant::now();
+    Ok(retention)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::chat_client::test_message;
+
+    fn ids(messages: &[ArchivedMessage]) -> Vec<&str> {
+        messages
+            .iter()
+            .map(|message| message.message.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn stores_messages_once_and_pages_newest_first() {
+        let mut archive = Archive::open_in_memory().unwrap();
+        let batch: Vec<ChatMessage> = (1..=5)
+            .map(|index| test_message(&format!("m{index}"), "1", "hello"))
+            .collect();
+        assert_eq!(archive.insert("global", None, &batch, 1_000).unwrap(), 5);
+        assert_eq!(
+            archive.insert("global", None, &batch[..2], 2_000).unwrap(),
+            0
+        );
+        archive
+            .insert(
+                "deadbeef",
+                Some("606849621"),
+                &[test_message("x", "1", "other")],
+                3_000,
+            )
+            .unwrap();
+
+        let first = archive.page("global", None, Some(2)).unwrap();
+        assert_eq!(ids(&first.messages), vec!["m5", "m4"]);
+        let second = archive.page("global", first.next_before, Some(2)).unwrap();
+        assert_eq!(ids(&second.messages), vec!["m3", "m2"]);
+        let last = archive.page("global", second.next_before, Some(2)).unwrap();
+        assert_eq!(ids(&last.messages), vec!["m1"]);
+        assert_eq!(last.next_before, None);
+
+        let channels = archive.channels().unwrap();
+        assert_eq!(channels[0].channel, "deadbeef");
+        assert_eq!(channels[0].place_id.as_deref(), Some("606849621"));
+        assert_eq!(channels[1].message_count, 5);
+    }
+
+    #[test]
+    fn searches_with_fts() {
+        let mut archive = Archive::open_in_memory().unwrap();
+        archive
+            .insert(
+                "global",
+                None,
+                &[
+                    test_message("a", "1", "anyone trading limiteds?"),
+                    test_message("b", "1", "join my server"),
+                    test_message("c", "1", "\"quoted\" OR weird (syntax"),
+                ],
+                1_000,
+            )
+            .unwrap();
+        archive
+            .insert(
+                "deadbeef",
+                None,
+                &[test_message("d", "1", "trading here too")],
+                1_000,
+            )
+            .unwrap();
+
+        assert_eq!(ids(&archive.search("trad", None, None).unwrap()).len(), 2);
+        assert_eq!(
+            ids(&archive.search("trading", Some("global"), None).unwrap()),
+            vec!["a"]
+        );
+        assert_eq!(
+            ids(&archive.search("weird (syntax", None, None).unwrap()),
+            vec!["c"]
+        );
+        assert_eq!(
+            ids(&archive.search("user1", None, Some(1)).unwrap()).len(),
+            1
+        );
+        assert!(archive.search("   ", None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn deletes_channels_and_applies_retention() {
+        let mut archive = Archive::open_in_memory().unwrap();
+        archive
+            .insert("global", None, &[test_message("old", "1", "old news")], 0)
+            .unwrap();
+        let recent: Vec<ChatMessage> = (1..=4)
+            .map(|index| test_message(&format!("r{index}"), "1", "recent"))
+            .collect();
+        archive
+            .insert("global", None, &recent, 10 * DAY_MS)
+            .unwrap();
+        archive
+            .insert(
+                "deadbeef",
+                None,
+                &[test_message("s", "1", "server")],
+                10 * DAY_MS,
+            )
+            .unwrap();
+
+        assert_eq!(archive.delete_channel("deadbeef").unwrap(), 1);
+        assert!(archive.search("server", None, None).unwrap().is_empty());
+
+        let retention = ArchiveRetention {
+            max_age_days: Some(5),
+            max_messages: Some(2),
+        };
+        assert_eq!(archive.apply_retention(&retention, 10 * DAY_MS).unwrap(), 3);
+        let page = archive.page("global", None, None).unwrap();
+        assert_eq!(ids(&page.messages), vec!["r4", "r3"]);
+        assert!(archive.search("old", None, None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,446 @@
+use crate::clock::now_ms;
+use anyhow::{Context, Result};
+use base64::Engine;
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+const REFRESHED_EVENT: &str = "auth://refreshed";
+const EXPIRED_EVENT: &str = "auth://expired";
+/// How long before expiry the session is refreshed. The server allows four
+/// refreshes an hour and issues one-hour tokens.
+const REFRESH_AHEAD: Duration = Duration::from_secs(5 * 60);
+/// When a token has no readable expiry.
+const FALLBACK_REFRESH_INTERVAL: Duration = Duration::from_secs(45 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const INITIAL_RETRY_MS: u64 = 5_000;
+const MAX_RETRY_MS: u64 = 5 * 60_000;
+const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub(crate) enum AuthEvent {
+    /// The new session, as `auth.refresh` returned it.
+    Refreshed(Value),
+    /// The server no longer accepts the session; it has been cleared.
+    Expired { reason: String },
+}
+
+#[derive(Debug)]
+enum RefreshError {
+    Unauthorized(String),
+    RateLimited(Duration),
+    Failed(anyhow::Error),
+}
+
+/// Where the scheduler reads and writes the session, and where its events
+/// go.
+pub(crate) trait AuthRefreshEnv: Send + Sync + 'static {
+    fn api_url(&self) -> Result<String>;
+    fn session(&self) -> Option<Value>;
+    fn save_session(&self, session: &Value) -> Result<()>;
+    fn clear_session(&self);
+    fn emit(&self, event: AuthEvent);
+}
+
+/// The `exp` claim of a JWT in milliseconds, read without verifying it.
+fn token_expiry_ms(token: &str) -> Option<u64> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let claims: Value = serde_json::from_slice(&bytes).ok()?;
+    claims["exp"].as_u64()?.checked_mul(1000)
+}
+
+/// How long to wait before refreshing a token; zero once it is due.
+fn refresh_delay(expiry_ms: Option<u64>, now_ms: u64) -> Duration {
+    let Some(expiry_ms) = expiry_ms else {
+        return FALLBACK_REFRESH_INTERVAL;
+    };
+    let due_ms = expiry_ms.saturating_sub(REFRESH_AHEAD.as_millis() as u64);
+    Duration::from_millis(due_ms.saturating_sub(now_ms))
+}
+
+/// 5s, 10s, 20s, ... capped at five minutes.
+fn retry_delay(attempt: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempt.min(16));
+    Duration::from_millis(INITIAL_RETRY_MS.saturating_mul(factor).min(MAX_RETRY_MS))
+}
+
+/// Reads the wait out of "Rate limit hit. Try again in 12s."
+fn rate_limit_wait(message: &str) -> Duration {
+    message
+        .rsplit_once(" in ")
+        .and_then(|(_, rest)| rest.split('s').next())
+        .and_then(|seconds| seconds.trim().parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RATE_LIMIT_WAIT)
+}
+
+/// Calls `auth.refresh` the way the web client's HTTP link does.
+async fn request_refresh(
+    client: &reqwest::Client,
+    api_url: &str,
+    jwt: &str,
+) -> Result<Value, RefreshError> {
+    let response = client
+        .post(format!("{}/auth.refresh", api_url.trim_end_matches('/')))
+        .header(CONTENT_TYPE, "application/json")
+        .body(json!({ "jwt": jwt }).to_string())
+        .send()
+        .await
+        .context("send refresh request")
+        .map_err(RefreshError::Failed)?;
+    let status = response.status();
+    let body: Value = response
+        .text()
+        .await
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or(Value::Null);
+
+    if let Some(error) = body.get("error") {
+        let message = error["message"]
+            .as_str()
+            .unwrap_or("Refresh failed")
+            .to_string();
+        return Err(match error["data"]["code"].as_str() {
+            Some("UNAUTHORIZED") => RefreshError::Unauthorized(message),
+            Some("TOO_MANY_REQUESTS") => RefreshError::RateLimited(rate_limit_wait(&message)),
+            _ => RefreshError::Failed(anyhow::anyhow!("{message} ({status})")),
+        });
+    }
+    match status.as_u16() {
+        401 => return Err(RefreshError::Unauthorized("Invalid session".to_string())),
+        429 => return Err(RefreshError::RateLimited(DEFAULT_RATE_LIMIT_WAIT)),
+        _ if !status.is_success() => {
+            return Err(RefreshError::Failed(anyhow::anyhow!(
+                "Refresh failed with status {status}"
+            )))
+        }
+        _ => {}
+    }
+
+    let session = body["result"]["data"].clone();
+    if session["jwt"].as_str().is_none_or(str::is_empty) {
+        return Err(RefreshError::Failed(anyhow::anyhow!(
+            "Refresh response has no session"
+        )));
+    }
+    Ok(session)
+}
+
+fn session_jwt(session: &Value) -> Option<String> {
+    session["jwt"]
+        .as_str()
+        .filter(|jwt| !jwt.is_empty())
+        .map(str::to_string)
+}
+
+/// Keeps the saved session fresh until the process exits. `changed` is
+/// notified whenever the session is replaced or cleared from elsewhere.
+pub(crate) async fn run(env: Arc<dyn AuthRefreshEnv>, changed: Arc<Notify>) {
+    let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            tracing::error!("Failed to start auth refresh: {err}");
+            return;
+        }
+    };
+    // `(attempt, wait)` while retrying a failed refresh.
+    let mut retry: Option<(u32, Duration)> = None;
+
+    loop {
+        let Some(jwt) = env.session().as_ref().and_then(session_jwt) else {
+            retry = None;
+            changed.notified().await;
+            continue;
+        };
+
+        let delay = match retry {
+            Some((_, wait)) => wait,
+            None => refresh_delay(token_expiry_ms(&jwt), now_ms()),
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = changed.notified() => {
+                retry = None;
+                continue;
+            }
+        }
+
+        let result = match env.api_url() {
+            Ok(api_url) => request_refresh(&client, &api_url, &jwt).await,
+            Err(err) => Err(RefreshError::Failed(err)),
+        };
+        match result {
+            Ok(session) => {
+                retry = None;
+                if let Err(err) = env.save_session(&session) {
+                    tracing::error!("Failed to save refreshed session: {err:#}");
+                }
+                env.emit(AuthEvent::Refreshed(session));
+            }
+            Err(RefreshError::Unauthorized(reason)) => {
+                retry = None;
+                env.clear_session();
+                env.emit(AuthEvent::Expired { reason });
+            }
+            Err(RefreshError::RateLimited(wait)) => {
+                retry = Some((retry.map_or(0, |(attempt, _)| attempt), wait));
+            }
+            Err(RefreshError::Failed(err)) => {
+                let attempt = retry.map_or(0, |(attempt, _)| attempt + 1);
+                let wait = retry_delay(attempt);
+                tracing::warn!(
+                    "Auth refresh failed, retrying in {}s: {err:#}",
+                    wait.as_secs()
+                );
+                retry = Some((attempt, wait));
+            }
+        }
+    }
+}
+
+struct TauriAuthEnv {
+    app: AppHandle,
+}
+
+impl AuthRefreshEnv for TauriAuthEnv {
+    fn api_url(&self) -> Result<String> {
+        crate::chat_client::api_url(&self.app)
+    }
+
+    fn session(&self) -> Option<Value> {
+        crate::auth_store::auth_session(&self.app)
+    }
+
+    fn save_session(&self, session: &Value) -> Result<()> {
+        crate::auth_store::set_auth_session(&self.app, session.clone()).map(|_| ())
+    }
+
+    fn clear_session(&self) {
+        if let Err(err) = crate::auth_store::clear_auth_session(&self.app) {
+            tracing::error!("Failed to clear expired session: {err:#}");
+        }
+    }
+
+    fn emit(&self, event: AuthEvent) {
+        // Saving or clearing the session already reconnected chat.
+        let _ = match &event {
+            AuthEvent::Refreshed(_) => self.app.emit(REFRESHED_EVENT, &event),
+            AuthEvent::Expired { .. } => self.app.emit(EXPIRED_EVENT, &event),
+        };
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct AuthRefreshState {
+    changed: Arc<Notify>,
+}
+
+/// Wakes the scheduler so it reschedules for a new or cleared session.
+pub(crate) fn session_changed(app: &AppHandle) {
+    app.state::<AuthRefreshState>().changed.notify_one();
+}
+
+pub(crate) fn start(app: &AppHandle) {
+    let env = Arc::new(TauriAuthEnv { app: app.clone() });
+    let changed = app.state::<AuthRefreshState>().changed.clone();
+    tauri::async_runtime::spawn(run(env, changed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    fn token(expiry_secs: u64) -> String {
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(json!({ "robloxUserId": "1", "exp": expiry_secs }).to_string());
+        format!("eyJhbGciOiJIUzI1NiJ9.{payload}.signature")
+    }
+
+    /// An HTTP server answering each request with the next `(status, body)`
+    /// and reporting the request bodies it received.
+    async fn spawn_mock_api(
+        responses: Vec<(u16, Value)>,
+    ) -> (String, mpsc::UnboundedReceiver<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (requests, received) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                let body_start = loop {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                    if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                        break end + 4;
+                    }
+                };
+                let head = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+                assert!(head.starts_with("post /auth.refresh "), "{head}");
+                let length: usize = head
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map(|value| value.trim().parse().unwrap())
+                    .unwrap_or(0);
+                while request.len() < body_start + length {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                let _ = requests.send(serde_json::from_slice(&request[body_start..]).unwrap());
+
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, received)
+    }
+
+    fn trpc_error(code: &str, number: i64, message: &str) -> Value {
+        json!({ "error": { "message": message, "code": number, "data": { "code": code } } })
+    }
+
+    struct TestEnv {
+        url: String,
+        session: Mutex<Option<Value>>,
+        events: mpsc::UnboundedSender<AuthEvent>,
+    }
+
+    impl AuthRefreshEnv for TestEnv {
+        fn api_url(&self) -> Result<String> {
+            Ok(self.url.clone())
+        }
+
+        fn session(&self) -> Option<Value> {
+            self.session.lock().unwrap().clone()
+        }
+
+        fn save_session(&self, session: &Value) -> Result<()> {
+            *self.session.lock().unwrap() = Some(session.clone());
+            Ok(())
+        }
+
+        fn clear_session(&self) {
+            *self.session.lock().unwrap() = None;
+        }
+
+        fn emit(&self, event: AuthEvent) {
+            let _ = self.events.send(event);
+        }
+    }
+
+    #[test]
+    fn schedules_ahead_of_expiry() {
+        let now = 1_000_000_000;
+        let expiry = token_expiry_ms(&token(2_000_000)).unwrap();
+        assert_eq!(expiry, 2_000_000_000);
+        assert_eq!(
+            refresh_delay(Some(expiry), now),
+            Duration::from_millis(expiry - now) - REFRESH_AHEAD
+        );
+        assert_eq!(refresh_delay(Some(now), now), Duration::ZERO);
+        assert_eq!(refresh_delay(None, now), FALLBACK_REFRESH_INTERVAL);
+        assert_eq!(token_expiry_ms("not-a-jwt"), None);
+
+        assert_eq!(retry_delay(0), Duration::from_secs(5));
+        assert_eq!(retry_delay(2), Duration::from_secs(20));
+        assert_eq!(retry_delay(u32::MAX), Duration::from_secs(300));
+        assert_eq!(
+            rate_limit_wait("Rate limit hit. Try again in 12s."),
+            Duration::from_secs(12)
+        );
+    }
+
+    #[tokio::test]
+    async fn maps_server_responses() {
+        let fresh = json!({ "jwt": token(1), "user": { "robloxUserId": "1" } });
+        let (url, mut requests) = spawn_mock_api(vec![
+            (200, json!({ "result": { "data": fresh } })),
+            (401, trpc_error("UNAUTHORIZED", -32001, "Invalid session")),
+            (
+                429,
+                trpc_error(
+                    "TOO_MANY_REQUESTS",
+                    -32029,
+                    "Rate limit hit. Try again in 7s.",
+                ),
+            ),
+            (502, Value::Null),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+
+        assert_eq!(request_refresh(&client, &url, "old").await.unwrap(), fresh);
+        assert_eq!(requests.recv().await.unwrap(), json!({ "jwt": "old" }));
+        assert!(matches!(
+            request_refresh(&client, &url, "old").await,
+            Err(RefreshError::Unauthorized(reason)) if reason == "Invalid session"
+        ));
+        assert!(matches!(
+            request_refresh(&client, &url, "old").await,
+            Err(RefreshError::RateLimited(wait)) if wait == Duration::from_secs(7)
+        ));
+        assert!(matches!(
+            request_refresh(&client, &url, "old").await,
+            Err(RefreshError::Failed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn refreshes_due_sessions_and_reports_dead_ones() {
+        let later = now_ms() / 1000 + 3600;
+        let fresh = json!({ "jwt": token(later), "user": { "robloxUserId": "1" } });
+        let (url, mut requests) = spawn_mock_api(vec![
+            (200, json!({ "result": { "data": fresh } })),
+            (401, trpc_error("UNAUTHORIZED", -32001, "Invalid session")),
+        ])
+        .await;
+        let (events, mut received) = mpsc::unbounded_channel();
+        let env = Arc::new(TestEnv {
+            url,
+            session: Mutex::new(Some(json!({ "jwt": token(1) }))),
+            events,
+        });
+        let changed = Arc::new(Notify::new());
+        tokio::spawn(run(env.clone(), changed.clone()));
+
+        assert_eq!(
+            received.recv().await.unwrap(),
+            AuthEvent::Refreshed(fresh.clone())
+        );
+        assert_eq!(requests.recv().await.unwrap(), json!({ "jwt": token(1) }));
+        assert_eq!(env.session(), Some(fresh));
+
+        // A session replaced from elsewhere is picked up straight away.
+        env.save_session(&json!({ "jwt": token(2) })).unwrap();
+        changed.notify_one();
+        assert_eq!(
+            received.recv().await.unwrap(),
+            AuthEvent::Expired {
+                reason: "Invalid session".to_string()
+            }
+        );
+        assert_eq!(requests.recv().await.unwrap(), json!({ "jwt": token(2) }));
+        assert_eq!(env.session(), None);
+    }
+}
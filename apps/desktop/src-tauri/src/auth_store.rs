@@ -0,0 +1,350 @@
+use anyhow::{Context, Result};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Where the session lived before it moved here.
+const LEGACY_AUTH_KEY: &str = "auth";
+const KEYRING_SERVICE: &str = "com.logix.bloxchat-desktop";
+const KEYRING_USER: &str = "auth-session";
+const SESSION_FILE_STEM: &str = "auth-session";
+const FILE_MAGIC: &[u8; 4] = b"BXA1";
+const NONCE_LEN: usize = 12;
+/// Mixed into the machine secret so the key is only used for this file.
+const KEY_CONTEXT: &[u8] = b"bloxchat auth session v1";
+
+/// Where the session ended up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum AuthStorage {
+    /// Keychain, Credential Manager or the Secret Service.
+    OsCredentialStore,
+    /// Encrypted with a key derived from this machine's ID.
+    EncryptedFile,
+}
+
+#[derive(Default)]
+pub(crate) struct AuthStoreState {
+    /// `None` until the session was first read.
+    cached: Mutex<Option<Option<Value>>>,
+}
+
+#[cfg(target_os = "linux")]
+fn machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+#[cfg(target_os = "macos")]
+fn machine_id() -> Option<String> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("IOPlatformUUID"))?
+        .split('"')
+        .nth(3)
+        .map(str::to_string)
+}
+
+#[cfg(target_os = "windows")]
+fn machine_id() -> Option<String> {
+    use windows::core::w;
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Registry::{
+        RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ, RRF_SUBKEY_WOW6464KEY,
+    };
+
+    let mut buffer = [0u16; 64];
+    let mut size = std::mem::size_of_val(&buffer) as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            w!("SOFTWARE\\Microsoft\\Cryptography"),
+            w!("MachineGuid"),
+            RRF_RT_REG_SZ | RRF_SUBKEY_WOW6464KEY,
+            None,
+            Some(buffer.as_mut_ptr().cast()),
+            Some(&mut size),
+        )
+    };
+    if status != ERROR_SUCCESS {
+        return None;
+    }
+    let len = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn machine_id() -> Option<String> {
+    None
+}
+
+fn file_key(machine_secret: &[u8]) -> Key {
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_CONTEXT);
+    hasher.update(machine_secret);
+    hasher.finalize()
+}
+
+fn seal(machine_secret: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new(&file_key(machine_secret));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow::anyhow!("encrypt auth session"))?;
+
+    let mut sealed = Vec::with_capacity(FILE_MAGIC.len() + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(FILE_MAGIC);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+fn unseal(machine_secret: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    let body = sealed
+        .strip_prefix(FILE_MAGIC)
+        .context("Not a BloxChat auth session file")?;
+    if body.len() < NONCE_LEN {
+        anyhow::bail!("Auth session file is truncated");
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(&file_key(machine_secret))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Auth session file was made on another machine or is damaged"))
+}
+
+/// Each profile signs in separately; the default one keeps the original
+/// credential names.
+fn credential_name(base: &str, profile: &str) -> String {
+    if profile == crate::profiles::DEFAULT_PROFILE {
+        base.to_string()
+    } else {
+        format!("{base}.{profile}")
+    }
+}
+
+fn session_file(app: &AppHandle, profile: &str) -> Result<PathBuf> {
+    Ok(crate::storage::data_dir(app)?.join(format!(
+        "{}.bin",
+        credential_name(SESSION_FILE_STEM, profile)
+    )))
+}
+
+fn machine_secret() -> Result<Vec<u8>> {
+    machine_id()
+        .map(String::into_bytes)
+        .context("No machine ID to encrypt the auth session with")
+}
+
+fn read_file(app: &AppHandle, profile: &str) -> Result<Option<Value>> {
+    let path = session_file(app, profile)?;
+    let sealed = match std::fs::read(&path) {
+        Ok(sealed) => sealed,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).context("read auth session file"),
+    };
+    let plaintext = unseal(&machine_secret()?, &sealed)?;
+    serde_json::from_slice(&plaintext)
+        .map(Some)
+        .context("parse auth session")
+}
+
+fn write_file(app: &AppHandle, profile: &str, json: &str) -> Result<()> {
+    let path = session_file(app, profile)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).context("create app data dir")?;
+    }
+    let sealed = seal(&machine_secret()?, json.as_bytes())?;
+    std::fs::write(&path, sealed).context("write auth session file")
+}
+
+fn remove_file(app: &AppHandle, profile: &str) -> Result<()> {
+    match std::fs::remove_file(session_file(app, profile)?) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            Err(err).context("remove auth session file")
+        }
+        _ => Ok(()),
+    }
+}
+
+fn keyring_entry(profile: &str) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, &credential_name(KEYRING_USER, profile))
+}
+
+// Portable installs keep the session beside the executable too.
+fn read_session(app: &AppHandle, profile: &str) -> Result<Option<Value>> {
+    if crate::storage::is_portable() {
+        return read_file(app, profile);
+    }
+    match keyring_entry(profile).and_then(|entry| entry.get_password()) {
+        Ok(json) => {
+            return serde_json::from_str(&json)
+                .map(Some)
+                .context("parse auth session")
+        }
+        Err(keyring::Error::NoEntry) => {}
+        Err(err) => tracing::warn!("OS credential store unavailable, using encrypted file: {err}"),
+    }
+    read_file(app, profile)
+}
+
+fn write_session(app: &AppHandle, profile: &str, session: &Value) -> Result<AuthStorage> {
+    let json = serde_json::to_string(session).context("serialize auth session")?;
+    if crate::storage::is_portable() {
+        write_file(app, profile, &json)?;
+        return Ok(AuthStorage::EncryptedFile);
+    }
+    match keyring_entry(profile).and_then(|entry| entry.set_password(&json)) {
+        Ok(()) => {
+            remove_file(app, profile)?;
+            Ok(AuthStorage::OsCredentialStore)
+        }
+        Err(err) => {
+            tracing::warn!("OS credential store unavailable, using encrypted file: {err}");
+            write_file(app, profile, &json)?;
+            Ok(AuthStorage::EncryptedFile)
+        }
+    }
+}
+
+fn delete_session(app: &AppHandle, profile: &str) -> Result<()> {
+    if crate::storage::is_portable() {
+        return remove_file(app, profile);
+    }
+    match keyring_entry(profile).and_then(|entry| entry.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(err) => tracing::error!("Failed to clear OS credential store: {err}"),
+    }
+    remove_file(app, profile)
+}
+
+fn lock_cache(app: &AppHandle) -> Result<std::sync::MutexGuard<'_, Option<Option<Value>>>> {
+    app.state::<AuthStoreState>()
+        .inner()
+        .cached
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock auth session: {err}"))
+}
+
+pub(crate) fn get_auth_session(app: &AppHandle) -> Result<Option<Value>> {
+    let mut cached = lock_cache(app)?;
+    if let Some(session) = cached.as_ref() {
+        return Ok(session.clone());
+    }
+    let session = read_session(app, &crate::profiles::active_profile(app))?;
+    *cached = Some(session.clone());
+    Ok(session)
+}
+
+/// Saves the verified session; `null` clears it.
+pub(crate) fn set_auth_session(app: &AppHandle, session: Value) -> Result<Option<AuthStorage>> {
+    if session.is_null() {
+        clear_auth_session(app)?;
+        return Ok(None);
+    }
+    let mut cached = lock_cache(app)?;
+    let storage = write_session(app, &crate::profiles::active_profile(app), &session)?;
+    *cached = Some(Some(session));
+    drop(cached);
+    session_changed(app);
+    Ok(Some(storage))
+}
+
+pub(crate) fn clear_auth_session(app: &AppHandle) -> Result<()> {
+    let mut cached = lock_cache(app)?;
+    delete_session(app, &crate::profiles::active_profile(app))?;
+    *cached = Some(None);
+    drop(cached);
+    session_changed(app);
+    Ok(())
+}
+
+/// Restarts the refresh timer and reconnects chat so it sends the new token.
+fn session_changed(app: &AppHandle) {
+    crate::auth_refresh::session_changed(app);
+    if let Err(err) = crate::chat_client::reconnect_if_open(app) {
+        tracing::error!("Failed to reconnect chat after sign-in change: {err:#}");
+    }
+}
+
+/// Drops the cached session after the active profile changed.
+pub(crate) fn profile_switched(app: &AppHandle) -> Result<()> {
+    *lock_cache(app)? = None;
+    crate::auth_refresh::session_changed(app);
+    Ok(())
+}
+
+/// Removes the session saved for a profile that is being deleted.
+pub(crate) fn delete_profile_session(app: &AppHandle, profile: &str) -> Result<()> {
+    delete_session(app, profile)
+}
+
+/// The saved session, for Rust callers that treat a missing or unreadable
+/// one alike.
+pub(crate) fn auth_session(app: &AppHandle) -> Option<Value> {
+    get_auth_session(app)
+        .inspect_err(|err| tracing::error!("Failed to read auth session: {err:#}"))
+        .ok()
+        .flatten()
+}
+
+/// Moves a session left in `store.json` by older versions into secure
+/// storage.
+pub(crate) fn migrate_from_store(app: &AppHandle) -> Result<()> {
+    let store = crate::storage::store(app).context("open settings store")?;
+    let Some(legacy) = store.get(LEGACY_AUTH_KEY) else {
+        return Ok(());
+    };
+
+    if !legacy.is_null() {
+        set_auth_session(app, legacy)?;
+    }
+    store.delete(LEGACY_AUTH_KEY);
+    store.save().context("save settings store")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_sessions_only_open_on_the_same_machine() {
+        let session = br#"{"jwt":"header.payload.signature","user":{"robloxUserId":"1"}}"#;
+        let sealed = seal(b"machine-a", session).unwrap();
+
+        assert!(sealed.starts_with(FILE_MAGIC));
+        assert!(!sealed
+            .windows(b"signature".len())
+            .any(|window| window == b"signature"));
+        assert_eq!(unseal(b"machine-a", &sealed).unwrap(), session);
+        assert!(unseal(b"machine-b", &sealed).is_err());
+        assert_ne!(seal(b"machine-a", session).unwrap(), sealed);
+    }
+
+    #[test]
+    fn rejects_damaged_files() {
+        let mut sealed = seal(b"machine-a", b"{}").unwrap();
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(unseal(b"machine-a", &sealed).is_err());
+        assert!(unseal(b"machine-a", b"BXA1short").is_err());
+        assert!(unseal(b"machine-a", b"{\"jwt\":\"plain\"}").is_err());
+    }
+
+    #[test]
+    fn profiles_get_their_own_credentials() {
+        assert_eq!(credential_name(KEYRING_USER, "default"), "auth-session");
+        assert_eq!(credential_name(KEYRING_USER, "alt"), "auth-session.alt");
+    }
+}
@@ -0,0 +1,1045 @@
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+pub(crate) const MESSAGES_EVENT: &str = "chat://messages";
+const CONNECTION_EVENT: &str = "chat://connection";
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_BATCH_SIZE: usize = 50;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const INITIAL_BACKOFF_MS: u64 = 1_000;
+const MAX_BACKOFF_MS: u64 = 30_000;
+
+type Socket = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChatUser {
+    pub(crate) roblox_user_id: String,
+    pub(crate) username: String,
+    pub(crate) display_name: String,
+    pub(crate) picture: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChatMessage {
+    pub(crate) id: String,
+    pub(crate) author: ChatUser,
+    pub(crate) content: String,
+    #[serde(default)]
+    pub(crate) reply_to_id: Option<String>,
+}
+
+/// A message from `user{author_id}`, for tests.
+#[cfg(test)]
+pub(crate) fn test_message(id: &str, author_id: &str, content: &str) -> ChatMessage {
+    ChatMessage {
+        id: id.to_string(),
+        author: ChatUser {
+            roblox_user_id: author_id.to_string(),
+            username: format!("user{author_id}"),
+            display_name: format!("User {author_id}"),
+            picture: String::new(),
+        },
+        content: content.to_string(),
+        reply_to_id: None,
+    }
+}
+
+#[cfg(test)]
+impl ChatMessage {
+    pub(crate) fn replying_to(self, id: &str) -> ChatMessage {
+        ChatMessage {
+            reply_to_id: Some(id.to_string()),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChatLimits {
+    max_message_length: u32,
+    rate_limit_count: u32,
+    rate_limit_window_ms: u64,
+}
+
+/// Messages received on one channel since the last flush.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChatMessageBatch {
+    pub(crate) channel: String,
+    pub(crate) messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub(crate) enum ConnectionStatus {
+    Connecting {
+        attempt: u32,
+    },
+    Connected,
+    #[serde(rename_all = "camelCase")]
+    Reconnecting {
+        attempt: u32,
+        retry_in_ms: u64,
+    },
+    Disconnected,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ChatClientEvent {
+    Messages(ChatMessageBatch),
+    Connection(ConnectionStatus),
+}
+
+/// Where the client gets its server and credentials from, and where its
+/// events go. Read on every (re)connect so setting changes apply.
+pub(crate) trait ChatClientEnv: Send + Sync + 'static {
+    fn ws_url(&self) -> Result<String>;
+    fn auth_token(&self) -> Option<String>;
+    fn emit(&self, event: ChatClientEvent);
+}
+
+enum Command {
+    Subscribe(String),
+    Unsubscribe(String),
+    Request {
+        method: &'static str,
+        path: &'static str,
+        input: Value,
+        reply: oneshot::Sender<Result<Value>>,
+    },
+    Reconnect,
+    Shutdown,
+}
+
+enum SessionEnd {
+    Lost,
+    Reconnect,
+    Shutdown,
+}
+
+/// Same schedule as the tRPC web client: 1s, 2s, 4s, ... capped at 30s.
+fn backoff_delay(attempt: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempt.min(16));
+    Duration::from_millis(
+        INITIAL_BACKOFF_MS
+            .saturating_mul(factor)
+            .min(MAX_BACKOFF_MS),
+    )
+}
+
+pub(crate) fn to_ws_url(api_url: &str) -> String {
+    let lower = api_url.to_ascii_lowercase();
+    if lower.starts_with("https") {
+        format!("wss{}", &api_url[5..])
+    } else if lower.starts_with("http") {
+        format!("ws{}", &api_url[4..])
+    } else {
+        api_url.to_string()
+    }
+}
+
+fn request_frame(id: u64, method: &str, path: &str, input: Value) -> Value {
+    json!({
+        "id": id,
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": { "path": path, "input": input },
+    })
+}
+
+fn stop_frame(id: u64) -> Value {
+    json!({ "id": id, "jsonrpc": "2.0", "method": "subscription.stop" })
+}
+
+fn error_message(error: &Value) -> String {
+    let message = error
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or("Request failed");
+    match error
+        .get("data")
+        .and_then(|data| data.get("code"))
+        .and_then(Value::as_str)
+    {
+        Some(code) => format!("{code}: {message}"),
+        None => message.to_string(),
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ChatClient {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+impl ChatClient {
+    pub(crate) fn spawn(env: Arc<dyn ChatClientEnv>) -> Self {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let worker = Worker {
+            env,
+            commands: receiver,
+            channels: BTreeSet::new(),
+            attempt: 0,
+        };
+        tauri::async_runtime::spawn(worker.run());
+        Self { commands }
+    }
+
+    fn send(&self, command: Command) -> Result<()> {
+        self.commands
+            .send(command)
+            .map_err(|_| anyhow::anyhow!("Chat client has stopped"))
+    }
+
+    pub(crate) fn subscribe(&self, channel: &str) -> Result<()> {
+        self.send(Command::Subscribe(channel.to_string()))
+    }
+
+    pub(crate) fn unsubscribe(&self, channel: &str) -> Result<()> {
+        self.send(Command::Unsubscribe(channel.to_string()))
+    }
+
+    pub(crate) fn reconnect(&self) -> Result<()> {
+        self.send(Command::Reconnect)
+    }
+
+    pub(crate) fn shutdown(&self) {
+        let _ = self.send(Command::Shutdown);
+    }
+
+    async fn request(
+        &self,
+        method: &'static str,
+        path: &'static str,
+        input: Value,
+    ) -> Result<Value> {
+        let (reply, response) = oneshot::channel();
+        self.send(Command::Request {
+            method,
+            path,
+            input,
+            reply,
+        })?;
+
+        tokio::time::timeout(REQUEST_TIMEOUT, response)
+            .await
+            .context("Chat server did not respond")?
+            .map_err(|_| anyhow::anyhow!("Chat client has stopped"))?
+    }
+
+    pub(crate) async fn publish(
+        &self,
+        channel: &str,
+        content: &str,
+        reply_to_id: Option<&str>,
+    ) -> Result<ChatMessage> {
+        let data = self
+            .request(
+                "mutation",
+                "chat.publish",
+                json!({ "channel": channel, "content": content, "replyToId": reply_to_id }),
+            )
+            .await?;
+        serde_json::from_value(data).context("parse published message")
+    }
+
+    pub(crate) async fn limits(&self, channel: &str) -> Result<ChatLimits> {
+        let data = self
+            .request("query", "chat.limits", json!({ "channel": channel }))
+            .await?;
+        serde_json::from_value(data).context("parse chat limits")
+    }
+}
+
+struct Worker {
+    env: Arc<dyn ChatClientEnv>,
+    commands: mpsc::UnboundedReceiver<Command>,
+    channels: BTreeSet<String>,
+    attempt: u32,
+}
+
+impl Worker {
+    async fn run(mut self) {
+        loop {
+            self.env
+                .emit(ChatClientEvent::Connection(ConnectionStatus::Connecting {
+                    attempt: self.attempt,
+                }));
+
+            let end = match self.connect().await {
+                Ok(socket) => {
+                    self.attempt = 0;
+                    self.env
+                        .emit(ChatClientEvent::Connection(ConnectionStatus::Connected));
+                    Session::new(&mut self).serve(socket).await
+                }
+                Err(err) => {
+                    tracing::error!("Failed to connect to chat server: {err:#}");
+                    SessionEnd::Lost
+                }
+            };
+
+            match end {
+                SessionEnd::Shutdown => break,
+                SessionEnd::Reconnect => continue,
+                SessionEnd::Lost => {}
+            }
+
+            let delay = backoff_delay(self.attempt);
+            self.attempt = self.attempt.saturating_add(1);
+            self.env.emit(ChatClientEvent::Connection(
+                ConnectionStatus::Reconnecting {
+                    attempt: self.attempt,
+                    retry_in_ms: delay.as_millis() as u64,
+                },
+            ));
+            if let SessionEnd::Shutdown = self.wait_for_retry(delay).await {
+                break;
+            }
+        }
+
+        self.env
+            .emit(ChatClientEvent::Connection(ConnectionStatus::Disconnected));
+    }
+
+    async fn connect(&self) -> Result<Socket> {
+        let url = self.env.ws_url()?;
+        let mut request = url
+            .as_str()
+            .into_client_request()
+            .with_context(|| format!("Invalid chat server URL: {url}"))?;
+        if let Some(token) = self.env.auth_token() {
+            let value =
+                HeaderValue::from_str(&format!("Bearer {token}")).context("Invalid auth token")?;
+            request.headers_mut().insert("Authorization", value);
+        }
+
+        let (socket, _) = tokio_tungstenite::connect_async(request)
+            .await
+            .with_context(|| format!("connect to {url}"))?;
+        Ok(socket)
+    }
+
+    /// Sleeps until the next attempt while still tracking subscriptions, so
+    /// they are restored once the connection is back.
+    async fn wait_for_retry(&mut self, delay: Duration) -> SessionEnd {
+        let sleep = tokio::time::sleep(delay);
+        tokio::pin!(sleep);
+
+        loop {
+            tokio::select! {
+                _ = &mut sleep => return SessionEnd::Lost,
+                command = self.commands.recv() => match command {
+                    None | Some(Command::Shutdown) => return SessionEnd::Shutdown,
+                    Some(Command::Reconnect) => return SessionEnd::Reconnect,
+                    Some(Command::Subscribe(channel)) => {
+                        self.channels.insert(channel);
+                    }
+                    Some(Command::Unsubscribe(channel)) => {
+                        self.channels.remove(&channel);
+                    }
+                    Some(Command::Request { reply, .. }) => {
+                        let _ = reply.send(Err(anyhow::anyhow!("Not connected to chat server")));
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// One WebSocket connection. Request ids restart with every connection.
+struct Session<'a> {
+    worker: &'a mut Worker,
+    next_id: u64,
+    subscriptions: HashMap<u64, String>,
+    pending: HashMap<u64, oneshot::Sender<Result<Value>>>,
+    batches: BTreeMap<String, Vec<ChatMessage>>,
+}
+
+impl<'a> Session<'a> {
+    fn new(worker: &'a mut Worker) -> Self {
+        Self {
+            worker,
+            next_id: 1,
+            subscriptions: HashMap::new(),
+            pending: HashMap::new(),
+            batches: BTreeMap::new(),
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn subscription_frame(&mut self, channel: &str) -> Value {
+        let id = self.next_id();
+        self.subscriptions.insert(id, channel.to_string());
+        request_frame(
+            id,
+            "subscription",
+            "chat.subscribe",
+            json!({ "channel": channel }),
+        )
+    }
+
+    async fn serve(mut self, socket: Socket) -> SessionEnd {
+        let (mut sink, mut stream) = socket.split();
+
+        let channels: Vec<String> = self.worker.channels.iter().cloned().collect();
+        if !channels.is_empty() {
+            let frames: Vec<Value> = channels
+                .iter()
+                .map(|channel| self.subscription_frame(channel))
+                .collect();
+            if sink
+                .send(Message::text(Value::Array(frames).to_string()))
+                .await
+                .is_err()
+            {
+                return SessionEnd::Lost;
+            }
+        }
+
+        let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+        let end = loop {
+            let outgoing = tokio::select! {
+                frame = stream.next() => match frame {
+                    Some(Ok(Message::Text(text))) => {
+                        if text.as_str() == "PING" {
+                            Some(Message::text("PONG"))
+                        } else if self.handle_text(text.as_str()) {
+                            break SessionEnd::Reconnect;
+                        } else {
+                            None
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break SessionEnd::Lost,
+                    Some(Ok(_)) => None,
+                    Some(Err(err)) => {
+                        tracing::warn!("Chat connection error: {err}");
+                        break SessionEnd::Lost;
+                    }
+                },
+                command = self.worker.commands.recv() => match command {
+                    None | Some(Command::Shutdown) => {
+                        let _ = sink.send(Message::Close(None)).await;
+                        break SessionEnd::Shutdown;
+                    }
+                    Some(Command::Reconnect) => break SessionEnd::Reconnect,
+                    Some(Command::Subscribe(channel)) => {
+                        if self.worker.channels.insert(channel.clone()) {
+                            Some(Message::text(self.subscription_frame(&channel).to_string()))
+                        } else {
+                            None
+                        }
+                    }
+                    Some(Command::Unsubscribe(channel)) => {
+                        self.worker.channels.remove(&channel);
+                        let id = self
+                            .subscriptions
+                            .iter()
+                            .find(|(_, subscribed)| **subscribed == channel)
+                            .map(|(id, _)| *id);
+                        id.map(|id| {
+                            self.subscriptions.remove(&id);
+                            Message::text(stop_frame(id).to_string())
+                        })
+                    }
+                    Some(Command::Request { method, path, input, reply }) => {
+                        let id = self.next_id();
+                        self.pending.insert(id, reply);
+                        Some(Message::text(request_frame(id, method, path, input).to_string()))
+                    }
+                },
+                _ = flush.tick() => {
+                    self.flush_batches();
+                    None
+                }
+            };
+
+            if let Some(message) = outgoing {
+                if let Err(err) = sink.send(message).await {
+                    tracing::warn!("Chat connection error: {err}");
+                    break SessionEnd::Lost;
+                }
+            }
+        };
+
+        self.flush_batches();
+        for (_, reply) in self.pending.drain() {
+            let _ = reply.send(Err(anyhow::anyhow!("Chat connection lost")));
+        }
+        end
+    }
+
+    /// Handles one text frame, which may hold a single response or a batch.
+    /// Returns true when the server asked clients to reconnect.
+    fn handle_text(&mut self, text: &str) -> bool {
+        let Ok(value) = serde_json::from_str::<Value>(text) else {
+            tracing::warn!("Ignoring malformed chat frame");
+            return false;
+        };
+
+        match value {
+            Value::Array(responses) => responses.into_iter().fold(false, |reconnect, response| {
+                self.handle_response(response) || reconnect
+            }),
+            response => self.handle_response(response),
+        }
+    }
+
+    fn handle_response(&mut self, response: Value) -> bool {
+        if response.get("method").and_then(Value::as_str) == Some("reconnect") {
+            return true;
+        }
+
+        let Some(id) = response.get("id").and_then(Value::as_u64) else {
+            return false;
+        };
+
+        if let Some(error) = response.get("error") {
+            let message = error_message(error);
+            if let Some(reply) = self.pending.remove(&id) {
+                let _ = reply.send(Err(anyhow::anyhow!(message)));
+            } else if let Some(channel) = self.subscriptions.remove(&id) {
+                tracing::error!("Chat subscription to {channel} failed: {message}");
+            }
+            return false;
+        }
+
+        let Some(result) = response.get("result") else {
+            return false;
+        };
+        let kind = result.get("type").and_then(Value::as_str).unwrap_or("data");
+        let data = result.get("data").cloned().unwrap_or(Value::Null);
+
+        if let Some(reply) = self.pending.remove(&id) {
+            let _ = reply.send(Ok(data));
+            return false;
+        }
+
+        match kind {
+            "data" => {
+                let Some(channel) = self.subscriptions.get(&id).cloned() else {
+                    return false;
+                };
+                match serde_json::from_value::<ChatMessage>(data) {
+                    Ok(message) => {
+                        let batch = self.batches.entry(channel).or_default();
+                        batch.push(message);
+                        if batch.len() >= MAX_BATCH_SIZE {
+                            self.flush_batches();
+                        }
+                    }
+                    Err(err) => tracing::warn!("Ignoring malformed chat message: {err}"),
+                }
+            }
+            "stopped" => {
+                self.subscriptions.remove(&id);
+            }
+            _ => {}
+        }
+
+        false
+    }
+
+    fn flush_batches(&mut self) {
+        for (channel, messages) in std::mem::take(&mut self.batches) {
+            if !messages.is_empty() {
+                self.worker
+                    .env
+                    .emit(ChatClientEvent::Messages(ChatMessageBatch {
+                        channel,
+                        messages,
+                    }));
+            }
+        }
+    }
+}
+
+/// The API URL from the settings store, normalized like the web client does.
+pub(crate) fn api_url(app: &AppHandle) -> Result<String> {
+    Ok(crate::settings::get_settings(app)?.api_url)
+}
+
+struct TauriChatEnv {
+    app: AppHandle,
+}
+
+impl ChatClientEnv for TauriChatEnv {
+    fn ws_url(&self) -> Result<String> {
+        Ok(to_ws_url(&api_url(&self.app)?))
+    }
+
+    fn auth_token(&self) -> Option<String> {
+        crate::auth_store::auth_session(&self.app)
+            .and_then(|auth| auth.get("jwt").and_then(Value::as_str).map(str::to_string))
+    }
+
+    fn emit(&self, event: ChatClientEvent) {
+        let _ = match event {
+            ChatClientEvent::Messages(batch) => {
+                if let Err(err) =
+                    crate::archive::archive_messages(&self.app, &batch.channel, &batch.messages)
+                {
+                    tracing::error!("Failed to archive messages: {err:#}");
+                }
+                crate::session::record_messages(&self.app, &batch);
+                let Some(moderated) = crate::moderation::moderate(&self.app, batch) else {
+                    return;
+                };
+                crate::notifications::process_batch(&self.app, &moderated.batch);
+                self.app.emit(MESSAGES_EVENT, moderated)
+            }
+            ChatClientEvent::Connection(status) => self.app.emit(CONNECTION_EVENT, status),
+        };
+    }
+}
+
+/// The app's chat connection, started on first use.
+#[derive(Default)]
+pub(crate) struct ChatClientState {
+    client: Mutex<Option<ChatClient>>,
+    /// Labels of the windows showing each channel; the connection stays
+    /// subscribed while any of them does.
+    viewers: Mutex<BTreeMap<String, BTreeSet<String>>>,
+}
+
+fn lock_viewers(
+    app: &AppHandle,
+) -> Result<std::sync::MutexGuard<'_, BTreeMap<String, BTreeSet<String>>>> {
+    app.state::<ChatClientState>()
+        .inner()
+        .viewers
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock chat viewers: {err}"))
+}
+
+/// Records that `label` shows `channel`; true if it is the first window to.
+fn add_viewer(
+    viewers: &mut BTreeMap<String, BTreeSet<String>>,
+    channel: &str,
+    label: &str,
+) -> bool {
+    let labels = viewers.entry(channel.to_string()).or_default();
+    labels.insert(label.to_string()) && labels.len() == 1
+}
+
+/// Records that `label` stopped showing `channel`; true if no window does
+/// any more.
+fn remove_viewer(
+    viewers: &mut BTreeMap<String, BTreeSet<String>>,
+    channel: &str,
+    label: &str,
+) -> bool {
+    let Some(labels) = viewers.get_mut(channel) else {
+        return false;
+    };
+    if !labels.remove(label) || !labels.is_empty() {
+        return false;
+    }
+    viewers.remove(channel);
+    true
+}
+
+fn client(app: &AppHandle) -> Result<ChatClient> {
+    let state = app.state::<ChatClientState>();
+    let mut client = state
+        .client
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock chat client: {err}"))?;
+    Ok(client
+        .get_or_insert_with(|| ChatClient::spawn(Arc::new(TauriChatEnv { app: app.clone() })))
+        .clone())
+}
+
+fn validate_channel(channel: &str) -> Result<()> {
+    if !crate::roblox::is_valid_channel(channel) {
+        anyhow::bail!("Invalid channel: {channel}");
+    }
+    Ok(())
+}
+
+/// A replayed session stands in for the server; the connection stays closed
+/// and sending is refused until it ends. Windows still subscribe, so their
+/// channels are joined again once it does.
+fn ensure_live(app: &AppHandle) -> Result<()> {
+    if crate::session::is_replaying(app) {
+        anyhow::bail!("Chat is read-only while a session is replaying");
+    }
+    Ok(())
+}
+
+pub(crate) fn chat_subscribe(app: &AppHandle, label: &str, channel: &str) -> Result<()> {
+    validate_channel(channel)?;
+    if add_viewer(&mut *lock_viewers(app)?, channel, label) && !crate::session::is_replaying(app) {
+        client(app)?.subscribe(channel)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn chat_unsubscribe(app: &AppHandle, label: &str, channel: &str) -> Result<()> {
+    if remove_viewer(&mut *lock_viewers(app)?, channel, label) && !crate::session::is_replaying(app)
+    {
+        client(app)?.unsubscribe(channel)?;
+    }
+    Ok(())
+}
+
+/// Drops the subscriptions of a window that was closed.
+pub(crate) fn forget_window(app: &AppHandle, label: &str) -> Result<()> {
+    let channels: Vec<String> = lock_viewers(app)?.keys().cloned().collect();
+    for channel in channels {
+        chat_unsubscribe(app, label, &channel)?;
+    }
+    Ok(())
+}
+
+/// Reconnects with the current API URL and auth token, e.g. after signing in.
+pub(crate) fn chat_reconnect(app: &AppHandle) -> Result<()> {
+    if crate::session::is_replaying(app) {
+        return Ok(());
+    }
+    client(app)?.reconnect()
+}
+
+/// Reconnects an open connection so it picks up a new auth token; does
+/// nothing when chat hasn't been used yet.
+pub(crate) fn reconnect_if_open(app: &AppHandle) -> Result<()> {
+    let state = app.state::<ChatClientState>();
+    let client = state
+        .client
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock chat client: {err}"))?
+        .clone();
+    match client {
+        Some(client) => client.reconnect(),
+        None => Ok(()),
+    }
+}
+
+/// Moves an open connection over when `apiUrl` changes.
+pub(crate) fn follow_settings(app: &AppHandle) {
+    let handle = app.clone();
+    crate::settings::on_change(app, "apiUrl", move |_| {
+        if let Err(err) = reconnect_if_open(&handle) {
+            tracing::error!("Failed to reconnect to the new API URL: {err:#}");
+        }
+    });
+}
+
+/// Closes the connection but remembers which channels the windows show, as
+/// a replay does until [`resume_after_replay`].
+pub(crate) fn close_connection(app: &AppHandle) -> Result<()> {
+    let state = app.state::<ChatClientState>();
+    let client = state
+        .client
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock chat client: {err}"))?
+        .take();
+    if let Some(client) = client {
+        client.shutdown();
+    }
+    Ok(())
+}
+
+/// Closes the connection; the next chat command starts a new one.
+pub(crate) fn chat_disconnect(app: &AppHandle) -> Result<()> {
+    close_connection(app)?;
+    lock_viewers(app)?.clear();
+    Ok(())
+}
+
+/// Reconnects after a replay and subscribes to every channel a window shows.
+pub(crate) fn resume_after_replay(app: &AppHandle) -> Result<()> {
+    let channels: Vec<String> = lock_viewers(app)?.keys().cloned().collect();
+    if channels.is_empty() {
+        return Ok(());
+    }
+    let client = client(app)?;
+    for channel in channels {
+        client.subscribe(&channel)?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn chat_publish(
+    app: &AppHandle,
+    channel: &str,
+    content: &str,
+    reply_to_id: Option<&str>,
+    acknowledged: &[String],
+) -> Result<ChatMessage> {
+    validate_channel(channel)?;
+    ensure_live(app)?;
+    crate::message_guard::ensure_sendable(app, content, acknowledged)?;
+    client(app)?.publish(channel, content, reply_to_id).await
+}
+
+pub(crate) async fn chat_limits(app: &AppHandle, channel: &str) -> Result<ChatLimits> {
+    validate_channel(channel)?;
+    ensure_live(app)?;
+    client(app)?.limits(channel).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+    struct TestEnv {
+        url: String,
+        events: mpsc::UnboundedSender<ChatClientEvent>,
+    }
+
+    impl ChatClientEnv for TestEnv {
+        fn ws_url(&self) -> Result<String> {
+            Ok(self.url.clone())
+        }
+
+        fn auth_token(&self) -> Option<String> {
+            Some("test-token".to_string())
+        }
+
+        fn emit(&self, event: ChatClientEvent) {
+            let _ = self.events.send(event);
+        }
+    }
+
+    fn frames(text: &str) -> Vec<Value> {
+        match serde_json::from_str(text).unwrap() {
+            Value::Array(frames) => frames,
+            frame => vec![frame],
+        }
+    }
+
+    /// A tRPC WebSocket server with `chat.subscribe`, `chat.publish` and
+    /// `chat.limits`. Publishing `"drop"` closes the connection.
+    async fn spawn_mock_server() -> (String, mpsc::UnboundedReceiver<Option<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (auth_tx, auth_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let auth_tx = auth_tx.clone();
+                tokio::spawn(async move {
+                    let callback = |request: &Request, response: Response| {
+                        let auth = request
+                            .headers()
+                            .get("Authorization")
+                            .and_then(|value| value.to_str().ok())
+                            .map(str::to_string);
+                        let _ = auth_tx.send(auth);
+                        Ok(response)
+                    };
+                    let socket = tokio_tungstenite::accept_hdr_async(stream, callback)
+                        .await
+                        .unwrap();
+                    let (mut sink, mut stream) = socket.split();
+                    let mut subscriptions: Vec<(Value, String)> = Vec::new();
+
+                    while let Some(Ok(Message::Text(text))) = stream.next().await {
+                        for frame in frames(text.as_str()) {
+                            let id = frame["id"].clone();
+                            let input = &frame["params"]["input"];
+                            match (frame["method"].as_str(), frame["params"]["path"].as_str()) {
+                                (Some("subscription"), Some("chat.subscribe")) => {
+                                    subscriptions.push((
+                                        id.clone(),
+                                        input["channel"].as_str().unwrap().to_string(),
+                                    ));
+                                    let started =
+                                        json!({ "id": id, "result": { "type": "started" } });
+                                    sink.send(Message::text(started.to_string())).await.unwrap();
+                                }
+                                (Some("mutation"), Some("chat.publish")) => {
+                                    if input["content"] == "drop" {
+                                        return;
+                                    }
+                                    let published = json!(test_message(
+                                        "m-1",
+                                        "1",
+                                        input["content"].as_str().unwrap()
+                                    ));
+                                    let mut replies = vec![
+                                        json!({ "id": id, "result": { "type": "data", "data": published } }),
+                                    ];
+                                    for (subscription, channel) in &subscriptions {
+                                        if channel == input["channel"].as_str().unwrap() {
+                                            replies.push(json!({ "id": subscription, "result": { "type": "data", "data": published } }));
+                                        }
+                                    }
+                                    for reply in replies {
+                                        sink.send(Message::text(reply.to_string())).await.unwrap();
+                                    }
+                                }
+                                (Some("query"), Some("chat.limits")) => {
+                                    let limits = json!({ "maxMessageLength": 200, "rateLimitCount": 5, "rateLimitWindowMs": 10000 });
+                                    let reply = json!({ "id": id, "result": { "type": "data", "data": limits } });
+                                    sink.send(Message::text(reply.to_string())).await.unwrap();
+                                }
+                                _ => {
+                                    let error = json!({ "id": id, "error": { "message": "No procedure", "code": -32004, "data": { "code": "NOT_FOUND" } } });
+                                    sink.send(Message::text(error.to_string())).await.unwrap();
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        (url, auth_rx)
+    }
+
+    async fn next_batch(events: &mut mpsc::UnboundedReceiver<ChatClientEvent>) -> ChatMessageBatch {
+        loop {
+            match tokio::time::timeout(Duration::from_secs(5), events.recv()).await {
+                Ok(Some(ChatClientEvent::Messages(batch))) => return batch,
+                Ok(Some(_)) => continue,
+                _ => panic!("no message batch received"),
+            }
+        }
+    }
+
+    async fn wait_for_status(
+        events: &mut mpsc::UnboundedReceiver<ChatClientEvent>,
+        wanted: impl Fn(&ConnectionStatus) -> bool,
+    ) {
+        loop {
+            match tokio::time::timeout(Duration::from_secs(5), events.recv()).await {
+                Ok(Some(ChatClientEvent::Connection(status))) if wanted(&status) => return,
+                Ok(Some(_)) => continue,
+                _ => panic!("connection status not reached"),
+            }
+        }
+    }
+
+    fn spawn_client(url: String) -> (ChatClient, mpsc::UnboundedReceiver<ChatClientEvent>) {
+        let (events, receiver) = mpsc::unbounded_channel();
+        (
+            ChatClient::spawn(Arc::new(TestEnv { url, events })),
+            receiver,
+        )
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff_delay(0), Duration::from_secs(1));
+        assert_eq!(backoff_delay(3), Duration::from_secs(8));
+        assert_eq!(backoff_delay(10), Duration::from_secs(30));
+        assert_eq!(backoff_delay(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn converts_api_url_to_ws_url() {
+        assert_eq!(
+            to_ws_url("https://bloxchat.logix.lol"),
+            "wss://bloxchat.logix.lol"
+        );
+        assert_eq!(to_ws_url("http://127.0.0.1:3000"), "ws://127.0.0.1:3000");
+    }
+
+    #[test]
+    fn keeps_channels_subscribed_while_any_window_shows_them() {
+        let mut viewers = BTreeMap::new();
+        assert!(add_viewer(&mut viewers, "global", "main"));
+        assert!(!add_viewer(&mut viewers, "global", "channel-1"));
+        assert!(!add_viewer(&mut viewers, "global", "main"));
+
+        assert!(!remove_viewer(&mut viewers, "global", "main"));
+        assert!(!remove_viewer(&mut viewers, "global", "main"));
+        assert!(remove_viewer(&mut viewers, "global", "channel-1"));
+        assert!(viewers.is_empty());
+        assert!(!remove_viewer(&mut viewers, "global", "channel-1"));
+    }
+
+    #[tokio::test]
+    async fn subscribes_publishes_and_queries_limits() {
+        let (url, mut auth) = spawn_mock_server().await;
+        let (client, mut events) = spawn_client(url);
+
+        client.subscribe("global").unwrap();
+        wait_for_status(&mut events, |status| *status == ConnectionStatus::Connected).await;
+        assert_eq!(
+            auth.recv().await.unwrap().as_deref(),
+            Some("Bearer test-token")
+        );
+
+        let published = client.publish("global", "hello", None).await.unwrap();
+        assert_eq!(published.content, "hello");
+
+        let batch = next_batch(&mut events).await;
+        assert_eq!(batch.channel, "global");
+        assert_eq!(batch.messages, vec![published]);
+
+        let limits = client.limits("global").await.unwrap();
+        assert_eq!(limits.max_message_length, 200);
+
+        client.shutdown();
+    }
+
+    #[tokio::test]
+    async fn reports_procedure_errors() {
+        let (url, _auth) = spawn_mock_server().await;
+        let (client, _events) = spawn_client(url);
+
+        let err = client
+            .request("query", "chat.history", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "NOT_FOUND: No procedure");
+
+        client.shutdown();
+    }
+
+    #[tokio::test]
+    async fn reconnects_and_resubscribes_after_losing_the_connection() {
+        let (url, mut auth) = spawn_mock_server().await;
+        let (client, mut events) = spawn_client(url);
+
+        client.subscribe("global").unwrap();
+        wait_for_status(&mut events, |status| *status == ConnectionStatus::Connected).await;
+
+        assert!(client.publish("global", "drop", None).await.is_err());
+        wait_for_status(&mut events, |status| {
+            matches!(
+                status,
+                ConnectionStatus::Reconnecting {
+                    attempt: 1,
+                    retry_in_ms: 1000
+                }
+            )
+        })
+        .await;
+        wait_for_status(&mut events, |status| *status == ConnectionStatus::Connected).await;
+        assert!(auth.recv().await.is_some());
+        assert!(auth.recv().await.is_some());
+
+        let published = client.publish("global", "back", None).await.unwrap();
+        let batch = next_batch(&mut events).await;
+        assert_eq!(batch.messages, vec![published]);
+
+        client.shutdown();
+    }
+}
@@ -0,0 +1,459 @@
+use crate::remote::RemoteCommand;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tauri::{AppHandle, Url};
+
+const ENV_PREFIX: &str = "BLOXCHAT_";
+
+static PROFILE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z0-9_-]{1,32}$").expect("valid profile regex"));
+
+pub(crate) const USAGE: &str = "\
+Usage: bloxchat [OPTIONS] [bloxchat://LINK]
+       bloxchat <COMMAND>
+
+Commands (sent to the running instance):
+  send <MESSAGE>       Send a message to the current channel
+  show                 Show and focus the chat window
+  channel <CHANNEL>    Switch to a channel (\"global\" or a Job ID)
+  status               Print the running instance's status as JSON
+
+Options:
+  --logs-path <PATH>   Roblox logs directory to watch    [env: BLOXCHAT_LOGS_PATH]
+  --api-url <URL>      BloxChat server to connect to     [env: BLOXCHAT_API_URL]
+  --channel <CHANNEL>  Channel to open (\"global\" or a Job ID) [env: BLOXCHAT_CHANNEL]
+  --profile <NAME>     Start with the profile named NAME  [env: BLOXCHAT_PROFILE]
+  --minimized          Start with the chat window minimized [env: BLOXCHAT_MINIMIZED]
+  --no-update          Skip the startup update check     [env: BLOXCHAT_NO_UPDATE]
+  --record <PATH>      Record chat and Job ID events to PATH [env: BLOXCHAT_RECORD]
+  --replay <PATH>      Replay a recorded session instead of going live [env: BLOXCHAT_REPLAY]
+  --replay-speed <N>   Replay speed multiplier, 0.1 to 100 [env: BLOXCHAT_REPLAY_SPEED]
+  --demo               Run against a built-in offline demo server [env: BLOXCHAT_DEMO]
+  -h, --help           Print this help";
+
+/// Startup overrides from the command line and `BLOXCHAT_*` variables. Flags
+/// win over environment variables.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliOptions {
+    pub(crate) logs_path: Option<PathBuf>,
+    pub(crate) api_url: Option<String>,
+    pub(crate) channel: Option<String>,
+    pub(crate) profile: Option<String>,
+    pub(crate) minimized: bool,
+    pub(crate) no_update: bool,
+    pub(crate) record: Option<PathBuf>,
+    pub(crate) replay: Option<PathBuf>,
+    pub(crate) replay_speed: Option<f64>,
+    pub(crate) demo: bool,
+    #[serde(skip)]
+    pub(crate) help: bool,
+    #[serde(skip)]
+    pub(crate) command: Option<RemoteCommand>,
+    /// Why the command line was ignored, logged once logging is set up.
+    #[serde(skip)]
+    pub(crate) parse_error: Option<String>,
+}
+
+pub(crate) fn is_valid_profile_name(name: &str) -> bool {
+    PROFILE_RE.is_match(name)
+}
+
+/// Same rules as `normalizeApiUrl` in the frontend store, but rejecting
+/// anything that is not an http(s) URL.
+pub(crate) fn normalize_api_url(value: &str) -> Result<String> {
+    let trimmed = value.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    let with_protocol = if lower.starts_with("http://") || lower.starts_with("https://") {
+        trimmed.to_string()
+    } else {
+        format!("https://{trimmed}")
+    };
+
+    let url = Url::parse(&with_protocol).with_context(|| format!("Invalid API URL: {value}"))?;
+    if url.host_str().is_none_or(str::is_empty) {
+        anyhow::bail!("Invalid API URL: {value}");
+    }
+
+    Ok(with_protocol.trim_end_matches('/').to_string())
+}
+
+fn parse_flag_value(option: &str, value: &str) -> Result<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        anyhow::bail!("{option} needs a value");
+    }
+    Ok(value.to_string())
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+fn parse_channel(value: &str) -> Result<String> {
+    if !crate::roblox::is_valid_channel(value) {
+        anyhow::bail!("Invalid channel: {value}");
+    }
+    Ok(value.to_string())
+}
+
+const COMMAND_NAMES: [&str; 4] = ["send", "show", "channel", "status"];
+
+fn parse_command(name: &str, args: &mut impl Iterator<Item = String>) -> Result<RemoteCommand> {
+    match name {
+        "send" => {
+            let content = args.next().context("send needs a message")?;
+            let content = parse_flag_value("send", &content)?;
+            Ok(RemoteCommand::Send { content })
+        }
+        "show" => Ok(RemoteCommand::Show),
+        "channel" => {
+            let channel = args.next().context("channel needs a channel")?;
+            Ok(RemoteCommand::Channel {
+                channel: parse_channel(channel.trim())?,
+            })
+        }
+        "status" => Ok(RemoteCommand::Status),
+        _ => anyhow::bail!("Unknown command: {name}"),
+    }
+}
+
+impl CliOptions {
+    fn set_value(&mut self, option: &str, value: &str) -> Result<()> {
+        let value = parse_flag_value(option, value)?;
+        match option {
+            "--logs-path" => self.logs_path = Some(PathBuf::from(value)),
+            "--api-url" => self.api_url = Some(normalize_api_url(&value)?),
+            "--channel" => self.channel = Some(parse_channel(&value)?),
+            "--profile" => {
+                if !is_valid_profile_name(&value) {
+                    anyhow::bail!("Invalid profile name: {value}");
+                }
+                self.profile = Some(value);
+            }
+            "--record" => self.record = Some(PathBuf::from(value)),
+            "--replay" => self.replay = Some(PathBuf::from(value)),
+            "--replay-speed" => {
+                let speed = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|speed| speed.is_finite() && (0.1..=100.0).contains(speed))
+                    .with_context(|| format!("Invalid replay speed: {value}"))?;
+                self.replay_speed = Some(speed);
+            }
+            _ => anyhow::bail!("Unknown option: {option}"),
+        }
+        Ok(())
+    }
+
+    /// Parses `args` (without the program name), falling back to `env` for
+    /// anything not given as a flag.
+    pub(crate) fn parse(
+        args: impl IntoIterator<Item = String>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut options = Self::default();
+
+        for (option, name) in [
+            ("--logs-path", "LOGS_PATH"),
+            ("--api-url", "API_URL"),
+            ("--channel", "CHANNEL"),
+            ("--profile", "PROFILE"),
+            ("--record", "RECORD"),
+            ("--replay", "REPLAY"),
+            ("--replay-speed", "REPLAY_SPEED"),
+        ] {
+            if let Some(value) = env(&format!("{ENV_PREFIX}{name}")) {
+                options
+                    .set_value(option, &value)
+                    .with_context(|| format!("{ENV_PREFIX}{name}"))?;
+            }
+        }
+        options.minimized = env(&format!("{ENV_PREFIX}MINIMIZED")).is_some_and(|v| is_truthy(&v));
+        options.no_update = env(&format!("{ENV_PREFIX}NO_UPDATE")).is_some_and(|v| is_truthy(&v));
+        options.demo = env(&format!("{ENV_PREFIX}DEMO")).is_some_and(|v| is_truthy(&v));
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => options.help = true,
+                "--minimized" => options.minimized = true,
+                "--no-update" => options.no_update = true,
+                "--demo" => options.demo = true,
+                "--logs-path" | "--api-url" | "--channel" | "--profile" | "--record"
+                | "--replay" | "--replay-speed" => {
+                    let value = args
+                        .next()
+                        .with_context(|| format!("{arg} needs a value"))?;
+                    options.set_value(&arg, &value)?;
+                }
+                _ if arg.starts_with("--") && arg.contains('=') => {
+                    let (option, value) = arg.split_once('=').unwrap_or_default();
+                    options.set_value(option, value)?;
+                }
+                _ if arg.starts_with('-') => anyhow::bail!("Unknown option: {arg}"),
+                // Deep links are routed separately by `deep_link`.
+                _ if crate::deep_link::is_deep_link_arg(&arg) => {}
+                _ if options.command.is_none() => {
+                    options.command = Some(parse_command(&arg, &mut args)?);
+                }
+                _ => anyhow::bail!("Unexpected argument: {arg}"),
+            }
+        }
+
+        if options.record.is_some() && options.replay.is_some() {
+            anyhow::bail!("--record and --replay can't be used together");
+        }
+
+        Ok(options)
+    }
+
+    /// Parses the current process arguments and environment, printing usage
+    /// and exiting on `--help`. Invalid input only exits for remote-control
+    /// commands; a normal launch starts with defaults and logs the problem.
+    pub(crate) fn from_process() -> Self {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        match Self::parse(args.clone(), |name| std::env::var(name).ok()) {
+            Ok(options) if options.help => {
+                attach_console();
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            Ok(options) => options,
+            Err(err) => {
+                attach_console();
+                eprintln!("{err:#}\n\n{USAGE}");
+                if args.iter().any(|arg| COMMAND_NAMES.contains(&arg.as_str())) {
+                    std::process::exit(2);
+                }
+                Self {
+                    parse_error: Some(format!("{err:#}")),
+                    ..Self::default()
+                }
+            }
+        }
+    }
+
+    /// Launches with a profile, server or logs folder of their own run beside
+    /// the main instance instead of handing off to it. They are named after
+    /// the profile, or else after what they override, and keep their data
+    /// apart under that name.
+    pub(crate) fn instance_name(&self) -> Option<String> {
+        if let Some(profile) = &self.profile {
+            return Some(profile.clone());
+        }
+        if self.api_url.is_none() && self.logs_path.is_none() {
+            return None;
+        }
+        // FNV-1a, so the name stays the same from one build to the next.
+        let key = format!("{:?}|{:?}", self.api_url, self.logs_path);
+        let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        Some(format!("custom-{hash:016x}"))
+    }
+
+    /// Startup-only flags of a later launch that the running instance can't
+    /// apply.
+    pub(crate) fn ignored_by_running_instance(&self) -> Vec<&'static str> {
+        [
+            ("--minimized", self.minimized),
+            ("--no-update", self.no_update),
+            ("--record", self.record.is_some()),
+            ("--replay", self.replay.is_some()),
+            ("--replay-speed", self.replay_speed.is_some()),
+            ("--demo", self.demo),
+        ]
+        .into_iter()
+        .filter_map(|(flag, given)| given.then_some(flag))
+        .collect()
+    }
+
+    /// Applies `--api-url` and `--logs-path` for this run only and keeps the
+    /// main window on `--channel`. Nothing is saved.
+    pub(crate) fn apply(&self, app: &AppHandle) -> Result<()> {
+        if self.api_url.is_some() || self.logs_path.is_some() {
+            let overrides = crate::settings::SettingsOverrides {
+                api_url: self.api_url.clone(),
+                logs_path: self
+                    .logs_path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().to_string()),
+            };
+            crate::settings::set_overrides(app, overrides)?;
+        }
+        if let Some(channel) = &self.channel {
+            crate::popout::switch_main_channel(app, channel)?;
+        }
+        Ok(())
+    }
+}
+
+/// Release builds on Windows are GUI-subsystem executables; attach to the
+/// launching console so command-line output is visible.
+#[cfg(target_os = "windows")]
+pub(crate) fn attach_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn attach_console() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn parse(args: &[&str], env: &[(&str, &str)]) -> Result<CliOptions> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        CliOptions::parse(args.iter().map(|arg| arg.to_string()), |name| {
+            env.get(name).cloned()
+        })
+    }
+
+    #[test]
+    fn parses_flags() {
+        let options = parse(
+            &[
+                "--logs-path",
+                "/tmp/logs",
+                "--api-url=localhost:3000/",
+                "--channel",
+                "global",
+                "--profile=alt",
+                "--minimized",
+                "--no-update",
+                "--replay=session.jsonl",
+                "--replay-speed",
+                "4",
+                "bloxchat://settings",
+            ],
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(options.logs_path, Some(PathBuf::from("/tmp/logs")));
+        assert_eq!(options.api_url.as_deref(), Some("https://localhost:3000"));
+        assert_eq!(options.channel.as_deref(), Some("global"));
+        assert_eq!(options.profile.as_deref(), Some("alt"));
+        assert!(options.minimized && options.no_update);
+        assert_eq!(options.replay, Some(PathBuf::from("session.jsonl")));
+        assert_eq!(options.replay_speed, Some(4.0));
+    }
+
+    #[test]
+    fn separate_instances_are_named_after_what_they_change() {
+        assert_eq!(parse(&["--minimized"], &[]).unwrap().instance_name(), None);
+        assert_eq!(
+            parse(&["--profile", "alt", "--api-url", "localhost"], &[])
+                .unwrap()
+                .instance_name()
+                .as_deref(),
+            Some("alt")
+        );
+
+        let local = parse(&["--api-url", "localhost:3000"], &[]).unwrap();
+        let name = local.instance_name().unwrap();
+        assert!(name.starts_with("custom-"));
+        assert_eq!(local.instance_name().unwrap(), name);
+        let other = parse(&["--logs-path", "/tmp/logs"], &[]).unwrap();
+        assert_ne!(other.instance_name().unwrap(), name);
+
+        let later = parse(
+            &["--minimized", "--replay", "a.jsonl", "--channel", "global"],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            later.ignored_by_running_instance(),
+            ["--minimized", "--replay"]
+        );
+    }
+
+    #[test]
+    fn parses_remote_commands() {
+        let options = parse(&["send", "hello there"], &[]).unwrap();
+        assert_eq!(
+            options.command,
+            Some(RemoteCommand::Send {
+                content: "hello there".to_string()
+            })
+        );
+
+        let options = parse(&["status"], &[]).unwrap();
+        assert_eq!(options.command, Some(RemoteCommand::Status));
+
+        let options = parse(
+            &[
+                "--minimized",
+                "channel",
+                "deadbeef-1111-4111-8111-111111111111",
+            ],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            options.command,
+            Some(RemoteCommand::Channel {
+                channel: "deadbeef-1111-4111-8111-111111111111".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&["show"], &[]).unwrap().command,
+            Some(RemoteCommand::Show)
+        );
+    }
+
+    #[test]
+    fn flags_override_environment() {
+        let options = parse(
+            &["--api-url", "http://127.0.0.1:3000"],
+            &[
+                ("BLOXCHAT_API_URL", "https://example.com"),
+                ("BLOXCHAT_CHANNEL", "deadbeef-1111-4111-8111-111111111111"),
+                ("BLOXCHAT_NO_UPDATE", "1"),
+                ("BLOXCHAT_DEMO", "yes"),
+                ("BLOXCHAT_MINIMIZED", "false"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(options.api_url.as_deref(), Some("http://127.0.0.1:3000"));
+        assert_eq!(
+            options.channel.as_deref(),
+            Some("deadbeef-1111-4111-8111-111111111111")
+        );
+        assert!(options.no_update && options.demo);
+        assert!(!options.minimized);
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert!(parse(&["--channel"], &[]).is_err());
+        assert!(parse(&["--channel", "lobby!"], &[]).is_err());
+        assert!(parse(&["--profile", "../etc"], &[]).is_err());
+        assert!(parse(&["--api-url", "https://"], &[]).is_err());
+        assert!(parse(&["--verbose"], &[]).is_err());
+        assert!(parse(&["send"], &[]).is_err());
+        assert!(parse(&["send", "  "], &[]).is_err());
+        assert!(parse(&["show", "extra"], &[]).is_err());
+        assert!(parse(&["channel", "lobby!"], &[]).is_err());
+        assert!(parse(&["status", "extra"], &[]).is_err());
+        assert!(parse(&["--replay-speed", "0"], &[]).is_err());
+        assert!(parse(&["--record", "a.jsonl", "--replay", "b.jsonl"], &[]).is_err());
+        assert!(parse(&[], &[("BLOXCHAT_CHANNEL", "???")]).is_err());
+    }
+}
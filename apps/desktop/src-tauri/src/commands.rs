@@ -1,6 +1,29 @@
+use crate::anchor::{AnchorCorner, AnchorSettings};
+use crate::archive::{ArchivePage, ArchiveRetention, ArchivedChannel, ArchivedMessage};
+use crate::auth_store::AuthStorage;
+use crate::chat_client::{ChatLimits, ChatMessage};
+use crate::cli::CliOptions;
+use crate::deep_link::DeepLink;
+use crate::demo::DemoInfo;
+use crate::hotkeys::HotkeyBindings;
+use crate::launcher::ServerInvite;
+use crate::link_safety::{BlocklistInfo, LinkVerdict};
+use crate::logging::{LogEntry, LogLevel};
 use crate::media::MediaProbe;
+use crate::message_guard::{GuardRules, GuardVerdict};
+use crate::moderation::ModerationSettings;
+use crate::notifications::NotificationRules;
+use crate::overlay::OverlayStatus;
+use crate::popout::{ChannelBinding, PopoutWindow};
+use crate::profiles::ProfileList;
+use crate::relay::RelayInfo;
 use crate::roblox::LogSettingsState;
-use tauri::AppHandle;
+use crate::session::{RecordingSummary, ReplaySummary, SessionStatus};
+use crate::settings::{Settings, SettingsPatch};
+use crate::settings_transfer::ImportPreview;
+use crate::transcript::{TranscriptExport, TranscriptFormat};
+use crate::window_state::WindowGeometry;
+use tauri::{AppHandle, WebviewWindow};
 
 fn to_cmd<T>(result: anyhow::Result<T>) -> Result<T, String> {
     result.map_err(|err| format!("{:#}", err))
@@ -28,18 +51,28 @@ pub(crate) fn get_roblox_logs_path(
 }
 
 #[tauri::command]
-pub(crate) fn set_roblox_logs_path(
-    path: String,
-    state: tauri::State<LogSettingsState>,
-) -> Result<String, String> {
+pub(crate) fn set_roblox_logs_path(path: String, app: AppHandle) -> Result<String, String> {
+    let patch = SettingsPatch {
+        logs_path: Some(path),
+        ..Default::default()
+    };
     to_cmd(
-        crate::roblox::set_roblox_logs_path(&*state, &path)
-            .map(|path| path.to_string_lossy().to_string()),
+        crate::settings::update_settings(&app, patch).map(|settings| {
+            crate::roblox::resolve_logs_path(&settings.logs_path)
+                .to_string_lossy()
+                .to_string()
+        }),
     )
 }
 
 #[tauri::command]
-pub(crate) fn get_job_id(state: tauri::State<LogSettingsState>) -> Result<String, String> {
+pub(crate) fn get_job_id(
+    state: tauri::State<LogSettingsState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if let Some(job_id) = crate::session::replay_job_id(&app) {
+        return Ok(job_id);
+    }
     to_cmd(crate::roblox::get_job_id(&*state))
 }
 
@@ -57,3 +90,607 @@ pub(crate) fn focus_roblox(app: AppHandle) -> bool {
 pub(crate) async fn is_image(url: String) -> Result<MediaProbe, String> {
     to_cmd(crate::media::is_image(&url).await)
 }
+
+#[tauri::command]
+pub(crate) fn get_window_geometry(window: WebviewWindow) -> Result<Option<WindowGeometry>, String> {
+    to_cmd(crate::window_state::get_window_geometry(&window))
+}
+
+#[tauri::command]
+pub(crate) fn set_window_opacity(
+    opacity: f64,
+    window: WebviewWindow,
+) -> Result<WindowGeometry, String> {
+    to_cmd(crate::window_state::set_window_opacity(&window, opacity))
+}
+
+#[tauri::command]
+pub(crate) fn set_window_collapsed(
+    collapsed: bool,
+    window: WebviewWindow,
+) -> Result<WindowGeometry, String> {
+    to_cmd(crate::window_state::set_window_collapsed(
+        &window, collapsed,
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn get_overlay_status(app: AppHandle) -> Result<OverlayStatus, String> {
+    to_cmd(crate::overlay::get_overlay_status(&app))
+}
+
+#[tauri::command]
+pub(crate) fn set_click_through(enabled: bool, app: AppHandle) -> Result<OverlayStatus, String> {
+    to_cmd(crate::overlay::set_click_through(&app, enabled))
+}
+
+#[tauri::command]
+pub(crate) fn toggle_click_through(app: AppHandle) -> Result<OverlayStatus, String> {
+    to_cmd(crate::overlay::toggle_click_through(&app))
+}
+
+#[tauri::command]
+pub(crate) fn set_overlay_fade_opacity(
+    opacity: f64,
+    app: AppHandle,
+) -> Result<OverlayStatus, String> {
+    to_cmd(crate::overlay::set_fade_opacity(&app, opacity))
+}
+
+#[tauri::command]
+pub(crate) fn get_window_anchor(app: AppHandle) -> Result<AnchorSettings, String> {
+    to_cmd(crate::anchor::get_window_anchor(&app))
+}
+
+#[tauri::command]
+pub(crate) fn set_window_anchor(
+    settings: AnchorSettings,
+    app: AppHandle,
+) -> Result<AnchorSettings, String> {
+    to_cmd(crate::anchor::set_window_anchor(&app, settings))
+}
+
+#[tauri::command]
+pub(crate) fn capture_window_anchor(
+    corner: AnchorCorner,
+    window: WebviewWindow,
+) -> Result<AnchorSettings, String> {
+    to_cmd(crate::anchor::capture_window_anchor(&window, corner))
+}
+
+#[tauri::command]
+pub(crate) async fn open_popout_window(
+    binding: ChannelBinding,
+    always_on_top: Option<bool>,
+    app: AppHandle,
+) -> Result<PopoutWindow, String> {
+    to_cmd(crate::popout::open_popout_window(
+        &app,
+        binding,
+        always_on_top.unwrap_or(true),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn close_popout_window(label: String, app: AppHandle) -> Result<(), String> {
+    to_cmd(crate::popout::close_popout_window(&app, &label))
+}
+
+#[tauri::command]
+pub(crate) fn list_popout_windows(app: AppHandle) -> Result<Vec<PopoutWindow>, String> {
+    to_cmd(crate::popout::list_popout_windows(&app))
+}
+
+#[tauri::command]
+pub(crate) fn set_popout_always_on_top(
+    label: String,
+    enabled: bool,
+    app: AppHandle,
+) -> Result<PopoutWindow, String> {
+    to_cmd(crate::popout::set_popout_always_on_top(
+        &app, &label, enabled,
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn get_window_channel(
+    window: WebviewWindow,
+    app: AppHandle,
+) -> Result<Option<ChannelBinding>, String> {
+    to_cmd(crate::popout::get_window_channel(&app, window.label()))
+}
+
+#[tauri::command]
+pub(crate) fn set_main_channel(
+    app: AppHandle,
+    binding: Option<ChannelBinding>,
+) -> Result<(), String> {
+    to_cmd(crate::popout::set_main_channel(&app, binding))
+}
+
+#[tauri::command]
+pub(crate) fn take_pending_deep_links(app: AppHandle) -> Result<Vec<DeepLink>, String> {
+    to_cmd(crate::deep_link::take_pending_deep_links(&app))
+}
+
+#[tauri::command]
+pub(crate) fn launch_roblox_server(
+    place_id: String,
+    job_id: String,
+) -> Result<ServerInvite, String> {
+    to_cmd(crate::launcher::launch_roblox_server(&place_id, &job_id))
+}
+
+#[tauri::command]
+pub(crate) fn create_server_invite(
+    place_id: String,
+    job_id: String,
+) -> Result<ServerInvite, String> {
+    to_cmd(crate::launcher::create_server_invite(&place_id, &job_id))
+}
+
+#[tauri::command]
+pub(crate) fn get_cli_options(options: tauri::State<CliOptions>) -> CliOptions {
+    options.inner().clone()
+}
+
+#[tauri::command]
+pub(crate) fn chat_subscribe(
+    channel: String,
+    window: WebviewWindow,
+    app: AppHandle,
+) -> Result<(), String> {
+    to_cmd(crate::chat_client::chat_subscribe(
+        &app,
+        window.label(),
+        &channel,
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn chat_unsubscribe(
+    channel: String,
+    window: WebviewWindow,
+    app: AppHandle,
+) -> Result<(), String> {
+    to_cmd(crate::chat_client::chat_unsubscribe(
+        &app,
+        window.label(),
+        &channel,
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn chat_reconnect(app: AppHandle) -> Result<(), String> {
+    to_cmd(crate::chat_client::chat_reconnect(&app))
+}
+
+#[tauri::command]
+pub(crate) fn chat_disconnect(app: AppHandle) -> Result<(), String> {
+    to_cmd(crate::chat_client::chat_disconnect(&app))
+}
+
+#[tauri::command]
+pub(crate) async fn chat_publish(
+    channel: String,
+    content: String,
+    reply_to_id: Option<String>,
+    acknowledged: Option<Vec<String>>,
+    app: AppHandle,
+) -> Result<ChatMessage, String> {
+    to_cmd(
+        crate::chat_client::chat_publish(
+            &app,
+            &channel,
+            &content,
+            reply_to_id.as_deref(),
+            &acknowledged.unwrap_or_default(),
+        )
+        .await,
+    )
+}
+
+#[tauri::command]
+pub(crate) async fn chat_limits(channel: String, app: AppHandle) -> Result<ChatLimits, String> {
+    to_cmd(crate::chat_client::chat_limits(&app, &channel).await)
+}
+
+#[tauri::command]
+pub(crate) fn get_notification_rules(app: AppHandle) -> Result<NotificationRules, String> {
+    to_cmd(crate::notifications::get_notification_rules(&app))
+}
+
+#[tauri::command]
+pub(crate) fn set_notification_rules(
+    rules: NotificationRules,
+    app: AppHandle,
+) -> Result<NotificationRules, String> {
+    to_cmd(crate::notifications::set_notification_rules(&app, rules))
+}
+
+#[tauri::command]
+pub(crate) fn set_channel_muted(
+    channel: String,
+    muted: bool,
+    app: AppHandle,
+) -> Result<NotificationRules, String> {
+    to_cmd(crate::notifications::set_channel_muted(
+        &app, &channel, muted,
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn archive_messages(
+    channel: String,
+    messages: Vec<ChatMessage>,
+    app: AppHandle,
+) -> Result<usize, String> {
+    to_cmd(crate::archive::archive_messages(&app, &channel, &messages))
+}
+
+#[tauri::command]
+pub(crate) fn get_archived_messages(
+    channel: String,
+    before: Option<i64>,
+    limit: Option<u32>,
+    app: AppHandle,
+) -> Result<ArchivePage, String> {
+    to_cmd(crate::archive::get_archived_messages(
+        &app, &channel, before, limit,
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn search_archive(
+    query: String,
+    channel: Option<String>,
+    limit: Option<u32>,
+    app: AppHandle,
+) -> Result<Vec<ArchivedMessage>, String> {
+    to_cmd(crate::archive::search_archive(
+        &app,
+        &query,
+        channel.as_deref(),
+        limit,
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn list_archived_channels(app: AppHandle) -> Result<Vec<ArchivedChannel>, String> {
+    to_cmd(crate::archive::list_archived_channels(&app))
+}
+
+#[tauri::command]
+pub(crate) fn delete_archived_channel(channel: String, app: AppHandle) -> Result<usize, String> {
+    to_cmd(crate::archive::delete_archived_channel(&app, &channel))
+}
+
+#[tauri::command]
+pub(crate) fn get_archive_retention(app: AppHandle) -> Result<ArchiveRetention, String> {
+    to_cmd(crate::archive::get_archive_retention(&app))
+}
+
+#[tauri::command]
+pub(crate) fn set_archive_retention(
+    retention: ArchiveRetention,
+    app: AppHandle,
+) -> Result<ArchiveRetention, String> {
+    to_cmd(crate::archive::set_archive_retention(&app, retention))
+}
+
+#[tauri::command]
+pub(crate) fn export_transcript(
+    channel: String,
+    from: Option<i64>,
+    to: Option<i64>,
+    format: TranscriptFormat,
+    path: String,
+    app: AppHandle,
+) -> Result<TranscriptExport, String> {
+    to_cmd(crate::transcript::export_transcript(
+        &app,
+        &channel,
+        from,
+        to,
+        format,
+        std::path::Path::new(&path),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn start_session_recording(path: String, app: AppHandle) -> Result<(), String> {
+    to_cmd(crate::session::start_recording(
+        &app,
+        std::path::Path::new(&path),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn stop_session_recording(app: AppHandle) -> Result<Option<RecordingSummary>, String> {
+    to_cmd(crate::session::stop_recording(&app))
+}
+
+#[tauri::command]
+pub(crate) fn start_session_replay(
+    path: String,
+    speed: Option<f64>,
+    app: AppHandle,
+) -> Result<ReplaySummary, String> {
+    to_cmd(crate::session::start_replay(
+        &app,
+        std::path::Path::new(&path),
+        speed.unwrap_or(1.0),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn start_queued_replay(app: AppHandle) -> Result<Option<ReplaySummary>, String> {
+    to_cmd(crate::session::start_queued_replay(&app))
+}
+
+#[tauri::command]
+pub(crate) fn stop_session_replay(app: AppHandle) -> Result<(), String> {
+    to_cmd(crate::session::stop_replay(&app))
+}
+
+#[tauri::command]
+pub(crate) fn get_session_status(app: AppHandle) -> Result<SessionStatus, String> {
+    to_cmd(crate::session::get_session_status(&app))
+}
+
+#[tauri::command]
+pub(crate) fn get_demo_info(app: AppHandle) -> Option<DemoInfo> {
+    crate::demo::get_demo_info(&app)
+}
+
+#[tauri::command]
+pub(crate) fn start_relay(port: Option<u16>, app: AppHandle) -> Result<RelayInfo, String> {
+    to_cmd(crate::relay::start_relay(&app, port))
+}
+
+#[tauri::command]
+pub(crate) fn stop_relay(app: AppHandle) -> Result<(), String> {
+    to_cmd(crate::relay::stop_relay(&app))
+}
+
+#[tauri::command]
+pub(crate) fn get_relay_info(app: AppHandle) -> Result<Option<RelayInfo>, String> {
+    to_cmd(crate::relay::get_relay_info(&app))
+}
+
+#[tauri::command]
+pub(crate) fn get_moderation_settings(app: AppHandle) -> Result<ModerationSettings, String> {
+    to_cmd(crate::moderation::get_moderation_settings(&app))
+}
+
+#[tauri::command]
+pub(crate) fn set_moderation_settings(
+    settings: ModerationSettings,
+    app: AppHandle,
+) -> Result<ModerationSettings, String> {
+    to_cmd(crate::moderation::set_moderation_settings(&app, settings))
+}
+
+#[tauri::command]
+pub(crate) fn block_user(
+    roblox_user_id: String,
+    username: Option<String>,
+    app: AppHandle,
+) -> Result<ModerationSettings, String> {
+    to_cmd(crate::moderation::block_user(
+        &app,
+        &roblox_user_id,
+        username,
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn unblock_user(
+    roblox_user_id: String,
+    app: AppHandle,
+) -> Result<ModerationSettings, String> {
+    to_cmd(crate::moderation::unblock_user(&app, &roblox_user_id))
+}
+
+#[tauri::command]
+pub(crate) fn export_moderation_list(path: String, app: AppHandle) -> Result<(), String> {
+    to_cmd(crate::moderation::export_moderation_list(
+        &app,
+        std::path::Path::new(&path),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn import_moderation_list(
+    path: String,
+    replace: Option<bool>,
+    app: AppHandle,
+) -> Result<ModerationSettings, String> {
+    to_cmd(crate::moderation::import_moderation_list(
+        &app,
+        std::path::Path::new(&path),
+        replace.unwrap_or(false),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn check_outgoing_message(
+    content: String,
+    app: AppHandle,
+) -> Result<GuardVerdict, String> {
+    to_cmd(crate::message_guard::check_outgoing_message(&app, &content))
+}
+
+#[tauri::command]
+pub(crate) fn get_message_guard_rules(app: AppHandle) -> Result<GuardRules, String> {
+    to_cmd(crate::message_guard::get_message_guard_rules(&app))
+}
+
+#[tauri::command]
+pub(crate) fn set_message_guard_rules(
+    rules: GuardRules,
+    app: AppHandle,
+) -> Result<GuardRules, String> {
+    to_cmd(crate::message_guard::set_message_guard_rules(&app, rules))
+}
+
+#[tauri::command]
+pub(crate) fn get_hotkeys(app: AppHandle) -> Result<HotkeyBindings, String> {
+    to_cmd(crate::hotkeys::get_hotkeys(&app))
+}
+
+#[tauri::command]
+pub(crate) fn set_hotkeys(
+    bindings: HotkeyBindings,
+    app: AppHandle,
+) -> Result<HotkeyBindings, String> {
+    to_cmd(crate::hotkeys::set_hotkeys(&app, bindings))
+}
+
+#[tauri::command]
+pub(crate) fn check_links(content: String, app: AppHandle) -> Result<Vec<LinkVerdict>, String> {
+    to_cmd(crate::link_safety::check_links(&app, &content))
+}
+
+#[tauri::command]
+pub(crate) fn open_link(
+    url: String,
+    acknowledged: Option<bool>,
+    app: AppHandle,
+) -> Result<LinkVerdict, String> {
+    to_cmd(crate::link_safety::open_link(
+        &app,
+        &url,
+        acknowledged.unwrap_or(false),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn get_link_blocklist_info(app: AppHandle) -> Result<BlocklistInfo, String> {
+    to_cmd(crate::link_safety::get_link_blocklist_info(&app))
+}
+
+#[tauri::command]
+pub(crate) async fn update_link_blocklist(
+    url: Option<String>,
+    app: AppHandle,
+) -> Result<BlocklistInfo, String> {
+    to_cmd(crate::link_safety::update_link_blocklist(&app, url).await)
+}
+
+#[tauri::command]
+pub(crate) fn get_auth_session(app: AppHandle) -> Result<Option<serde_json::Value>, String> {
+    to_cmd(crate::auth_store::get_auth_session(&app))
+}
+
+#[tauri::command]
+pub(crate) fn set_auth_session(
+    session: serde_json::Value,
+    app: AppHandle,
+) -> Result<Option<AuthStorage>, String> {
+    to_cmd(crate::auth_store::set_auth_session(&app, session))
+}
+
+#[tauri::command]
+pub(crate) fn clear_auth_session(app: AppHandle) -> Result<(), String> {
+    to_cmd(crate::auth_store::clear_auth_session(&app))
+}
+
+#[tauri::command]
+pub(crate) fn get_settings(app: AppHandle) -> Result<Settings, String> {
+    to_cmd(crate::settings::get_settings(&app))
+}
+
+#[tauri::command]
+pub(crate) fn update_settings(patch: SettingsPatch, app: AppHandle) -> Result<Settings, String> {
+    to_cmd(crate::settings::update_settings(&app, patch))
+}
+
+#[tauri::command]
+pub(crate) fn export_settings(path: String, app: AppHandle) -> Result<(), String> {
+    to_cmd(crate::settings_transfer::export_settings(
+        &app,
+        std::path::Path::new(&path),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn preview_settings_import(
+    path: String,
+    app: AppHandle,
+) -> Result<ImportPreview, String> {
+    to_cmd(crate::settings_transfer::preview_settings_import(
+        &app,
+        std::path::Path::new(&path),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn import_settings(path: String, app: AppHandle) -> Result<ImportPreview, String> {
+    to_cmd(crate::settings_transfer::import_settings(
+        &app,
+        std::path::Path::new(&path),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn list_profiles(app: AppHandle) -> Result<ProfileList, String> {
+    to_cmd(crate::profiles::list_profiles(&app))
+}
+
+#[tauri::command]
+pub(crate) fn create_profile(name: String, app: AppHandle) -> Result<ProfileList, String> {
+    to_cmd(crate::profiles::create_profile(&app, &name))
+}
+
+#[tauri::command]
+pub(crate) fn delete_profile(name: String, app: AppHandle) -> Result<ProfileList, String> {
+    to_cmd(crate::profiles::delete_profile(&app, &name))
+}
+
+#[tauri::command]
+pub(crate) fn switch_profile(name: String, app: AppHandle) -> Result<ProfileList, String> {
+    to_cmd(crate::profiles::switch_profile(&app, &name))
+}
+
+#[tauri::command]
+pub(crate) fn link_profile_account(
+    name: String,
+    roblox_user_id: Option<String>,
+    app: AppHandle,
+) -> Result<ProfileList, String> {
+    to_cmd(crate::profiles::link_profile_account(
+        &app,
+        &name,
+        roblox_user_id,
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn set_profile_auto_switch(
+    enabled: bool,
+    app: AppHandle,
+) -> Result<ProfileList, String> {
+    to_cmd(crate::profiles::set_profile_auto_switch(&app, enabled))
+}
+
+#[tauri::command]
+pub(crate) fn create_diagnostics_bundle(path: String, app: AppHandle) -> Result<(), String> {
+    to_cmd(crate::diagnostics::create_diagnostics_bundle(
+        &app,
+        std::path::Path::new(&path),
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn get_recent_logs(
+    min_level: Option<LogLevel>,
+    limit: Option<usize>,
+    app: AppHandle,
+) -> Result<Vec<LogEntry>, String> {
+    to_cmd(crate::logging::get_recent_logs(
+        &app,
+        min_level.unwrap_or(LogLevel::Warn),
+        limit.unwrap_or(100),
+    ))
+}
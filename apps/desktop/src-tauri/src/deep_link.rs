@@ -0,0 +1,247 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Emitter, Manager, Url};
+
+pub(crate) const DEEP_LINK_SCHEME: &str = "bloxchat";
+const INVALID_LINK_EVENT: &str = "deep-link://invalid";
+
+static UUID_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$")
+        .expect("valid uuid regex")
+});
+
+/// A validated `bloxchat://` link.
+///
+/// - `bloxchat://join/<channel>`
+/// - `bloxchat://settings`
+/// - `bloxchat://verify/<sessionId>`
+/// - `bloxchat://message/<channel>/<messageId>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub(crate) enum DeepLink {
+    JoinChannel {
+        channel: String,
+    },
+    OpenSettings,
+    #[serde(rename_all = "camelCase")]
+    CompleteVerification {
+        session_id: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    OpenMessage {
+        channel: String,
+        message_id: String,
+    },
+}
+
+impl DeepLink {
+    fn event_name(&self) -> &'static str {
+        match self {
+            DeepLink::JoinChannel { .. } => "deep-link://join-channel",
+            DeepLink::OpenSettings => "deep-link://open-settings",
+            DeepLink::CompleteVerification { .. } => "deep-link://complete-verification",
+            DeepLink::OpenMessage { .. } => "deep-link://open-message",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct InvalidDeepLink {
+    url: String,
+    error: String,
+}
+
+/// Links received before the webview could listen for them (cold start).
+#[derive(Default)]
+pub(crate) struct DeepLinkState {
+    pending: Mutex<Vec<DeepLink>>,
+}
+
+fn parse_channel(value: &str) -> Result<String> {
+    if !crate::roblox::is_valid_channel(value) {
+        anyhow::bail!("Invalid channel: {value}");
+    }
+
+    Ok(value.to_string())
+}
+
+fn parse_uuid(value: &str, what: &str) -> Result<String> {
+    if !UUID_RE.is_match(value) {
+        anyhow::bail!("Invalid {what}: {value}");
+    }
+
+    Ok(value.to_ascii_lowercase())
+}
+
+pub(crate) fn parse_deep_link(raw: &str) -> Result<DeepLink> {
+    let url = Url::parse(raw.trim()).context("Malformed link")?;
+    if !url.scheme().eq_ignore_ascii_case(DEEP_LINK_SCHEME) {
+        anyhow::bail!("Unsupported scheme: {}", url.scheme());
+    }
+
+    // `bloxchat://join/x` puts the action in the host, `bloxchat:join/x` in
+    // the path; accept both.
+    let segments: Vec<&str> = url
+        .host_str()
+        .into_iter()
+        .chain(url.path().split('/'))
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    let Some((action, args)) = segments.split_first() else {
+        anyhow::bail!("Missing link action");
+    };
+
+    match (action.to_ascii_lowercase().as_str(), args) {
+        ("join", [channel]) => Ok(DeepLink::JoinChannel {
+            channel: parse_channel(channel)?,
+        }),
+        ("settings", []) => Ok(DeepLink::OpenSettings),
+        ("verify", [session_id]) => Ok(DeepLink::CompleteVerification {
+            session_id: parse_uuid(session_id, "verification session")?,
+        }),
+        ("message", [channel, message_id]) => Ok(DeepLink::OpenMessage {
+            channel: parse_channel(channel)?,
+            message_id: parse_uuid(message_id, "message id")?,
+        }),
+        ("join" | "settings" | "verify" | "message", _) => {
+            anyhow::bail!("Wrong number of arguments for {action}")
+        }
+        _ => anyhow::bail!("Unknown link action: {action}"),
+    }
+}
+
+pub(crate) fn is_deep_link_arg(arg: &str) -> bool {
+    arg.get(..DEEP_LINK_SCHEME.len() + 1)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{DEEP_LINK_SCHEME}:")))
+}
+
+/// Picks the `bloxchat://` links out of a process argument list.
+pub(crate) fn deep_link_args(args: &[String]) -> impl Iterator<Item = &str> {
+    args.iter()
+        .skip(1)
+        .map(String::as_str)
+        .filter(|arg| is_deep_link_arg(arg))
+}
+
+fn report_invalid(app: &AppHandle, url: &str, err: anyhow::Error) {
+    tracing::warn!("Ignoring invalid deep link {url}: {err:#}");
+    let _ = app.emit(
+        INVALID_LINK_EVENT,
+        InvalidDeepLink {
+            url: url.to_string(),
+            error: format!("{err:#}"),
+        },
+    );
+}
+
+/// Routes a link received while the app is running to the UI.
+pub(crate) fn dispatch_deep_link(app: &AppHandle, url: &str) {
+    match parse_deep_link(url) {
+        Ok(link) => open_link(app, link),
+        Err(err) => report_invalid(app, url, err),
+    }
+}
+
+pub(crate) fn open_link(app: &AppHandle, link: DeepLink) {
+    crate::show_chat_window(app);
+    let _ = app.emit(link.event_name(), link);
+}
+
+/// Queues a cold-start link until the UI asks for it with
+/// [`take_pending_deep_links`].
+pub(crate) fn queue_deep_link(app: &AppHandle, url: &str) {
+    match parse_deep_link(url) {
+        Ok(link) => queue_link(app, link),
+        Err(err) => report_invalid(app, url, err),
+    }
+}
+
+pub(crate) fn queue_link(app: &AppHandle, link: DeepLink) {
+    if let Ok(mut pending) = app.state::<DeepLinkState>().pending.lock() {
+        pending.push(link);
+    }
+}
+
+pub(crate) fn take_pending_deep_links(app: &AppHandle) -> Result<Vec<DeepLink>> {
+    let state = app.state::<DeepLinkState>();
+    let mut pending = state
+        .pending
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock pending deep links: {err}"))?;
+    Ok(std::mem::take(&mut *pending))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = "0f8fad5b-d9cb-469f-a165-70867728950e";
+
+    #[test]
+    fn parses_each_action() {
+        assert_eq!(
+            parse_deep_link("bloxchat://join/global").unwrap(),
+            DeepLink::JoinChannel {
+                channel: "global".to_string()
+            }
+        );
+        assert_eq!(
+            parse_deep_link("bloxchat://settings/").unwrap(),
+            DeepLink::OpenSettings
+        );
+        assert_eq!(
+            parse_deep_link(&format!("bloxchat:verify/{SESSION}")).unwrap(),
+            DeepLink::CompleteVerification {
+                session_id: SESSION.to_string()
+            }
+        );
+        assert_eq!(
+            parse_deep_link(&format!(
+                "bloxchat://message/deadbeef-1111-4111-8111-111111111111/{SESSION}"
+            ))
+            .unwrap(),
+            DeepLink::OpenMessage {
+                channel: "deadbeef-1111-4111-8111-111111111111".to_string(),
+                message_id: SESSION.to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_links() {
+        assert!(parse_deep_link("https://join/global").is_err());
+        assert!(parse_deep_link("bloxchat://").is_err());
+        assert!(parse_deep_link("bloxchat://join").is_err());
+        assert!(parse_deep_link("bloxchat://join/not-a-job!").is_err());
+        assert!(parse_deep_link("bloxchat://settings/extra").is_err());
+        assert!(parse_deep_link("bloxchat://verify/123456").is_err());
+        assert!(parse_deep_link("bloxchat://explode").is_err());
+    }
+
+    #[test]
+    fn serializes_with_action_tag() {
+        let link = DeepLink::CompleteVerification {
+            session_id: SESSION.to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(link).unwrap(),
+            serde_json::json!({ "action": "completeVerification", "sessionId": SESSION })
+        );
+    }
+
+    #[test]
+    fn finds_links_in_argv() {
+        let args = vec![
+            "bloxchat".to_string(),
+            "--flag".to_string(),
+            "BloxChat://settings".to_string(),
+        ];
+        assert_eq!(
+            deep_link_args(&args).collect::<Vec<_>>(),
+            vec!["BloxChat://settings"]
+        );
+    }
+}
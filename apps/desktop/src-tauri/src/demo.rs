@@ -0,0 +1,297 @@
+use crate::chat_client::ChatUser;
+use crate::relay::{RelayHub, RelayLimits};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::net::TcpListener;
+
+const DEMO_LOGS_DIR: &str = "demo-logs";
+/// Named like a Roblox player log so the log watcher picks it up.
+const DEMO_LOG_FILE: &str = "0.000.0_demo_Player_last.log";
+const DEMO_PLACE_ID: &str = "606849621";
+/// Servers the scripted player hops between, leaving to the menu in between.
+const DEMO_JOB_IDS: [&str; 2] = [
+    "de30a000-0000-4000-8000-000000000001",
+    "de30a000-0000-4000-8000-000000000002",
+];
+const SCRIPT_INTERVAL: Duration = Duration::from_secs(4);
+const JOB_INTERVAL: Duration = Duration::from_secs(45);
+
+/// Scripted chatter: `(user id, username, display name, content, replies to
+/// the previous line)`.
+#[rustfmt::skip]
+const SCRIPT: &[(&str, &str, &str, &str, bool)] = &[
+    ("156", "builderman", "Builderman", "welcome to the BloxChat demo!", false),
+    ("261", "shedletsky", "Shedletsky", "anyone up for a round of sword fights?", false),
+    ("1", "roblox", "Roblox", "count me in", true),
+    ("80254", "stickmasterluke", "Luke", "this server has the best lag", false),
+    ("156", "builderman", "Builderman", "the Job ID changes every so often", false),
+    ("261", "shedletsky", "Shedletsky", "Shedletsky was here", false),
+    ("1", "roblox", "Roblox", "https://www.roblox.com/games/606849621 gets a preview", false),
+    ("80254", "stickmasterluke", "Luke", "nice", true),
+];
+
+fn demo_player() -> ChatUser {
+    ChatUser {
+        roblox_user_id: "0".to_string(),
+        username: "demo_player".to_string(),
+        display_name: "You".to_string(),
+        picture: String::new(),
+    }
+}
+
+fn join_line(job_id: &str) -> String {
+    format!("[FLog::Output] ! Joining game '{job_id}' place {DEMO_PLACE_ID} at 127.0.0.1\n")
+}
+
+fn leave_line() -> String {
+    "[FLog::Network] Disconnect from game\n".to_string()
+}
+
+/// The scripted player's current server, `None` while in the menu.
+type CurrentJob = Arc<Mutex<Option<String>>>;
+
+fn current_channel(job_id: &CurrentJob) -> String {
+    job_id
+        .lock()
+        .ok()
+        .and_then(|job_id| job_id.clone())
+        .unwrap_or_else(|| "global".to_string())
+}
+
+/// Posts the script to global chat and the current server, one line at a time.
+async fn run_script(hub: Arc<RelayHub>, job_id: CurrentJob) {
+    let mut interval = tokio::time::interval(SCRIPT_INTERVAL);
+    let mut previous: Option<String> = None;
+    for (index, (user_id, username, display_name, content, reply)) in
+        SCRIPT.iter().enumerate().cycle()
+    {
+        interval.tick().await;
+        let channel = if index % 2 == 0 {
+            "global".to_string()
+        } else {
+            current_channel(&job_id)
+        };
+        let author = ChatUser {
+            roblox_user_id: user_id.to_string(),
+            username: username.to_string(),
+            display_name: display_name.to_string(),
+            picture: String::new(),
+        };
+        let reply_to_id = if *reply { previous.take() } else { None };
+        previous = Some(hub.post(&channel, author, content, reply_to_id).id);
+    }
+}
+
+fn append_log(path: &Path, line: &str) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context("open demo log")?;
+    file.write_all(line.as_bytes()).context("write demo log")
+}
+
+/// Joins and leaves the demo servers by writing to a fake Roblox log, so Job
+/// IDs reach the app through the log watcher and `get_job_id`.
+async fn run_job_cycle(log_file: PathBuf, current: CurrentJob) {
+    let mut interval = tokio::time::interval(JOB_INTERVAL);
+    for job_id in DEMO_JOB_IDS
+        .iter()
+        .flat_map(|job_id| [Some(*job_id), None])
+        .cycle()
+    {
+        interval.tick().await;
+        let line = match job_id {
+            Some(job_id) => join_line(job_id),
+            None => leave_line(),
+        };
+        if let Err(err) = append_log(&log_file, &line) {
+            tracing::error!("Failed to update demo log: {err:#}");
+        }
+        if let Ok(mut current) = current.lock() {
+            *current = job_id.map(str::to_string);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DemoInfo {
+    api_url: String,
+    logs_path: PathBuf,
+}
+
+#[derive(Default)]
+pub(crate) struct DemoState {
+    info: Mutex<Option<DemoInfo>>,
+}
+
+/// Starts the demo backend on a loopback port and switches chat and log
+/// watching over to it for this run only; nothing is saved.
+pub(crate) fn start_demo(app: &AppHandle) -> Result<DemoInfo> {
+    let logs_path = crate::storage::data_dir(app)?.join(DEMO_LOGS_DIR);
+    std::fs::create_dir_all(&logs_path).context("create demo logs folder")?;
+    let log_file = logs_path.join(DEMO_LOG_FILE);
+    std::fs::write(&log_file, "").context("reset demo log")?;
+
+    let listener =
+        std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).context("bind demo server")?;
+    listener
+        .set_nonblocking(true)
+        .context("configure demo server")?;
+    let port = listener.local_addr().context("read demo port")?.port();
+
+    let info = DemoInfo {
+        api_url: format!("http://127.0.0.1:{port}"),
+        logs_path: logs_path.clone(),
+    };
+    crate::settings::set_overrides(
+        app,
+        crate::settings::SettingsOverrides {
+            api_url: Some(info.api_url.clone()),
+            logs_path: Some(logs_path.to_string_lossy().to_string()),
+        },
+    )?;
+
+    // Everything sent from the app is posted as the demo player, and signing
+    // in signs in as them.
+    let hub = RelayHub::with_guest_sign_in(RelayLimits::default(), demo_player(), DEMO_PLACE_ID);
+    let job_id = CurrentJob::default();
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(err) => {
+                tracing::error!("Failed to start demo server: {err}");
+                return;
+            }
+        };
+        tokio::join!(
+            crate::relay::serve(listener, hub.clone()),
+            run_script(hub, job_id.clone()),
+            run_job_cycle(log_file, job_id),
+        );
+    });
+
+    if let Ok(mut current) = app.state::<DemoState>().info.lock() {
+        *current = Some(info.clone());
+    }
+    Ok(info)
+}
+
+pub(crate) fn get_demo_info(app: &AppHandle) -> Option<DemoInfo> {
+    app.state::<DemoState>()
+        .info
+        .lock()
+        .ok()
+        .and_then(|info| info.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roblox::LogSettingsState;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::Value;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[test]
+    fn demo_log_lines_drive_get_job_id() {
+        let dir = std::env::temp_dir().join(format!("bloxchat-demo-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log_file = dir.join(DEMO_LOG_FILE);
+        std::fs::write(&log_file, "").unwrap();
+        let state = LogSettingsState {
+            logs_path: Mutex::new(dir.clone()),
+            watcher_control: Mutex::new(None),
+        };
+
+        append_log(&log_file, &join_line(DEMO_JOB_IDS[0])).unwrap();
+        assert_eq!(crate::roblox::get_job_id(&state).unwrap(), DEMO_JOB_IDS[0]);
+        assert_eq!(
+            crate::roblox::get_place_id(&state, DEMO_JOB_IDS[0]).unwrap(),
+            Some(DEMO_PLACE_ID.to_string())
+        );
+
+        append_log(&log_file, &leave_line()).unwrap();
+        assert_eq!(crate::roblox::get_job_id(&state).unwrap(), "global");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn serves_the_script_and_guest_messages() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let hub =
+            RelayHub::with_guest_sign_in(RelayLimits::default(), demo_player(), DEMO_PLACE_ID);
+        tokio::spawn(crate::relay::serve(listener, hub.clone()));
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        socket
+            .send(Message::text(
+                r#"{"id":7,"jsonrpc":"2.0","method":"subscription","params":{"path":"chat.subscribe","input":{"channel":"global"}}}"#,
+            ))
+            .await
+            .unwrap();
+        let Some(Ok(Message::Text(started))) = socket.next().await else {
+            panic!("expected a started frame");
+        };
+        assert!(started.as_str().contains("started"));
+
+        let current = CurrentJob::default();
+        tokio::spawn(run_script(hub, current));
+        let Some(Ok(Message::Text(data))) = socket.next().await else {
+            panic!("expected a scripted message");
+        };
+        let frame: Value = serde_json::from_str(data.as_str()).unwrap();
+        assert_eq!(frame["result"]["data"]["author"]["username"], "builderman");
+
+        socket
+            .send(Message::text(
+                r#"{"id":8,"jsonrpc":"2.0","method":"mutation","params":{"path":"chat.publish","input":{"channel":"global","content":"hi"}}}"#,
+            ))
+            .await
+            .unwrap();
+        let Some(Ok(Message::Text(reply))) = socket.next().await else {
+            panic!("expected a publish reply");
+        };
+        let frame: Value = serde_json::from_str(reply.as_str()).unwrap();
+        assert_eq!(frame["result"]["data"]["author"]["username"], "demo_player");
+    }
+
+    #[tokio::test]
+    async fn signs_in_over_http() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let hub =
+            RelayHub::with_guest_sign_in(RelayLimits::default(), demo_player(), DEMO_PLACE_ID);
+        tokio::spawn(crate::relay::serve(listener, hub));
+
+        let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        let body = r#"{"0":{"sessionId":"00000000-0000-4000-8000-000000000000"}}"#;
+        let request = format!(
+            "POST /auth.checkVerification?batch=1 HTTP/1.1\r\nHost: {address}\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(response.contains("Access-Control-Allow-Origin: *"));
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let body: Value = serde_json::from_str(body).unwrap();
+        let data = &body[0]["result"]["data"];
+        assert_eq!(data["status"], "verified");
+        assert_eq!(data["user"]["username"], "demo_player");
+        assert_eq!(data["jwt"].as_str().unwrap().split('.').count(), 3);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::clock::now_ms;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const MAX_JOB_ID_TRANSITIONS: usize = 50;
+const MAX_APP_LOG_FILES: usize = 3;
+const MAX_APP_LOG_BYTES: u64 = 1024 * 1024;
+const ROBLOX_LOG_TAIL_BYTES: u64 = 256 * 1024;
+
+/// Things in Roblox and app logs that identify the player or their machine,
+/// on top of the secrets `message_guard` already knows.
+static REDACTIONS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+    [
+        (
+            r"(?i)\b(ticket|authticket|token|jwt|session|sessionid|cookie|secret|password|key)=[^&\s'\x22]+",
+            "$1=<redacted>",
+        ),
+        (r"(?i)\buserid:\d+", "userid:<redacted>"),
+        (
+            r#"(?i)("(?:linked)?robloxUserId"\s*:\s*)"\d+""#,
+            r#"${1}"<redacted>""#,
+        ),
+        (r"\b\d{1,3}(?:\.\d{1,3}){3}\b", "<ip>"),
+        (r"(?i)([\\/](?:users|home)[\\/]+)[^\\/\s'\x22]+", "${1}<user>"),
+    ]
+    .into_iter()
+    .map(|(pattern, replacement)| {
+        (
+            Regex::new(pattern).expect("valid redaction regex"),
+            replacement,
+        )
+    })
+    .collect()
+});
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JobIdTransition {
+    job_id: String,
+    at_ms: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct DiagnosticsState {
+    job_ids: Mutex<VecDeque<JobIdTransition>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BuildInfo {
+    version: &'static str,
+    commit: &'static str,
+    target: &'static str,
+    profile: &'static str,
+    os: &'static str,
+    portable: bool,
+}
+
+fn build_info() -> BuildInfo {
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        commit: env!("BLOXCHAT_GIT_COMMIT"),
+        target: env!("BLOXCHAT_BUILD_TARGET"),
+        profile: env!("BLOXCHAT_BUILD_PROFILE"),
+        os: std::env::consts::OS,
+        portable: crate::storage::is_portable(),
+    }
+}
+
+fn modified_ms(path: &Path) -> Option<u64> {
+    let modified = path.metadata().and_then(|meta| meta.modified()).ok()?;
+    modified
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|elapsed| elapsed.as_millis() as u64)
+}
+
+/// Called by the log watcher on every Job ID change.
+pub(crate) fn record_job_id(app: &AppHandle, job_id: &str) {
+    let state = app.state::<DiagnosticsState>();
+    let Ok(mut job_ids) = state.job_ids.lock() else {
+        return;
+    };
+    if job_ids.len() == MAX_JOB_ID_TRANSITIONS {
+        job_ids.pop_front();
+    }
+    job_ids.push_back(JobIdTransition {
+        job_id: job_id.to_string(),
+        at_ms: now_ms(),
+    });
+}
+
+fn redact(text: &str) -> String {
+    let mut text = crate::message_guard::redact_secrets(text);
+    for (pattern, replacement) in REDACTIONS.iter() {
+        text = pattern.replace_all(&text, *replacement).into_owned();
+    }
+    text
+}
+
+/// The last `max_bytes` of a file, starting at a line boundary.
+fn read_tail(path: &Path, max_bytes: u64) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let len = file.metadata().context("stat log file")?.len();
+    let start = len.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start))
+        .context("seek log file tail")?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).context("read log tail")?;
+
+    let text = String::from_utf8_lossy(&bytes);
+    match (start, text.find('\n')) {
+        (0, _) | (_, None) => Ok(text.into_owned()),
+        (_, Some(newline)) => Ok(text[newline + 1..].to_string()),
+    }
+}
+
+fn settings_summary(app: &AppHandle) -> Result<Value> {
+    let mut settings = crate::settings::settings_values(app)?;
+    if let Some(Value::Array(media)) = settings.remove("favoritedMedia") {
+        settings.insert("favoritedMediaCount".to_string(), json!(media.len()));
+    }
+    let profiles = crate::profiles::list_profiles(app)?;
+
+    Ok(json!({
+        "settings": settings,
+        "profiles": profiles,
+        "signedIn": crate::auth_store::auth_session(app).is_some(),
+        "demo": crate::demo::get_demo_info(app).is_some(),
+    }))
+}
+
+fn watcher_status(app: &AppHandle) -> Result<Value> {
+    let state = app.state::<crate::roblox::LogSettingsState>();
+    let logs_path = crate::roblox::get_roblox_logs_path(&state)?;
+    let watcher_running = state
+        .watcher_control
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock watcher_control: {err}"))?
+        .is_some();
+    let latest_log = crate::roblox::latest_player_log(&logs_path).map(|path| {
+        json!({
+            "name": path.file_name().map(|name| name.to_string_lossy().to_string()),
+            "sizeBytes": path.metadata().map(|meta| meta.len()).ok(),
+            "modifiedMs": modified_ms(&path),
+        })
+    });
+    let candidates: Vec<Value> = crate::roblox::logs_path_candidates()
+        .into_iter()
+        .map(|path| {
+            json!({
+                "path": path,
+                "exists": path.is_dir(),
+                "hasLogs": crate::roblox::latest_player_log(&path).is_some(),
+            })
+        })
+        .collect();
+    let job_ids: Vec<JobIdTransition> = app
+        .state::<DiagnosticsState>()
+        .job_ids
+        .lock()
+        .map(|job_ids| job_ids.iter().cloned().collect())
+        .unwrap_or_default();
+
+    Ok(json!({
+        "logsPath": logs_path,
+        "logsPathExists": logs_path.is_dir(),
+        "defaultLogsPath": crate::roblox::default_roblox_logs_path(),
+        "candidates": candidates,
+        "watcherRunning": watcher_running,
+        "latestLog": latest_log,
+        "jobId": crate::roblox::get_job_id(&state).ok(),
+        "replaying": crate::session::is_replaying(app),
+        "jobIdTransitions": job_ids,
+    }))
+}
+
+/// The newest app log files, oldest first.
+fn app_log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort_by_key(|path| modified_ms(path));
+    let skip = files.len().saturating_sub(MAX_APP_LOG_FILES);
+    files.split_off(skip)
+}
+
+fn pretty(value: &impl Serialize) -> Result<String> {
+    serde_json::to_string_pretty(value).context("serialize diagnostics")
+}
+
+/// Gathers every bundle entry as `(name, redacted text)`. Parts that fail
+/// are replaced by a note so the rest still gets through.
+fn collect_entries(app: &AppHandle) -> Vec<(String, String)> {
+    let mut entries = vec![("build.json".to_string(), pretty(&build_info()))];
+    entries.push((
+        "settings.json".to_string(),
+        settings_summary(app).and_then(|summary| pretty(&summary)),
+    ));
+    entries.push((
+        "watcher.json".to_string(),
+        watcher_status(app).and_then(|status| pretty(&status)),
+    ));
+
+    let state = app.state::<crate::roblox::LogSettingsState>();
+    let roblox_log = crate::roblox::get_roblox_logs_path(&state)
+        .ok()
+        .and_then(|dir| crate::roblox::latest_player_log(&dir));
+    entries.push((
+        "roblox-log-tail.txt".to_string(),
+        match roblox_log {
+            Some(path) => read_tail(&path, ROBLOX_LOG_TAIL_BYTES),
+            None => Ok("No Roblox log found in the watched folder.\n".to_string()),
+        },
+    ));
+
+    let log_files = crate::storage::data_dir(app)
+        .map(|dir| app_log_files(&dir.join(crate::logging::APP_LOGS_DIR)))
+        .unwrap_or_default();
+    if log_files.is_empty() {
+        entries.push((
+            "app-logs/README.txt".to_string(),
+            Ok("No app log files were found.\n".to_string()),
+        ));
+    }
+    for path in log_files {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        entries.push((
+            format!("app-logs/{name}"),
+            read_tail(&path, MAX_APP_LOG_BYTES),
+        ));
+    }
+
+    entries
+        .into_iter()
+        .map(|(name, content)| {
+            let content = content.unwrap_or_else(|err| format!("Unavailable: {err:#}\n"));
+            (name, redact(&content))
+        })
+        .collect()
+}
+
+fn write_zip<W: Write + Seek>(writer: W, entries: &[(String, String)]) -> Result<W> {
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, content) in entries {
+        zip.start_file(name.as_str(), options)
+            .with_context(|| format!("add {name} to diagnostics"))?;
+        zip.write_all(content.as_bytes())
+            .with_context(|| format!("write {name} to diagnostics"))?;
+    }
+    zip.finish().context("finish diagnostics bundle")
+}
+
+/// Zips build info, redacted settings, watcher status, Job ID history and
+/// the ends of the Roblox and app logs into `path` for bug reports. The auth
+/// session is never included.
+pub(crate) fn create_diagnostics_bundle(app: &AppHandle, path: &Path) -> Result<()> {
+    let entries = collect_entries(app);
+    let file = File::create(path).with_context(|| format!("create {}", path.display()))?;
+    write_zip(file, &entries)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn redacts_identifying_details() {
+        let line = r#"2026-03-18 [FLog::Output] Report placeid:606849621, userid:12345678 at 10.0.0.2 path C:\Users\Alex\AppData\Local\Roblox\logs https://auth.roblox.com/?ticket=abc123&x=1 _|WARNING:-DO-NOT-SHARE-THIS.--abc me@example.com /home/alex/.var"#;
+        let redacted = redact(line);
+        let profiles = redact(r#"{ "name": "alt", "linkedRobloxUserId": "99887766" }"#);
+        assert!(profiles.contains(r#""linkedRobloxUserId": "<redacted>""#));
+
+        for secret in [
+            "12345678",
+            "10.0.0.2",
+            "Alex",
+            "abc123",
+            "DO-NOT-SHARE",
+            "me@example.com",
+            "alex",
+        ] {
+            assert!(!redacted.contains(secret), "{secret} left in {redacted}");
+        }
+        assert!(redacted.contains("placeid:606849621"));
+        assert!(redacted.contains(r"C:\Users\<user>\AppData"));
+        assert!(redacted.contains("ticket=<redacted>&x=1"));
+    }
+
+    #[test]
+    fn tails_start_on_a_line_boundary() {
+        let path = std::env::temp_dir().join(format!("bloxchat-tail-{}.log", std::process::id()));
+        std::fs::write(&path, "first line\nsecond line\nthird\n").unwrap();
+
+        assert_eq!(read_tail(&path, 14).unwrap(), "third\n");
+        assert_eq!(
+            read_tail(&path, 1024).unwrap(),
+            "first line\nsecond line\nthird\n"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn writes_a_readable_zip() {
+        let entries = vec![
+            ("build.json".to_string(), "{}".to_string()),
+            ("app-logs/app.log".to_string(), "hello\n".to_string()),
+        ];
+        let bytes = write_zip(Cursor::new(Vec::new()), &entries)
+            .unwrap()
+            .into_inner();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.len(), 2);
+        let mut content = String::new();
+        archive
+            .by_name("app-logs/app.log")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "hello\n");
+    }
+}
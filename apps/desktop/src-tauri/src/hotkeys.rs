@@ -0,0 +1,216 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+#[cfg(desktop)]
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+const HOTKEYS_KEY: &str = "hotkeys";
+const HOTKEYS_CHANGED_EVENT: &str = "hotkeys://changed";
+const MAX_CHAT_KEYS: usize = 4;
+
+/// Global key bindings, written as `Ctrl+Shift+O` or a bare key like `Slash`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct HotkeyBindings {
+    /// Toggles click-through on the overlay.
+    overlay: String,
+    /// Brings the chat window up. Windows matches these by key name through
+    /// the key listener instead, so Roblox still receives the keystroke.
+    chat: Vec<String>,
+}
+
+impl Default for HotkeyBindings {
+    fn default() -> Self {
+        Self {
+            overlay: "Ctrl+Shift+O".to_string(),
+            chat: vec!["Slash".to_string(), "NumpadDivide".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HotkeyAction {
+    ToggleClickThrough,
+    ShowChat,
+}
+
+#[derive(Default)]
+struct Registered {
+    bindings: HotkeyBindings,
+    #[cfg(desktop)]
+    overlay: Option<Shortcut>,
+    #[cfg(desktop)]
+    chat: Vec<Shortcut>,
+}
+
+#[derive(Default)]
+pub(crate) struct HotkeyState {
+    registered: Mutex<Registered>,
+}
+
+fn lock_registered(app: &AppHandle) -> Result<std::sync::MutexGuard<'_, Registered>> {
+    app.state::<HotkeyState>()
+        .inner()
+        .registered
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock hotkeys: {err}"))
+}
+
+#[cfg(desktop)]
+fn parse_shortcut(binding: &str) -> Result<Shortcut> {
+    binding
+        .parse::<Shortcut>()
+        .map_err(|err| anyhow::anyhow!("Invalid hotkey {binding}: {err}"))
+}
+
+#[cfg(not(desktop))]
+fn parse_shortcut(_binding: &str) -> Result<()> {
+    Ok(())
+}
+
+/// Checks and normalizes `bindings` without applying them.
+pub(crate) fn validate_bindings(bindings: HotkeyBindings) -> Result<HotkeyBindings> {
+    let overlay = bindings.overlay.trim().to_string();
+    if overlay.is_empty() {
+        anyhow::bail!("The overlay hotkey can't be empty");
+    }
+    parse_shortcut(&overlay)?;
+
+    let mut chat: Vec<String> = Vec::new();
+    for key in bindings.chat {
+        let key = key.trim();
+        if key.is_empty() || chat.iter().any(|existing| existing == key) {
+            continue;
+        }
+        if key == overlay {
+            anyhow::bail!("{key} is already the overlay hotkey");
+        }
+        parse_shortcut(key)?;
+        chat.push(key.to_string());
+    }
+    if chat.len() > MAX_CHAT_KEYS {
+        anyhow::bail!("At most {MAX_CHAT_KEYS} chat hotkeys are allowed");
+    }
+
+    Ok(HotkeyBindings { overlay, chat })
+}
+
+/// Swaps the registered shortcuts for `bindings`, putting the old ones back
+/// when any of the new ones is taken.
+#[cfg(desktop)]
+fn register(app: &AppHandle, registered: &mut Registered, bindings: HotkeyBindings) -> Result<()> {
+    let overlay = parse_shortcut(&bindings.overlay)?;
+    // Windows reads the chat keys through the rdev listener instead.
+    let chat = if cfg!(target_os = "linux") {
+        bindings
+            .chat
+            .iter()
+            .map(|key| parse_shortcut(key))
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        Vec::new()
+    };
+
+    let shortcuts = app.global_shortcut();
+    let previous: Vec<Shortcut> = registered
+        .chat
+        .iter()
+        .copied()
+        .chain(registered.overlay)
+        .collect();
+    shortcuts.unregister_all().context("release hotkeys")?;
+    let mut next = chat.clone();
+    next.push(overlay);
+    if let Err(err) = shortcuts.register_multiple(next) {
+        let _ = shortcuts.unregister_all();
+        let _ = shortcuts.register_multiple(previous);
+        return Err(anyhow::Error::from(err).context("A hotkey is already in use"));
+    }
+
+    registered.bindings = bindings;
+    registered.overlay = Some(overlay);
+    registered.chat = chat;
+    Ok(())
+}
+
+#[cfg(not(desktop))]
+fn register(_app: &AppHandle, registered: &mut Registered, bindings: HotkeyBindings) -> Result<()> {
+    registered.bindings = bindings;
+    Ok(())
+}
+
+/// Registers the saved bindings, falling back to the defaults when they are
+/// invalid or taken.
+pub(crate) fn load_hotkeys(app: &AppHandle) -> Result<()> {
+    let saved = crate::storage::store(app)
+        .ok()
+        .and_then(|store| store.get(HOTKEYS_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .and_then(|bindings| validate_bindings(bindings).ok())
+        .unwrap_or_default();
+
+    let mut registered = lock_registered(app)?;
+    if saved != HotkeyBindings::default() {
+        match register(app, &mut registered, saved) {
+            Ok(()) => return Ok(()),
+            Err(err) => tracing::warn!("Falling back to the default hotkeys: {err:#}"),
+        }
+    }
+    register(app, &mut registered, HotkeyBindings::default())
+}
+
+/// Which action a pressed shortcut triggers, if any.
+#[cfg(desktop)]
+pub(crate) fn action_for(app: &AppHandle, shortcut: &Shortcut) -> Option<HotkeyAction> {
+    let registered = lock_registered(app).ok()?;
+    if registered.overlay.as_ref() == Some(shortcut) {
+        Some(HotkeyAction::ToggleClickThrough)
+    } else if registered.chat.contains(shortcut) {
+        Some(HotkeyAction::ShowChat)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn get_hotkeys(app: &AppHandle) -> Result<HotkeyBindings> {
+    Ok(lock_registered(app)?.bindings.clone())
+}
+
+pub(crate) fn set_hotkeys(app: &AppHandle, bindings: HotkeyBindings) -> Result<HotkeyBindings> {
+    let bindings = validate_bindings(bindings)?;
+    register(app, &mut *lock_registered(app)?, bindings.clone())?;
+
+    let store = crate::storage::store(app).context("open settings store")?;
+    store.set(
+        HOTKEYS_KEY,
+        serde_json::to_value(&bindings).context("serialize hotkeys")?,
+    );
+    store.save().context("save settings store")?;
+    let _ = app.emit(HOTKEYS_CHANGED_EVENT, &bindings);
+    Ok(bindings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(overlay: &str, chat: &[&str]) -> HotkeyBindings {
+        HotkeyBindings {
+            overlay: overlay.to_string(),
+            chat: chat.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn validates_and_tidies_bindings() {
+        let tidy = validate_bindings(bindings(" Alt+C ", &["Slash", " ", "Slash", "F2"])).unwrap();
+        assert_eq!(tidy, bindings("Alt+C", &["Slash", "F2"]));
+
+        assert!(validate_bindings(bindings("", &["Slash"])).is_err());
+        assert!(validate_bindings(bindings("Ctrl+Nope", &[])).is_err());
+        assert!(validate_bindings(bindings("Slash", &["Slash"])).is_err());
+        assert!(validate_bindings(bindings("F1", &["F2", "F3", "F4", "F5", "F6"])).is_err());
+        assert!(validate_bindings(HotkeyBindings::default()).is_ok());
+    }
+}
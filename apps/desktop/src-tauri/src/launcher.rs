@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+#[cfg(target_os = "linux")]
+const SOBER_FLATPAK_ID: &str = "org.vinegarhq.Sober";
+const ROBLOX_WEB_BASE: &str = "https://www.roblox.com";
+
+static PLACE_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[1-9]\d{0,19}$").expect("valid place id regex"));
+
+/// A specific Roblox server: the place plus the Job ID chat is keyed by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ServerTarget {
+    place_id: String,
+    job_id: String,
+}
+
+impl ServerTarget {
+    pub(crate) fn new(place_id: &str, job_id: &str) -> Result<Self> {
+        let place_id = place_id.trim();
+        let job_id = job_id.trim().to_ascii_lowercase();
+
+        if !PLACE_ID_RE.is_match(place_id) {
+            anyhow::bail!("Invalid place id");
+        }
+        if job_id == "global" || !crate::roblox::is_valid_job_id(&job_id) {
+            anyhow::bail!("Invalid Job ID");
+        }
+
+        Ok(Self {
+            place_id: place_id.to_string(),
+            job_id,
+        })
+    }
+
+    /// The URI the Roblox player registers for (`roblox://`).
+    fn launch_uri(&self) -> String {
+        format!(
+            "roblox://experiences/start?placeId={}&gameInstanceId={}",
+            self.place_id, self.job_id
+        )
+    }
+
+    /// The web link that opens the same server, for sharing and for Sober.
+    fn place_url(&self) -> String {
+        format!(
+            "{ROBLOX_WEB_BASE}/games/start?placeId={}&gameInstanceId={}",
+            self.place_id, self.job_id
+        )
+    }
+
+    /// A link that opens the server's chat channel in BloxChat.
+    fn chat_link(&self) -> String {
+        format!(
+            "{}://join/{}",
+            crate::deep_link::DEEP_LINK_SCHEME,
+            self.job_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ServerInvite {
+    place_id: String,
+    job_id: String,
+    launch_uri: String,
+    join_url: String,
+    chat_link: String,
+}
+
+impl From<&ServerTarget> for ServerInvite {
+    fn from(target: &ServerTarget) -> Self {
+        Self {
+            place_id: target.place_id.clone(),
+            job_id: target.job_id.clone(),
+            launch_uri: target.launch_uri(),
+            join_url: target.place_url(),
+            chat_link: target.chat_link(),
+        }
+    }
+}
+
+pub(crate) fn create_server_invite(place_id: &str, job_id: &str) -> Result<ServerInvite> {
+    Ok(ServerInvite::from(&ServerTarget::new(place_id, job_id)?))
+}
+
+#[cfg(target_os = "linux")]
+fn launch(target: &ServerTarget) -> Result<()> {
+    std::process::Command::new("flatpak")
+        .args(["run", SOBER_FLATPAK_ID, &target.place_url()])
+        .spawn()
+        .context("start Sober (is the org.vinegarhq.Sober flatpak installed?)")?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn launch(target: &ServerTarget) -> Result<()> {
+    tauri_plugin_opener::open_url(target.launch_uri(), None::<&str>)
+        .context("open Roblox launch URI (is Roblox installed?)")
+}
+
+/// Starts Roblox and joins the server `job_id` of `place_id`.
+pub(crate) fn launch_roblox_server(place_id: &str, job_id: &str) -> Result<ServerInvite> {
+    let target = ServerTarget::new(place_id, job_id)?;
+    launch(&target)?;
+    Ok(ServerInvite::from(&target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JOB: &str = "0f8fad5b-d9cb-469f-a165-70867728950e";
+
+    #[test]
+    fn validates_place_and_job_ids() {
+        assert!(ServerTarget::new("1818", JOB).is_ok());
+        assert!(ServerTarget::new(" 1818 ", &JOB.to_uppercase()).is_ok());
+        assert!(ServerTarget::new("0", JOB).is_err());
+        assert!(ServerTarget::new("12a", JOB).is_err());
+        assert!(ServerTarget::new("1818", "global").is_err());
+        assert!(ServerTarget::new("1818", "abc&placeId=1").is_err());
+    }
+
+    #[test]
+    fn builds_launch_uri_and_invite_links() {
+        let invite = create_server_invite("1818", JOB).unwrap();
+        assert_eq!(
+            invite.launch_uri,
+            format!("roblox://experiences/start?placeId=1818&gameInstanceId={JOB}")
+        );
+        assert_eq!(
+            invite.join_url,
+            format!("https://www.roblox.com/games/start?placeId=1818&gameInstanceId={JOB}")
+        );
+        assert_eq!(invite.chat_link, format!("bloxchat://join/{JOB}"));
+        assert!(crate::deep_link::parse_deep_link(&invite.chat_link).is_ok());
+    }
+}
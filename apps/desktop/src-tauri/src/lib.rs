@@ -1,57 +1,192 @@
+mod anchor;
+mod archive;
+mod auth_refresh;
+mod auth_store;
+mod chat_client;
+mod cli;
+mod clock;
 mod commands;
+mod deep_link;
+mod demo;
+mod diagnostics;
+mod hotkeys;
+mod launcher;
+mod link_safety;
+mod logging;
 mod media;
+mod message_guard;
+mod moderation;
+mod notifications;
+mod overlay;
+mod popout;
+mod profiles;
+mod relay;
+mod remote;
 mod roblox;
+mod session;
+mod settings;
+mod settings_transfer;
+mod storage;
+mod transcript;
 mod updater;
+mod window_state;
 
 use commands::*;
 #[cfg(target_os = "windows")]
 use rdev::{listen, Event, EventType};
-use std::path::PathBuf;
-use std::sync::{mpsc, Mutex};
+use std::sync::Mutex;
+#[cfg(not(target_os = "linux"))]
 use tauri::Emitter;
-#[cfg(target_os = "linux")]
 use tauri::Manager;
 use tauri::{AppHandle, WebviewWindow};
 #[cfg(desktop)]
 use tauri_plugin_deep_link::DeepLinkExt;
-#[cfg(target_os = "linux")]
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Shortcut, ShortcutState};
+#[cfg(desktop)]
+use tauri_plugin_global_shortcut::ShortcutState;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let cli = cli::CliOptions::from_process();
+    if cli.command == Some(remote::RemoteCommand::Status) {
+        std::process::exit(remote::run_status_client());
+    }
+
     let mut builder = tauri::Builder::default();
-    let initial_logs_path = roblox::default_roblox_logs_path();
-    let (watcher_control_tx, watcher_control_rx) = mpsc::channel::<PathBuf>();
 
+    // Launches with their own profile, server or logs folder run side by
+    // side with the main instance, e.g. against a local server.
+    let separate_instance = cli.instance_name();
+    if let Some(name) = &separate_instance {
+        storage::use_separate_instance(name.clone());
+    }
     #[cfg(desktop)]
-    {
+    if separate_instance.is_none() {
         builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
-            show_chat_window(app);
-            let _ = app.emit("single-instance", argv);
+            handle_second_instance(app, &argv);
         }));
     }
 
     builder
+        .manage(overlay::OverlayState::default())
+        .manage(anchor::AnchorState::default())
+        .manage(popout::PopoutState::default())
+        .manage(deep_link::DeepLinkState::default())
+        .manage(cli.clone())
+        .manage(chat_client::ChatClientState::default())
+        .manage(auth_store::AuthStoreState::default())
+        .manage(auth_refresh::AuthRefreshState::default())
+        .manage(notifications::NotificationState::default())
+        .manage(moderation::ModerationState::default())
+        .manage(message_guard::MessageGuardState::default())
+        .manage(link_safety::LinkSafetyState::default())
+        .manage(archive::ArchiveState::default())
+        .manage(session::SessionState::default())
+        .manage(demo::DemoState::default())
+        .manage(diagnostics::DiagnosticsState::default())
+        .manage(relay::RelayState::default())
+        .manage(hotkeys::HotkeyState::default())
+        .manage(logging::LoggingState::default())
+        .manage(settings::SettingsState::default())
+        .manage(profiles::ProfileState::default())
         .manage(roblox::LogSettingsState {
-            logs_path: Mutex::new(initial_logs_path.clone()),
-            watcher_control: Mutex::new(Some(watcher_control_tx)),
+            logs_path: Mutex::new(roblox::default_roblox_logs_path()),
+            watcher_control: Mutex::new(None),
         })
         .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_app_exit::init())
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_drpc::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(move |app| {
+            if let Err(err) = logging::init_logging(app.handle()) {
+                eprintln!("Failed to set up logging: {err:#}");
+            }
+            if let Some(err) = &cli.parse_error {
+                tracing::warn!("Ignoring invalid command line: {err}");
+            }
+            if let Some(command) = &cli.command {
+                if let Some(code) = remote::handle_own_command(app.handle(), command) {
+                    std::process::exit(code);
+                }
+            }
+            profiles::load_profiles(app.handle());
+            settings::load_settings(app.handle());
+            logging::follow_settings(app.handle());
+            roblox::follow_settings(app.handle());
+            chat_client::follow_settings(app.handle());
+            if let Err(err) = auth_store::migrate_from_store(app.handle()) {
+                tracing::error!("Failed to move auth session out of the settings store: {err:#}");
+            }
+            if let Some(name) = &cli.profile {
+                if let Err(err) = profiles::activate_profile(app.handle(), name) {
+                    tracing::error!("Failed to switch to profile {name}: {err:#}");
+                }
+            }
+            if let Err(err) = cli.apply(app.handle()) {
+                tracing::error!("Failed to apply command-line settings: {err:#}");
+            }
+            if cli.demo {
+                match demo::start_demo(app.handle()) {
+                    Ok(info) => tracing::info!("Demo backend running, {info:?}"),
+                    Err(err) => tracing::error!("Failed to start demo backend: {err:#}"),
+                }
+            } else {
+                auth_refresh::start(app.handle());
+            }
+            notifications::load_rules(app.handle());
+            moderation::load_settings(app.handle());
+            message_guard::load_rules(app.handle());
+            link_safety::load_blocklist(app.handle());
+            if let Err(err) = archive::open_archive(app.handle()) {
+                tracing::error!("Failed to open chat archive: {err:#}");
+            }
             prime_chat_window(app.handle());
-            tauri::async_runtime::spawn(updater::check_for_startup_update(app.handle().clone()));
-            roblox::start_log_watcher(initial_logs_path.clone(), watcher_control_rx);
+            if cli.minimized {
+                if let Some(window) = app.get_webview_window(CHAT_WINDOW_LABEL) {
+                    let _ = window.minimize();
+                }
+            }
+            popout::restore_popout_windows(app.handle());
+            if !cli.no_update {
+                tauri::async_runtime::spawn(updater::check_for_startup_update(
+                    app.handle().clone(),
+                ));
+            }
+            if let Err(err) = roblox::start_log_watcher(app.handle().clone()) {
+                tracing::error!("Failed to start Roblox log watcher: {err:#}");
+            }
+            if let Some(path) = &cli.record {
+                if let Err(err) = session::start_recording(app.handle(), path) {
+                    tracing::error!("Failed to start session recording: {err:#}");
+                }
+            }
+            if let Some(path) = &cli.replay {
+                if let Err(err) =
+                    session::queue_replay(app.handle(), path, cli.replay_speed.unwrap_or(1.0))
+                {
+                    tracing::error!("Failed to replay session: {err:#}");
+                }
+            }
+            // `bloxchat status` reports on the instance that remote commands
+            // reach, not on one running beside it.
+            if separate_instance.is_none() {
+                if let Err(err) = remote::start_status_server(app.handle().clone()) {
+                    tracing::error!("Failed to start status server: {err:#}");
+                }
+            }
             start_key_listener(app.handle().clone());
-            #[cfg(target_os = "linux")]
-            if let Err(err) = register_linux_shortcuts(app.handle().clone()) {
-                eprintln!("Failed to register Linux global shortcuts: {err:#}");
+            overlay::start_focus_watcher(app.handle().clone());
+            anchor::start_anchor_tracker(app.handle().clone());
+            #[cfg(desktop)]
+            if let Err(err) = register_global_shortcuts(app.handle().clone()) {
+                tracing::error!("Failed to register global shortcuts: {err:#}");
             }
             #[cfg(desktop)]
-            app.deep_link().register("bloxchat")?;
+            {
+                app.deep_link().register(deep_link::DEEP_LINK_SCHEME)?;
+                queue_startup_deep_links(app.handle());
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -62,7 +197,85 @@ pub fn run() {
             get_default_roblox_logs_path,
             get_roblox_logs_path,
             set_roblox_logs_path,
-            get_job_id
+            get_job_id,
+            get_window_geometry,
+            set_window_opacity,
+            set_window_collapsed,
+            get_overlay_status,
+            set_click_through,
+            toggle_click_through,
+            set_overlay_fade_opacity,
+            get_window_anchor,
+            set_window_anchor,
+            capture_window_anchor,
+            open_popout_window,
+            close_popout_window,
+            list_popout_windows,
+            set_popout_always_on_top,
+            get_window_channel,
+            set_main_channel,
+            take_pending_deep_links,
+            launch_roblox_server,
+            create_server_invite,
+            get_cli_options,
+            chat_subscribe,
+            chat_unsubscribe,
+            chat_reconnect,
+            chat_disconnect,
+            chat_publish,
+            chat_limits,
+            get_notification_rules,
+            set_notification_rules,
+            set_channel_muted,
+            archive_messages,
+            get_archived_messages,
+            search_archive,
+            list_archived_channels,
+            delete_archived_channel,
+            get_archive_retention,
+            set_archive_retention,
+            export_transcript,
+            start_session_recording,
+            stop_session_recording,
+            start_session_replay,
+            stop_session_replay,
+            start_queued_replay,
+            get_session_status,
+            get_demo_info,
+            start_relay,
+            stop_relay,
+            get_relay_info,
+            get_moderation_settings,
+            set_moderation_settings,
+            block_user,
+            unblock_user,
+            export_moderation_list,
+            import_moderation_list,
+            check_outgoing_message,
+            get_message_guard_rules,
+            set_message_guard_rules,
+            get_hotkeys,
+            set_hotkeys,
+            check_links,
+            open_link,
+            get_link_blocklist_info,
+            update_link_blocklist,
+            get_auth_session,
+            set_auth_session,
+            clear_auth_session,
+            get_settings,
+            update_settings,
+            export_settings,
+            preview_settings_import,
+            import_settings,
+            list_profiles,
+            create_profile,
+            delete_profile,
+            switch_profile,
+            link_profile_account,
+            set_profile_auto_switch,
+            create_diagnostics_bundle,
+            get_recent_logs
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -79,7 +292,7 @@ fn start_key_listener(app: AppHandle) {
         };
 
         if let Err(err) = listen(callback) {
-            eprintln!("Error in global shortcut listener: {:?}", err);
+            tracing::error!("Error in global shortcut listener: {:?}", err);
         }
     });
 }
@@ -89,8 +302,9 @@ fn start_key_listener(_app: AppHandle) {}
 
 const CHAT_WINDOW_LABEL: &str = "main";
 
-fn apply_chat_window_display_prefs(window: &WebviewWindow) {
-    let _ = window.set_always_on_top(true);
+fn apply_window_display_prefs(window: &WebviewWindow) {
+    let _ = window.set_always_on_top(popout::always_on_top(window.app_handle(), window.label()));
+    overlay::apply_click_through(window);
 
     #[cfg(target_os = "linux")]
     let _ = window.set_visible_on_all_workspaces(true);
@@ -103,15 +317,24 @@ fn prime_chat_window(app: &AppHandle) {
 
     #[cfg(not(target_os = "linux"))]
     let _ = window.set_focusable(true);
-    apply_chat_window_display_prefs(&window);
+    apply_window_display_prefs(&window);
+
+    if let Err(err) = window_state::restore_window_geometry(&window) {
+        tracing::error!("Failed to restore chat window geometry: {err:#}");
+    }
+    window_state::track_window_geometry(&window);
 }
 
 fn show_chat_window(app: &AppHandle) {
-    let Some(window) = app.get_webview_window(CHAT_WINDOW_LABEL) else {
+    show_window(app, CHAT_WINDOW_LABEL);
+}
+
+fn show_window(app: &AppHandle, label: &str) {
+    let Some(window) = app.get_webview_window(label) else {
         return;
     };
 
-    apply_chat_window_display_prefs(&window);
+    apply_window_display_prefs(&window);
     let _ = window.unminimize();
     let _ = window.show();
 
@@ -120,16 +343,78 @@ fn show_chat_window(app: &AppHandle) {
     #[cfg(not(target_os = "linux"))]
     let _ = window.set_focus();
     #[cfg(not(target_os = "linux"))]
-    let _ = app.emit("focus-chat-input", ());
+    let _ = app.emit_to(label, "focus-chat-input", ());
+}
+
+/// Arguments of a `bloxchat` process started while this one was running:
+/// either a remote-control command or an optional channel and deep link.
+/// Launches with a profile, server or logs folder never get here; they run
+/// as a separate instance.
+#[cfg(desktop)]
+fn handle_second_instance(app: &AppHandle, argv: &[String]) {
+    match cli::CliOptions::parse(argv.iter().skip(1).cloned(), |_| None) {
+        Ok(cli::CliOptions {
+            command: Some(command),
+            ..
+        }) => remote::dispatch(app, command),
+        Ok(options) => {
+            let ignored = options.ignored_by_running_instance();
+            if !ignored.is_empty() {
+                tracing::warn!(
+                    "BloxChat is already running; ignoring {}",
+                    ignored.join(", ")
+                );
+            }
+            if let Err(err) = options.apply(app) {
+                tracing::error!("Failed to apply command-line settings: {err:#}");
+            }
+            show_chat_window(app);
+            for url in deep_link::deep_link_args(argv) {
+                deep_link::dispatch_deep_link(app, url);
+            }
+        }
+        Err(err) => {
+            tracing::warn!("Ignoring arguments from second instance: {err:#}");
+            show_chat_window(app);
+        }
+    }
 }
 
-#[cfg(target_os = "linux")]
-fn register_linux_shortcuts(app: AppHandle) -> tauri::Result<()> {
-    let shortcuts = [
-        Shortcut::new(None, Code::Slash),
-        Shortcut::new(None, Code::NumpadDivide),
-    ];
+/// Links that launched the app are queued for the UI; later ones arrive through
+/// the single-instance handoff (or `on_open_url` on macOS) and are emitted.
+#[cfg(desktop)]
+fn queue_startup_deep_links(app: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    {
+        let urls = app
+            .deep_link()
+            .get_current()
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        for url in urls {
+            deep_link::queue_deep_link(app, url.as_str());
+        }
+
+        let handle = app.clone();
+        app.deep_link().on_open_url(move |event| {
+            for url in event.urls() {
+                deep_link::dispatch_deep_link(&handle, url.as_str());
+            }
+        });
+    }
 
+    #[cfg(not(target_os = "macos"))]
+    {
+        let args: Vec<String> = std::env::args().collect();
+        for url in deep_link::deep_link_args(&args) {
+            deep_link::queue_deep_link(app, url);
+        }
+    }
+}
+
+#[cfg(desktop)]
+fn register_global_shortcuts(app: AppHandle) -> anyhow::Result<()> {
     app.plugin(
         tauri_plugin_global_shortcut::Builder::new()
             .with_handler(move |app, shortcut, event| {
@@ -137,18 +422,18 @@ fn register_linux_shortcuts(app: AppHandle) -> tauri::Result<()> {
                     return;
                 }
 
-                if !shortcuts.iter().any(|registered| registered == shortcut) {
-                    return;
+                match hotkeys::action_for(app, shortcut) {
+                    Some(hotkeys::HotkeyAction::ToggleClickThrough) => {
+                        if let Err(err) = overlay::toggle_click_through(app) {
+                            tracing::error!("Failed to toggle click-through mode: {err:#}");
+                        }
+                    }
+                    Some(hotkeys::HotkeyAction::ShowChat) => show_chat_window(app),
+                    None => {}
                 }
-
-                show_chat_window(app);
             })
             .build(),
     )?;
 
-    app.global_shortcut()
-        .register_multiple(shortcuts)
-        .map_err(anyhow::Error::from)?;
-
-    Ok(())
+    hotkeys::load_hotkeys(&app)
 }
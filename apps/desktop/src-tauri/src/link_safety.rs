@@ -0,0 +1,515 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const BLOCKLIST_URL_KEY: &str = "linkBlocklistUrl";
+const BLOCKLIST_FILE: &str = "link-blocklist.txt";
+const BUNDLED_BLOCKLIST: &str = include_str!("link_blocklist.txt");
+const MAX_BLOCKLIST_BYTES: usize = 4 << 20;
+const BLOCKLIST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Domains a link has to belong to to be treated as Roblox's own.
+const ROBLOX_DOMAINS: [&str; 5] = [
+    "roblox.com",
+    "rbxcdn.com",
+    "rbx.com",
+    "robloxlabs.com",
+    "ro.blox.com",
+];
+/// Edits within which a domain counts as a lookalike of one of the longer
+/// Roblox domains.
+const LOOKALIKE_DISTANCE: usize = 2;
+
+/// Links with or without a scheme; bare hosts need a dot and a letter TLD.
+static LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\bhttps?://[^\s<>]+|(?:[\p{L}\p{N}-]+\.)+\p{L}{2,}(?:/[^\s<>]*)?")
+        .expect("valid link regex")
+});
+
+/// Characters commonly swapped in for Latin letters, mapped to what they
+/// imitate.
+#[rustfmt::skip]
+const CONFUSABLES: &[(char, char)] = &[
+    ('а', 'a'), ('е', 'e'), ('о', 'o'), ('р', 'p'), ('с', 'c'), ('у', 'y'),
+    ('х', 'x'), ('і', 'i'), ('ј', 'j'), ('ѕ', 's'), ('ԁ', 'd'), ('ɡ', 'g'),
+    ('ο', 'o'), ('α', 'a'), ('ε', 'e'), ('ι', 'i'), ('κ', 'k'), ('ν', 'v'),
+    ('ρ', 'p'), ('τ', 't'), ('χ', 'x'), ('β', 'b'), ('ℓ', 'l'), ('ı', 'i'),
+    ('0', 'o'), ('1', 'l'), ('3', 'e'), ('5', 's'), ('8', 'b'),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum LinkRisk {
+    Safe,
+    /// Worth a warning before opening.
+    Suspicious,
+    /// Known or near-certain scam.
+    Dangerous,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum LinkReason {
+    /// The host is on the blocklist.
+    Blocklisted {
+        domain: String,
+    },
+    /// Non-Latin characters made to read as a Roblox domain.
+    Homograph {
+        imitates: String,
+    },
+    /// A near miss of a Roblox domain, e.g. `rob1ox.com` or `roblox.co`.
+    Lookalike {
+        imitates: String,
+    },
+    /// A Roblox domain used as a subdomain of someone else's.
+    RobloxSubdomain {
+        domain: String,
+    },
+    /// An internationalized domain that can't be shown as typed.
+    Punycode,
+    /// Mentions Roblox or Robux without being a Roblox domain.
+    RobloxKeyword,
+    NotHttp,
+}
+
+impl LinkReason {
+    fn risk(&self) -> LinkRisk {
+        match self {
+            Self::Blocklisted { .. } | Self::Homograph { .. } | Self::RobloxSubdomain { .. } => {
+                LinkRisk::Dangerous
+            }
+            Self::Lookalike { .. } | Self::Punycode | Self::RobloxKeyword | Self::NotHttp => {
+                LinkRisk::Suspicious
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LinkVerdict {
+    /// The link as written in the message.
+    link: String,
+    /// Where it points, with a scheme added when the message left it out.
+    url: String,
+    host: String,
+    risk: LinkRisk,
+    reasons: Vec<LinkReason>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BlocklistInfo {
+    bundled_domains: usize,
+    downloaded_domains: usize,
+    source_url: Option<String>,
+}
+
+/// Domains from a blocklist file; `#` starts a comment.
+fn parse_blocklist(text: &str) -> BTreeSet<String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .map(|domain| domain.trim_start_matches("*.").trim_end_matches('.'))
+        .filter(|domain| !domain.is_empty() && !domain.contains(char::is_whitespace))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+static BUNDLED: LazyLock<BTreeSet<String>> = LazyLock::new(|| parse_blocklist(BUNDLED_BLOCKLIST));
+
+/// `host` and every domain above it, e.g. `a.b.com`, `b.com`, `com`.
+fn host_suffixes(host: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(host), |suffix| {
+        suffix.split_once('.').map(|(_, parent)| parent)
+    })
+}
+
+fn is_same_or_subdomain(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|rest| rest.ends_with('.'))
+}
+
+/// The part of `host` someone registered, e.g. `roblox.com` for
+/// `www.roblox.com` and `roblox.co.uk` for `www.roblox.co.uk`.
+fn registrable_domain(host: &str) -> &str {
+    let labels: Vec<&str> = host.split('.').collect();
+    let take = match labels.as_slice() {
+        [.., second, tld] if tld.len() == 2 && second.len() <= 3 && labels.len() > 2 => 3,
+        _ => 2,
+    };
+    let skip = labels.len().saturating_sub(take);
+    let start: usize = labels[..skip].iter().map(|label| label.len() + 1).sum();
+    &host[start..]
+}
+
+fn skeleton(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| {
+            CONFUSABLES
+                .iter()
+                .find(|(from, _)| *from == c)
+                .map_or(c, |(_, to)| *to)
+        })
+        .collect::<String>()
+        .replace("rn", "m")
+        .replace("vv", "w")
+}
+
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    for (i, l) in left.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, r) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(l != *r);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[right.len()]
+}
+
+fn roblox_domain_imitated(host: &str) -> Option<&'static str> {
+    let registrable = registrable_domain(host);
+    let shape = skeleton(registrable);
+    ROBLOX_DOMAINS
+        .iter()
+        .filter(|domain| domain.matches('.').count() == 1)
+        .find(|domain| {
+            shape == **domain
+                || (domain.len() >= 10 && edit_distance(registrable, domain) <= LOOKALIKE_DISTANCE)
+        })
+        .copied()
+}
+
+/// Judges a single link. `blocklist` holds the downloaded domains; the
+/// bundled ones are always checked.
+fn check_link(link: &str, blocklist: &BTreeSet<String>) -> Option<LinkVerdict> {
+    let trimmed = link.trim_end_matches(['.', ',', '!', '?', ')', ']', '\'', '"', ':', ';']);
+    let has_scheme = trimmed
+        .split_once("://")
+        .is_some_and(|(scheme, _)| scheme.chars().all(|c| c.is_ascii_alphanumeric()));
+    let url = if has_scheme {
+        trimmed.to_string()
+    } else {
+        format!("https://{trimmed}")
+    };
+
+    // The host as typed, before the URL parser turns it into punycode.
+    let typed_host = url
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(&url)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or("")
+        .rsplit('@')
+        .next()
+        .unwrap_or("")
+        .split(':')
+        .next()
+        .unwrap_or("")
+        .trim_end_matches('.')
+        .to_lowercase();
+    let parsed = reqwest::Url::parse(&url).ok()?;
+    let host = parsed.host_str()?.trim_end_matches('.').to_lowercase();
+
+    let mut reasons = Vec::new();
+    if !matches!(parsed.scheme(), "http" | "https") {
+        reasons.push(LinkReason::NotHttp);
+    }
+
+    if let Some(domain) = host_suffixes(&host)
+        .find_map(|suffix| BUNDLED.get(suffix).or_else(|| blocklist.get(suffix)))
+    {
+        reasons.push(LinkReason::Blocklisted {
+            domain: domain.clone(),
+        });
+    }
+
+    let official = ROBLOX_DOMAINS
+        .iter()
+        .any(|domain| is_same_or_subdomain(&host, domain));
+    if !official {
+        if !typed_host.is_ascii() {
+            if let Some(imitates) = roblox_domain_imitated(&typed_host) {
+                reasons.push(LinkReason::Homograph {
+                    imitates: imitates.to_string(),
+                });
+            }
+        } else if let Some(imitates) = roblox_domain_imitated(&host) {
+            reasons.push(LinkReason::Lookalike {
+                imitates: imitates.to_string(),
+            });
+        }
+
+        if let Some(domain) = ROBLOX_DOMAINS.iter().find(|domain| {
+            host.starts_with(&format!("{domain}.")) || host.contains(&format!(".{domain}."))
+        }) {
+            reasons.push(LinkReason::RobloxSubdomain {
+                domain: domain.to_string(),
+            });
+        }
+
+        if host.split('.').any(|label| label.starts_with("xn--")) {
+            reasons.push(LinkReason::Punycode);
+        }
+
+        let shape = skeleton(&typed_host);
+        if reasons.is_empty() && (shape.contains("roblox") || shape.contains("robux")) {
+            reasons.push(LinkReason::RobloxKeyword);
+        }
+    }
+
+    Some(LinkVerdict {
+        link: link.to_string(),
+        url: parsed.to_string(),
+        host,
+        risk: reasons
+            .iter()
+            .map(LinkReason::risk)
+            .max()
+            .unwrap_or(LinkRisk::Safe),
+        reasons,
+    })
+}
+
+fn check_content(content: &str, blocklist: &BTreeSet<String>) -> Vec<LinkVerdict> {
+    LINK_RE
+        .find_iter(content)
+        .filter_map(|found| check_link(found.as_str(), blocklist))
+        .collect()
+}
+
+#[derive(Default)]
+pub(crate) struct LinkSafetyState {
+    downloaded: Mutex<BTreeSet<String>>,
+}
+
+fn lock_downloaded(app: &AppHandle) -> Result<std::sync::MutexGuard<'_, BTreeSet<String>>> {
+    app.state::<LinkSafetyState>()
+        .inner()
+        .downloaded
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock link blocklist: {err}"))
+}
+
+fn blocklist_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(crate::storage::data_dir(app)?.join(BLOCKLIST_FILE))
+}
+
+/// Loads the last downloaded blocklist, if there is one.
+pub(crate) fn load_blocklist(app: &AppHandle) {
+    let Ok(path) = blocklist_path(app) else {
+        return;
+    };
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return;
+    };
+    if let Ok(mut downloaded) = lock_downloaded(app) {
+        *downloaded = parse_blocklist(&text);
+    }
+}
+
+/// Checks every link in a chat message.
+pub(crate) fn check_links(app: &AppHandle, content: &str) -> Result<Vec<LinkVerdict>> {
+    let downloaded = lock_downloaded(app)?;
+    Ok(check_content(content, &downloaded))
+}
+
+/// Opens `link` in the browser. Anything not safe needs `acknowledged`, set
+/// once the user has seen the warning.
+pub(crate) fn open_link(app: &AppHandle, link: &str, acknowledged: bool) -> Result<LinkVerdict> {
+    let verdict = {
+        let downloaded = lock_downloaded(app)?;
+        check_link(link.trim(), &downloaded)
+    }
+    .with_context(|| format!("Invalid link: {link}"))?;
+    if verdict.risk != LinkRisk::Safe && !acknowledged {
+        anyhow::bail!("{} looks unsafe. Confirm to open it anyway.", verdict.host);
+    }
+
+    tauri_plugin_opener::open_url(&verdict.url, None::<&str>).context("open link")?;
+    Ok(verdict)
+}
+
+fn blocklist_info(app: &AppHandle) -> Result<BlocklistInfo> {
+    let source_url = crate::storage::store(app)
+        .ok()
+        .and_then(|store| store.get(BLOCKLIST_URL_KEY))
+        .and_then(|value| value.as_str().map(str::to_string));
+    Ok(BlocklistInfo {
+        bundled_domains: BUNDLED.len(),
+        downloaded_domains: lock_downloaded(app)?.len(),
+        source_url,
+    })
+}
+
+pub(crate) fn get_link_blocklist_info(app: &AppHandle) -> Result<BlocklistInfo> {
+    blocklist_info(app)
+}
+
+/// Downloads the blocklist from `source_url`, or the last URL used, and
+/// keeps it for later starts.
+pub(crate) async fn update_link_blocklist(
+    app: &AppHandle,
+    source_url: Option<String>,
+) -> Result<BlocklistInfo> {
+    let store = crate::storage::store(app).context("open settings store")?;
+    let source_url = match source_url {
+        Some(url) => url.trim().to_string(),
+        None => store
+            .get(BLOCKLIST_URL_KEY)
+            .and_then(|value| value.as_str().map(str::to_string))
+            .context("No blocklist URL configured")?,
+    };
+    let parsed = reqwest::Url::parse(&source_url).context("Invalid blocklist URL")?;
+    if parsed.scheme() != "https" {
+        anyhow::bail!("Blocklist URL must use https");
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(BLOCKLIST_TIMEOUT)
+        .build()
+        .context("build HTTP client")?;
+    let mut response = client
+        .get(parsed)
+        .send()
+        .await
+        .context("send blocklist request")?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Blocklist download failed with status {}",
+            response.status()
+        );
+    }
+
+    // Checked up front when the server says, and again while reading when it
+    // doesn't, so an oversized list is never held in memory.
+    let too_large = || anyhow::anyhow!("Blocklist is larger than {} MB", MAX_BLOCKLIST_BYTES >> 20);
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_BLOCKLIST_BYTES as u64)
+    {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.context("read blocklist")? {
+        if bytes.len() + chunk.len() > MAX_BLOCKLIST_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    let domains = parse_blocklist(&text);
+    if domains.is_empty() {
+        anyhow::bail!("Blocklist has no domains");
+    }
+
+    let path = blocklist_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).context("create app data dir")?;
+    }
+    std::fs::write(&path, text.as_bytes()).context("save blocklist")?;
+    *lock_downloaded(app)? = domains;
+    store.set(BLOCKLIST_URL_KEY, source_url);
+    blocklist_info(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verdict(link: &str) -> LinkVerdict {
+        check_link(link, &BTreeSet::new()).unwrap()
+    }
+
+    #[test]
+    fn extracts_links_from_messages() {
+        let links: Vec<String> = check_content(
+            "join https://www.roblox.com/games/606849621, or www.rbxcdn.com. also rob1ox.com/login!",
+            &BTreeSet::new(),
+        )
+        .into_iter()
+        .map(|verdict| verdict.url)
+        .collect();
+        assert_eq!(
+            links,
+            [
+                "https://www.roblox.com/games/606849621",
+                "https://www.rbxcdn.com/",
+                "https://rob1ox.com/login",
+            ]
+        );
+        assert!(check_content("gg wp... see you.", &BTreeSet::new()).is_empty());
+    }
+
+    #[test]
+    fn flags_lookalike_and_homograph_domains() {
+        assert_eq!(verdict("https://web.roblox.com/home").risk, LinkRisk::Safe);
+        assert_eq!(verdict("https://example.org").risk, LinkRisk::Safe);
+
+        let homograph = verdict("https://rоblox.com/login");
+        assert_eq!(homograph.risk, LinkRisk::Dangerous);
+        assert_eq!(
+            homograph.reasons[0],
+            LinkReason::Homograph {
+                imitates: "roblox.com".to_string()
+            }
+        );
+        assert!(homograph.host.starts_with("xn--"));
+
+        for lookalike in ["rob1ox.com", "robiox.com", "roblox.co", "roblx.com"] {
+            let checked = verdict(lookalike);
+            assert!(
+                checked
+                    .reasons
+                    .iter()
+                    .any(|reason| matches!(reason, LinkReason::Lookalike { .. })),
+                "{lookalike}: {checked:?}"
+            );
+        }
+
+        let nested = verdict("http://roblox.com.account-verify.ru/");
+        assert_eq!(nested.risk, LinkRisk::Dangerous);
+        assert_eq!(
+            verdict("https://roblox-trading-hub.net").reasons,
+            [LinkReason::RobloxKeyword]
+        );
+    }
+
+    #[test]
+    fn checks_bundled_and_downloaded_blocklists() {
+        let blocked = verdict("https://www.free-robux.com/claim");
+        assert_eq!(blocked.risk, LinkRisk::Dangerous);
+        assert_eq!(
+            blocked.reasons[0],
+            LinkReason::Blocklisted {
+                domain: "free-robux.com".to_string()
+            }
+        );
+
+        let downloaded =
+            parse_blocklist("# scams\n*.Evil-Trades.example \nplain.example # note\n\n");
+        assert_eq!(
+            downloaded.iter().collect::<Vec<_>>(),
+            ["evil-trades.example", "plain.example"]
+        );
+        let checked = check_link("https://shop.evil-trades.example", &downloaded).unwrap();
+        assert_eq!(checked.risk, LinkRisk::Dangerous);
+        assert_eq!(
+            check_link("https://notevil-trades.example", &downloaded)
+                .unwrap()
+                .risk,
+            LinkRisk::Safe
+        );
+    }
+}
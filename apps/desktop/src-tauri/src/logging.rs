@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Layer, Registry};
+
+/// Folder in the data dir the app writes its own logs to.
+pub(crate) const APP_LOGS_DIR: &str = "logs";
+const LOG_FILE_PREFIX: &str = "bloxchat";
+const LOG_FILE_SUFFIX: &str = "log";
+/// Days of log files kept; one file per day.
+const MAX_LOG_FILES: usize = 7;
+const MAX_RECENT_LOGS: usize = 500;
+
+/// Most to least severe, so a level includes everything before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+
+    fn from_level(level: &Level) -> LogLevel {
+        match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            Level::TRACE => LogLevel::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LogEntry {
+    at_ms: u64,
+    level: LogLevel,
+    target: String,
+    message: String,
+}
+
+/// Keeps the last [`MAX_RECENT_LOGS`] events for [`get_recent_logs`].
+struct RecentLogs {
+    entries: Arc<Mutex<VecDeque<LogEntry>>>,
+}
+
+/// Collects an event's message followed by its other fields as `key=value`.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={value}", field.name());
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RecentLogs {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let entry = LogEntry {
+            at_ms: crate::clock::now_ms(),
+            level: LogLevel::from_level(metadata.level()),
+            target: metadata.target().to_string(),
+            message: visitor.message + &visitor.fields,
+        };
+
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() == MAX_RECENT_LOGS {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct LoggingState {
+    recent: Arc<Mutex<VecDeque<LogEntry>>>,
+    level: Mutex<Option<reload::Handle<LevelFilter, Registry>>>,
+    /// Flushes the file writer when dropped, so it lives as long as the app.
+    file_guard: Mutex<Option<WorkerGuard>>,
+}
+
+/// Sends `tracing` events to daily log files in the data dir, stderr and
+/// the recent log buffer. Runs first in setup so nothing is missed.
+pub(crate) fn init_logging(app: &AppHandle) -> Result<()> {
+    let state = app.state::<LoggingState>();
+    let dir = crate::storage::data_dir(app)?.join(APP_LOGS_DIR);
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .with_context(|| format!("create log files in {}", dir.display()))?;
+    let (file_writer, file_guard) = tracing_appender::non_blocking(appender);
+    let (level, level_handle) = reload::Layer::new(LogLevel::default().filter());
+
+    tracing_subscriber::registry()
+        .with(level)
+        .with(fmt::layer().with_writer(file_writer).with_ansi(false))
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(RecentLogs {
+            entries: state.recent.clone(),
+        })
+        .try_init()
+        .context("install logger")?;
+
+    *state
+        .level
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock log level: {err}"))? = Some(level_handle);
+    *state
+        .file_guard
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock log file: {err}"))? = Some(file_guard);
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        tracing::error!("{info}");
+        default_hook(info);
+    }));
+    Ok(())
+}
+
+fn set_log_level(app: &AppHandle, level: LogLevel) -> Result<()> {
+    let state = app.state::<LoggingState>();
+    let handle = state
+        .level
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock log level: {err}"))?;
+    if let Some(handle) = handle.as_ref() {
+        handle.reload(level.filter()).context("change log level")?;
+    }
+    Ok(())
+}
+
+/// Applies the `logLevel` setting now and whenever it changes.
+pub(crate) fn follow_settings(app: &AppHandle) {
+    let apply = |app: &AppHandle, level: LogLevel| {
+        if let Err(err) = set_log_level(app, level) {
+            tracing::error!("Failed to change log level: {err:#}");
+        }
+    };
+
+    match crate::settings::get_settings(app) {
+        Ok(settings) => apply(app, settings.log_level),
+        Err(err) => tracing::error!("Failed to read log level setting: {err:#}"),
+    }
+    let handle = app.clone();
+    crate::settings::on_change(app, "logLevel", move |settings| {
+        apply(&handle, settings.log_level)
+    });
+}
+
+fn filter_recent(entries: &VecDeque<LogEntry>, min_level: LogLevel, limit: usize) -> Vec<LogEntry> {
+    let mut recent: Vec<LogEntry> = entries
+        .iter()
+        .rev()
+        .filter(|entry| entry.level <= min_level)
+        .take(limit)
+        .cloned()
+        .collect();
+    recent.reverse();
+    recent
+}
+
+/// The newest `limit` events at `min_level` or more severe, oldest first.
+pub(crate) fn get_recent_logs(
+    app: &AppHandle,
+    min_level: LogLevel,
+    limit: usize,
+) -> Result<Vec<LogEntry>> {
+    let state = app.state::<LoggingState>();
+    let entries = state
+        .recent
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock recent logs: {err}"))?;
+    Ok(filter_recent(&entries, min_level, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_recent_events_with_their_fields() {
+        let entries = Arc::new(Mutex::new(VecDeque::new()));
+        let subscriber = tracing_subscriber::registry().with(RecentLogs {
+            entries: entries.clone(),
+        });
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("Connected");
+            tracing::warn!(channel = "global", "Chat subscription failed");
+            tracing::error!("Failed to save: {}", "disk full");
+        });
+
+        let entries = entries.lock().unwrap();
+        let messages: Vec<_> = entries.iter().map(|entry| entry.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Connected",
+                "Chat subscription failed channel=global",
+                "Failed to save: disk full"
+            ]
+        );
+
+        let problems = filter_recent(&entries, LogLevel::Warn, 10);
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].level, LogLevel::Warn);
+        let latest = filter_recent(&entries, LogLevel::Trace, 1);
+        assert_eq!(latest[0].level, LogLevel::Error);
+    }
+}
@@ -0,0 +1,589 @@
+use crate::chat_client::ChatMessageBatch;
+use crate::clock::now_ms;
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const MODERATION_KEY: &str = "moderation";
+const LIST_FORMAT: &str = "bloxchat-moderation";
+const LIST_VERSION: u32 = 1;
+const MAX_PATTERN_LENGTH: usize = 200;
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+/// Recent messages remembered per channel for flood detection.
+const MAX_RECENT_MESSAGES: usize = 200;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct BlockedUser {
+    /// Username when blocked, for display only.
+    username: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FilterKind {
+    /// Case-insensitive whole word or phrase.
+    Word,
+    /// Case-insensitive regular expression.
+    Regex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FilterAction {
+    /// Replace the matched text with asterisks.
+    Mask,
+    /// Drop the message.
+    Hide,
+    /// Show the message folded away behind its reason.
+    Collapse,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ContentFilter {
+    kind: FilterKind,
+    pattern: String,
+    action: FilterAction,
+}
+
+/// Collapses a message once the same text was seen `threshold` times in a
+/// channel within `window_secs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct FloodSettings {
+    enabled: bool,
+    threshold: u32,
+    window_secs: u32,
+}
+
+impl Default for FloodSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 3,
+            window_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ModerationSettings {
+    /// Keyed by Roblox user ID.
+    blocked_users: BTreeMap<String, BlockedUser>,
+    filters: Vec<ContentFilter>,
+    flood: FloodSettings,
+}
+
+/// The shareable part of the settings, as written by `export_moderation_list`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModerationList {
+    format: String,
+    version: u32,
+    #[serde(default)]
+    blocked_users: BTreeMap<String, BlockedUser>,
+    #[serde(default)]
+    filters: Vec<ContentFilter>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "camelCase")]
+pub(crate) enum CollapseReason {
+    Filter {
+        pattern: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Flood {
+        repeat_count: usize,
+    },
+}
+
+/// A batch after moderation, as sent to the UI.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ModeratedBatch {
+    #[serde(flatten)]
+    pub(crate) batch: ChatMessageBatch,
+    /// Messages to show folded, by message id.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    collapsed: BTreeMap<String, CollapseReason>,
+    hidden_count: usize,
+}
+
+fn compile_filter(filter: &ContentFilter) -> Result<Regex> {
+    let pattern = filter.pattern.trim();
+    if pattern.is_empty() {
+        anyhow::bail!("Filter pattern cannot be empty");
+    }
+    if pattern.len() > MAX_PATTERN_LENGTH {
+        anyhow::bail!("Filter pattern is longer than {MAX_PATTERN_LENGTH} characters");
+    }
+
+    let source = match filter.kind {
+        FilterKind::Regex => pattern.to_string(),
+        FilterKind::Word => {
+            // `\b` only holds next to word characters.
+            let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+            let start = if is_word(pattern.chars().next()) {
+                r"\b"
+            } else {
+                ""
+            };
+            let end = if is_word(pattern.chars().next_back()) {
+                r"\b"
+            } else {
+                ""
+            };
+            format!("{start}{}{end}", regex::escape(pattern))
+        }
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(true)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .with_context(|| format!("Invalid filter: {pattern}"))
+}
+
+/// Lowercased with whitespace collapsed, so trivial variations still count
+/// as the same message.
+fn flood_key(content: &str) -> String {
+    content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn mask(regex: &Regex, content: &str) -> String {
+    regex
+        .replace_all(content, |caps: &regex::Captures| {
+            "*".repeat(caps[0].chars().count())
+        })
+        .into_owned()
+}
+
+#[derive(Default)]
+struct ModerationEngine {
+    settings: ModerationSettings,
+    filters: Vec<(ContentFilter, Regex)>,
+    /// `(flood key, received at)` per channel.
+    recent: HashMap<String, VecDeque<(String, u64)>>,
+}
+
+impl ModerationEngine {
+    fn compile(mut settings: ModerationSettings) -> Result<Self> {
+        for filter in &mut settings.filters {
+            filter.pattern = filter.pattern.trim().to_string();
+        }
+        settings.flood.threshold = settings.flood.threshold.max(1);
+
+        let filters = settings
+            .filters
+            .iter()
+            .map(|filter| Ok((filter.clone(), compile_filter(filter)?)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            settings,
+            filters,
+            recent: HashMap::new(),
+        })
+    }
+
+    /// How many times `content` was seen in `channel` inside the flood
+    /// window, including this time.
+    fn repeat_count(&mut self, channel: &str, content: &str, now: u64) -> usize {
+        let window_ms = u64::from(self.settings.flood.window_secs) * 1000;
+        let recent = self.recent.entry(channel.to_string()).or_default();
+        while recent
+            .front()
+            .is_some_and(|(_, at)| now.saturating_sub(*at) > window_ms)
+        {
+            recent.pop_front();
+        }
+        if recent.len() >= MAX_RECENT_MESSAGES {
+            recent.pop_front();
+        }
+
+        let key = flood_key(content);
+        let count = recent.iter().filter(|(seen, _)| *seen == key).count() + 1;
+        recent.push_back((key, now));
+        count
+    }
+
+    fn moderate(&mut self, mut batch: ChatMessageBatch, now: u64) -> ModeratedBatch {
+        let mut collapsed = BTreeMap::new();
+        let mut hidden_count = 0;
+        let mut messages = Vec::with_capacity(batch.messages.len());
+
+        'messages: for mut message in std::mem::take(&mut batch.messages) {
+            if self
+                .settings
+                .blocked_users
+                .contains_key(&message.author.roblox_user_id)
+            {
+                hidden_count += 1;
+                continue;
+            }
+
+            let mut reason = None;
+            for (filter, regex) in &self.filters {
+                if !regex.is_match(&message.content) {
+                    continue;
+                }
+                match filter.action {
+                    FilterAction::Hide => {
+                        hidden_count += 1;
+                        continue 'messages;
+                    }
+                    FilterAction::Collapse => {
+                        reason.get_or_insert_with(|| CollapseReason::Filter {
+                            pattern: filter.pattern.clone(),
+                        });
+                    }
+                    FilterAction::Mask => message.content = mask(regex, &message.content),
+                }
+            }
+
+            if self.settings.flood.enabled {
+                let repeat_count = self.repeat_count(&batch.channel, &message.content, now);
+                if repeat_count > self.settings.flood.threshold as usize {
+                    reason.get_or_insert(CollapseReason::Flood { repeat_count });
+                }
+            }
+
+            if let Some(reason) = reason {
+                collapsed.insert(message.id.clone(), reason);
+            }
+            messages.push(message);
+        }
+
+        batch.messages = messages;
+        ModeratedBatch {
+            batch,
+            collapsed,
+            hidden_count,
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ModerationState {
+    engine: Mutex<ModerationEngine>,
+}
+
+fn lock_engine(app: &AppHandle) -> Result<std::sync::MutexGuard<'_, ModerationEngine>> {
+    app.state::<ModerationState>()
+        .inner()
+        .engine
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock moderation settings: {err}"))
+}
+
+/// Applies the block list and filters to an incoming batch. Returns `None`
+/// when nothing is left to show.
+pub(crate) fn moderate(app: &AppHandle, batch: ChatMessageBatch) -> Option<ModeratedBatch> {
+    let moderated = match lock_engine(app) {
+        Ok(mut engine) => engine.moderate(batch, now_ms()),
+        Err(err) => {
+            tracing::error!("Failed to apply moderation: {err:#}");
+            return None;
+        }
+    };
+    (!moderated.batch.messages.is_empty()).then_some(moderated)
+}
+
+fn save_settings(app: &AppHandle, settings: &ModerationSettings) -> Result<()> {
+    let store = crate::storage::store(app).context("open settings store")?;
+    store.set(
+        MODERATION_KEY,
+        serde_json::to_value(settings).context("serialize moderation settings")?,
+    );
+    store.save().context("save settings store")
+}
+
+/// Loads the saved settings; filters that no longer compile are dropped.
+pub(crate) fn load_settings(app: &AppHandle) {
+    let mut saved: ModerationSettings = crate::storage::store(app)
+        .ok()
+        .and_then(|store| store.get(MODERATION_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+    saved
+        .filters
+        .retain(|filter| compile_filter(filter).is_ok());
+
+    match ModerationEngine::compile(saved) {
+        Ok(compiled) => {
+            if let Ok(mut engine) = lock_engine(app) {
+                *engine = compiled;
+            }
+        }
+        Err(err) => tracing::error!("Failed to load moderation settings: {err:#}"),
+    }
+}
+
+pub(crate) fn get_moderation_settings(app: &AppHandle) -> Result<ModerationSettings> {
+    Ok(lock_engine(app)?.settings.clone())
+}
+
+/// Checks and normalizes `settings` without applying them.
+pub(crate) fn validate_settings(settings: ModerationSettings) -> Result<ModerationSettings> {
+    Ok(ModerationEngine::compile(settings)?.settings)
+}
+
+pub(crate) fn set_moderation_settings(
+    app: &AppHandle,
+    settings: ModerationSettings,
+) -> Result<ModerationSettings> {
+    let compiled = ModerationEngine::compile(settings)?;
+    let settings = compiled.settings.clone();
+    *lock_engine(app)? = compiled;
+    save_settings(app, &settings)?;
+    Ok(settings)
+}
+
+fn update_settings(
+    app: &AppHandle,
+    update: impl FnOnce(&mut ModerationSettings),
+) -> Result<ModerationSettings> {
+    let settings = {
+        let mut engine = lock_engine(app)?;
+        update(&mut engine.settings);
+        engine.settings.clone()
+    };
+    save_settings(app, &settings)?;
+    Ok(settings)
+}
+
+pub(crate) fn block_user(
+    app: &AppHandle,
+    roblox_user_id: &str,
+    username: Option<String>,
+) -> Result<ModerationSettings> {
+    let roblox_user_id = roblox_user_id.trim();
+    if roblox_user_id.is_empty() || !roblox_user_id.chars().all(|c| c.is_ascii_digit()) {
+        anyhow::bail!("Invalid Roblox user ID: {roblox_user_id}");
+    }
+    update_settings(app, |settings| {
+        settings.blocked_users.insert(
+            roblox_user_id.to_string(),
+            BlockedUser {
+                username: username.unwrap_or_default(),
+            },
+        );
+    })
+}
+
+pub(crate) fn unblock_user(app: &AppHandle, roblox_user_id: &str) -> Result<ModerationSettings> {
+    update_settings(app, |settings| {
+        settings.blocked_users.remove(roblox_user_id.trim());
+    })
+}
+
+fn parse_list(text: &str) -> Result<ModerationList> {
+    let list: ModerationList =
+        serde_json::from_str(text).context("Not a BloxChat moderation list")?;
+    if list.format != LIST_FORMAT {
+        anyhow::bail!("Not a BloxChat moderation list");
+    }
+    crate::storage::check_version("Moderation list", list.version, LIST_VERSION)?;
+    for filter in &list.filters {
+        compile_filter(filter)?;
+    }
+    Ok(list)
+}
+
+/// Adds an imported list to `settings`, or replaces its block list and
+/// filters when `replace` is set. Filters already present are skipped.
+fn merge_list(settings: &mut ModerationSettings, list: ModerationList, replace: bool) {
+    if replace {
+        settings.blocked_users = list.blocked_users;
+        settings.filters = list.filters;
+        return;
+    }
+
+    for (user_id, blocked) in list.blocked_users {
+        settings.blocked_users.entry(user_id).or_insert(blocked);
+    }
+    for filter in list.filters {
+        if !settings.filters.contains(&filter) {
+            settings.filters.push(filter);
+        }
+    }
+}
+
+pub(crate) fn export_moderation_list(app: &AppHandle, path: &Path) -> Result<()> {
+    let settings = get_moderation_settings(app)?;
+    let list = ModerationList {
+        format: LIST_FORMAT.to_string(),
+        version: LIST_VERSION,
+        blocked_users: settings.blocked_users,
+        filters: settings.filters,
+    };
+    let json = serde_json::to_string_pretty(&list).context("serialize moderation list")?;
+    std::fs::write(path, json)
+        .with_context(|| format!("write moderation list to {}", path.display()))
+}
+
+pub(crate) fn import_moderation_list(
+    app: &AppHandle,
+    path: &Path,
+    replace: bool,
+) -> Result<ModerationSettings> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("read moderation list {}", path.display()))?;
+    let list = parse_list(&text)?;
+
+    let mut settings = get_moderation_settings(app)?;
+    merge_list(&mut settings, list, replace);
+    set_moderation_settings(app, settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat_client::{test_message, ChatMessage};
+
+    fn batch(messages: Vec<ChatMessage>) -> ChatMessageBatch {
+        ChatMessageBatch {
+            channel: "global".to_string(),
+            messages,
+        }
+    }
+
+    fn filter(kind: FilterKind, pattern: &str, action: FilterAction) -> ContentFilter {
+        ContentFilter {
+            kind,
+            pattern: pattern.to_string(),
+            action,
+        }
+    }
+
+    fn contents(moderated: &ModeratedBatch) -> Vec<&str> {
+        moderated
+            .batch
+            .messages
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn blocks_users_and_applies_filters() {
+        let mut settings = ModerationSettings::default();
+        settings
+            .blocked_users
+            .insert("13".to_string(), BlockedUser::default());
+        settings.filters = vec![
+            filter(FilterKind::Word, "heck", FilterAction::Mask),
+            filter(FilterKind::Regex, r"free\s+robux", FilterAction::Hide),
+            filter(FilterKind::Word, "spoiler", FilterAction::Collapse),
+        ];
+        let mut engine = ModerationEngine::compile(settings).unwrap();
+
+        let moderated = engine.moderate(
+            batch(vec![
+                test_message("a", "13", "hello"),
+                test_message("b", "1", "what the HECK, checking"),
+                test_message("c", "1", "FREE   robux here"),
+                test_message("d", "1", "Spoiler: the ending"),
+            ]),
+            0,
+        );
+        assert_eq!(moderated.hidden_count, 2);
+        assert_eq!(
+            contents(&moderated),
+            vec!["what the ****, checking", "Spoiler: the ending"]
+        );
+        assert_eq!(
+            moderated.collapsed.get("d"),
+            Some(&CollapseReason::Filter {
+                pattern: "spoiler".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn collapses_floods_within_the_window() {
+        let mut engine = ModerationEngine::compile(ModerationSettings::default()).unwrap();
+        let spam: Vec<ChatMessage> = (0..5)
+            .map(|index| {
+                test_message(
+                    &format!("m{index}"),
+                    "1",
+                    if index % 2 == 0 {
+                        "JOIN  now"
+                    } else {
+                        "join now"
+                    },
+                )
+            })
+            .collect();
+
+        let moderated = engine.moderate(batch(spam), 0);
+        assert_eq!(moderated.batch.messages.len(), 5);
+        assert_eq!(moderated.collapsed.len(), 2);
+        assert_eq!(
+            moderated.collapsed.get("m4"),
+            Some(&CollapseReason::Flood { repeat_count: 5 })
+        );
+
+        let later = engine.moderate(batch(vec![test_message("n", "1", "join now")]), 31_000);
+        assert!(later.collapsed.is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_filters() {
+        let mut settings = ModerationSettings {
+            filters: vec![filter(FilterKind::Regex, "(unclosed", FilterAction::Mask)],
+            ..Default::default()
+        };
+        assert!(ModerationEngine::compile(settings.clone()).is_err());
+        settings.filters = vec![filter(FilterKind::Word, "  ", FilterAction::Mask)];
+        assert!(ModerationEngine::compile(settings).is_err());
+    }
+
+    #[test]
+    fn lists_import_by_merging_or_replacing() {
+        let mut settings = ModerationSettings::default();
+        settings.blocked_users.insert(
+            "1".to_string(),
+            BlockedUser {
+                username: "mine".to_string(),
+            },
+        );
+        settings.filters = vec![filter(FilterKind::Word, "heck", FilterAction::Mask)];
+
+        let shared = r#"{
+            "format": "bloxchat-moderation",
+            "version": 1,
+            "blockedUsers": { "1": { "username": "theirs" }, "2": {} },
+            "filters": [
+                { "kind": "word", "pattern": "heck", "action": "mask" },
+                { "kind": "regex", "pattern": "scam\\w*", "action": "hide" }
+            ]
+        }"#;
+
+        let mut merged = settings.clone();
+        merge_list(&mut merged, parse_list(shared).unwrap(), false);
+        assert_eq!(merged.blocked_users.len(), 2);
+        assert_eq!(merged.blocked_users["1"].username, "mine");
+        assert_eq!(merged.filters.len(), 2);
+
+        let mut replaced = settings;
+        merge_list(&mut replaced, parse_list(shared).unwrap(), true);
+        assert_eq!(replaced.blocked_users["1"].username, "theirs");
+
+        assert!(parse_list(r#"{"format":"other","version":1}"#).is_err());
+        assert!(parse_list(
+            r#"{"format":"bloxchat-moderation","version":1,"filters":[{"kind":"regex","pattern":"(","action":"hide"}]}"#
+        )
+        .is_err());
+    }
+}
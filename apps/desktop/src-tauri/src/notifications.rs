@@ -0,0 +1,556 @@
+use crate::chat_client::{ChatMessage, ChatMessageBatch};
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, VecDeque};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+const NOTIFICATION_RULES_KEY: &str = "notificationRules";
+const MATCHED_EVENT: &str = "notifications://matched";
+const MAX_OWN_MESSAGE_IDS: usize = 200;
+const MAX_PATTERN_LENGTH: usize = 200;
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+const MAX_BODY_CHARS: usize = 140;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct NotificationRules {
+    enabled: bool,
+    /// Our username or display name.
+    mentions: bool,
+    /// Replies to messages we sent.
+    replies: bool,
+    /// Case-insensitive substrings.
+    keywords: Vec<String>,
+    /// Case-insensitive regular expressions.
+    patterns: Vec<String>,
+    muted_channels: BTreeSet<String>,
+    /// Stay quiet while a BloxChat window has focus.
+    only_when_unfocused: bool,
+}
+
+impl Default for NotificationRules {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mentions: true,
+            replies: true,
+            keywords: Vec::new(),
+            patterns: Vec::new(),
+            muted_channels: BTreeSet::new(),
+            only_when_unfocused: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum MatchReason {
+    Mention,
+    Reply,
+    Keyword { keyword: String },
+    Pattern { pattern: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NotificationMatch {
+    channel: String,
+    message_id: String,
+    reason: MatchReason,
+}
+
+/// Who "we" are, from the signed-in session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Identity {
+    roblox_user_id: String,
+    username: String,
+    display_name: String,
+}
+
+/// Rules with their keywords and patterns prepared for matching.
+struct CompiledRules {
+    rules: NotificationRules,
+    keywords: Vec<String>,
+    patterns: Vec<(String, Regex)>,
+}
+
+fn compile_pattern(pattern: &str) -> Result<Regex> {
+    if pattern.len() > MAX_PATTERN_LENGTH {
+        anyhow::bail!("Pattern is longer than {MAX_PATTERN_LENGTH} characters");
+    }
+
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .with_context(|| format!("Invalid pattern: {pattern}"))
+}
+
+impl CompiledRules {
+    fn compile(mut rules: NotificationRules) -> Result<Self> {
+        rules.keywords = rules
+            .keywords
+            .iter()
+            .map(|keyword| keyword.trim().to_string())
+            .filter(|keyword| !keyword.is_empty())
+            .collect();
+        rules.patterns.retain(|pattern| !pattern.trim().is_empty());
+
+        let patterns = rules
+            .patterns
+            .iter()
+            .map(|pattern| Ok((pattern.clone(), compile_pattern(pattern)?)))
+            .collect::<Result<_>>()?;
+        let keywords = rules
+            .keywords
+            .iter()
+            .map(|keyword| keyword.to_lowercase())
+            .collect();
+
+        Ok(Self {
+            rules,
+            keywords,
+            patterns,
+        })
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whether `name` appears in `content` as a whole word, with or without `@`.
+fn mentions_name(content: &str, name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return false;
+    }
+
+    let content = content.to_lowercase();
+    content.match_indices(&name).any(|(start, matched)| {
+        let before = content[..start].chars().next_back();
+        let after = content[start + matched.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+fn match_message(
+    compiled: &CompiledRules,
+    identity: Option<&Identity>,
+    own_message_ids: &VecDeque<String>,
+    message: &ChatMessage,
+) -> Option<MatchReason> {
+    let rules = &compiled.rules;
+    if let Some(identity) = identity {
+        if message.author.roblox_user_id == identity.roblox_user_id {
+            return None;
+        }
+
+        if rules.mentions
+            && (mentions_name(&message.content, &identity.username)
+                || mentions_name(&message.content, &identity.display_name))
+        {
+            return Some(MatchReason::Mention);
+        }
+
+        if rules.replies
+            && message
+                .reply_to_id
+                .as_ref()
+                .is_some_and(|id| own_message_ids.contains(id))
+        {
+            return Some(MatchReason::Reply);
+        }
+    }
+
+    let content = message.content.to_lowercase();
+    if let Some(index) = compiled
+        .keywords
+        .iter()
+        .position(|keyword| content.contains(keyword.as_str()))
+    {
+        return Some(MatchReason::Keyword {
+            keyword: rules.keywords[index].clone(),
+        });
+    }
+
+    compiled
+        .patterns
+        .iter()
+        .find(|(_, regex)| regex.is_match(&message.content))
+        .map(|(pattern, _)| MatchReason::Pattern {
+            pattern: pattern.clone(),
+        })
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn notification_title(message: &ChatMessage, reason: &MatchReason) -> String {
+    let author = &message.author.display_name;
+    match reason {
+        MatchReason::Mention => format!("{author} mentioned you"),
+        MatchReason::Reply => format!("{author} replied to you"),
+        MatchReason::Keyword { keyword } => format!("{author} mentioned \"{keyword}\""),
+        MatchReason::Pattern { .. } => format!("New message from {author}"),
+    }
+}
+
+pub(crate) struct NotificationState {
+    inner: Mutex<NotificationEngine>,
+}
+
+struct NotificationEngine {
+    compiled: CompiledRules,
+    own_message_ids: VecDeque<String>,
+}
+
+impl Default for NotificationState {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(NotificationEngine {
+                compiled: CompiledRules {
+                    rules: NotificationRules::default(),
+                    keywords: Vec::new(),
+                    patterns: Vec::new(),
+                },
+                own_message_ids: VecDeque::new(),
+            }),
+        }
+    }
+}
+
+impl NotificationEngine {
+    fn remember_own_message(&mut self, id: &str) {
+        if self.own_message_ids.iter().any(|known| known == id) {
+            return;
+        }
+        if self.own_message_ids.len() >= MAX_OWN_MESSAGE_IDS {
+            self.own_message_ids.pop_front();
+        }
+        self.own_message_ids.push_back(id.to_string());
+    }
+
+    /// Matches in `batch`, remembering our own messages so later replies to
+    /// them are recognised.
+    fn process(
+        &mut self,
+        batch: &ChatMessageBatch,
+        identity: Option<&Identity>,
+    ) -> Vec<(ChatMessage, MatchReason)> {
+        let rules = &self.compiled.rules;
+        let muted = !rules.enabled || rules.muted_channels.contains(&batch.channel);
+
+        let mut matches = Vec::new();
+        for message in &batch.messages {
+            if identity
+                .is_some_and(|identity| message.author.roblox_user_id == identity.roblox_user_id)
+            {
+                self.remember_own_message(&message.id);
+                continue;
+            }
+            if muted {
+                continue;
+            }
+            if let Some(reason) =
+                match_message(&self.compiled, identity, &self.own_message_ids, message)
+            {
+                matches.push((message.clone(), reason));
+            }
+        }
+        matches
+    }
+}
+
+fn load_identity(app: &AppHandle) -> Option<Identity> {
+    let auth = crate::auth_store::auth_session(app)?;
+    let user = auth.get("user")?;
+    let field = |name: &str| user.get(name).and_then(Value::as_str).map(str::to_string);
+    Some(Identity {
+        roblox_user_id: field("robloxUserId")?,
+        username: field("username").unwrap_or_default(),
+        display_name: field("displayName").unwrap_or_default(),
+    })
+}
+
+fn lock_engine(app: &AppHandle) -> Result<std::sync::MutexGuard<'_, NotificationEngine>> {
+    app.state::<NotificationState>()
+        .inner()
+        .inner
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock notification rules: {err}"))
+}
+
+fn chat_window_focused(app: &AppHandle) -> bool {
+    app.webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false) && window.is_visible().unwrap_or(false))
+}
+
+/// Checks a batch from the native chat client and raises a desktop
+/// notification for anything aimed at us.
+pub(crate) fn process_batch(app: &AppHandle, batch: &ChatMessageBatch) {
+    let identity = load_identity(app);
+    let (matches, only_when_unfocused) = match lock_engine(app) {
+        Ok(mut engine) => (
+            engine.process(batch, identity.as_ref()),
+            engine.compiled.rules.only_when_unfocused,
+        ),
+        Err(err) => {
+            tracing::error!("Failed to check notification rules: {err:#}");
+            return;
+        }
+    };
+
+    for (message, reason) in &matches {
+        let _ = app.emit(
+            MATCHED_EVENT,
+            NotificationMatch {
+                channel: batch.channel.clone(),
+                message_id: message.id.clone(),
+                reason: reason.clone(),
+            },
+        );
+    }
+
+    let Some((message, reason)) = matches.first() else {
+        return;
+    };
+    if only_when_unfocused && chat_window_focused(app) {
+        return;
+    }
+
+    let title = if matches.len() > 1 {
+        format!("{} new messages for you", matches.len())
+    } else {
+        notification_title(message, reason)
+    };
+    if let Err(err) = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(truncate(&message.content, MAX_BODY_CHARS))
+        .show()
+    {
+        tracing::error!("Failed to show notification: {err}");
+    }
+}
+
+fn save_rules(app: &AppHandle, rules: &NotificationRules) -> Result<()> {
+    let store = crate::storage::store(app).context("open settings store")?;
+    store.set(
+        NOTIFICATION_RULES_KEY,
+        serde_json::to_value(rules).context("serialize notification rules")?,
+    );
+    store.save().context("save settings store")
+}
+
+/// Loads the saved rules into the engine; invalid saved patterns are dropped.
+pub(crate) fn load_rules(app: &AppHandle) {
+    let saved: NotificationRules = crate::storage::store(app)
+        .ok()
+        .and_then(|store| store.get(NOTIFICATION_RULES_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+
+    let compiled = match CompiledRules::compile(saved.clone()) {
+        Ok(compiled) => compiled,
+        Err(err) => {
+            tracing::warn!("Dropping invalid notification patterns: {err:#}");
+            let mut rules = saved;
+            rules
+                .patterns
+                .retain(|pattern| compile_pattern(pattern).is_ok());
+            match CompiledRules::compile(rules) {
+                Ok(compiled) => compiled,
+                Err(_) => return,
+            }
+        }
+    };
+
+    if let Ok(mut engine) = lock_engine(app) {
+        engine.compiled = compiled;
+    }
+}
+
+pub(crate) fn get_notification_rules(app: &AppHandle) -> Result<NotificationRules> {
+    Ok(lock_engine(app)?.compiled.rules.clone())
+}
+
+/// Checks and normalizes `rules` without applying them.
+pub(crate) fn validate_rules(rules: NotificationRules) -> Result<NotificationRules> {
+    Ok(CompiledRules::compile(rules)?.rules)
+}
+
+pub(crate) fn set_notification_rules(
+    app: &AppHandle,
+    rules: NotificationRules,
+) -> Result<NotificationRules> {
+    let compiled = CompiledRules::compile(rules)?;
+    let rules = compiled.rules.clone();
+    lock_engine(app)?.compiled = compiled;
+    save_rules(app, &rules)?;
+    Ok(rules)
+}
+
+pub(crate) fn set_channel_muted(
+    app: &AppHandle,
+    channel: &str,
+    muted: bool,
+) -> Result<NotificationRules> {
+    if !crate::roblox::is_valid_channel(channel) {
+        anyhow::bail!("Invalid channel: {channel}");
+    }
+
+    let rules = {
+        let mut engine = lock_engine(app)?;
+        let muted_channels = &mut engine.compiled.rules.muted_channels;
+        if muted {
+            muted_channels.insert(channel.to_string());
+        } else {
+            muted_channels.remove(channel);
+        }
+        engine.compiled.rules.clone()
+    };
+    save_rules(app, &rules)?;
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat_client::test_message;
+
+    fn identity() -> Identity {
+        Identity {
+            roblox_user_id: "1".to_string(),
+            username: "builderman".to_string(),
+            display_name: "Builder Man".to_string(),
+        }
+    }
+
+    fn engine(rules: NotificationRules) -> NotificationEngine {
+        NotificationEngine {
+            compiled: CompiledRules::compile(rules).unwrap(),
+            own_message_ids: VecDeque::new(),
+        }
+    }
+
+    fn reasons(
+        engine: &mut NotificationEngine,
+        channel: &str,
+        messages: Vec<ChatMessage>,
+    ) -> Vec<MatchReason> {
+        let batch = ChatMessageBatch {
+            channel: channel.to_string(),
+            messages,
+        };
+        engine
+            .process(&batch, Some(&identity()))
+            .into_iter()
+            .map(|(_, reason)| reason)
+            .collect()
+    }
+
+    #[test]
+    fn mentions_match_whole_names_only() {
+        assert!(mentions_name("hey @Builderman!", "builderman"));
+        assert!(mentions_name("builder man, look", "Builder Man"));
+        assert!(!mentions_name("buildermania", "builderman"));
+        assert!(!mentions_name("anything", ""));
+    }
+
+    #[test]
+    fn matches_mentions_replies_keywords_and_patterns() {
+        let mut engine = engine(NotificationRules {
+            keywords: vec![" Trade ".to_string()],
+            patterns: vec![r"\bvip\s+server\b".to_string()],
+            ..NotificationRules::default()
+        });
+
+        let found = reasons(
+            &mut engine,
+            "global",
+            vec![
+                test_message("a", "1", "my own message"),
+                test_message("b", "2", "hi builderman"),
+                test_message("c", "2", "sure").replying_to("a"),
+                test_message("d", "3", "anyone want to TRADE?"),
+                test_message("e", "3", "join my VIP  server"),
+                test_message("f", "3", "nothing here"),
+            ],
+        );
+
+        assert_eq!(
+            found,
+            vec![
+                MatchReason::Mention,
+                MatchReason::Reply,
+                MatchReason::Keyword {
+                    keyword: "Trade".to_string()
+                },
+                MatchReason::Pattern {
+                    pattern: r"\bvip\s+server\b".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn muted_channels_and_disabled_rules_stay_quiet() {
+        let mut rules = NotificationRules::default();
+        rules.muted_channels.insert("global".to_string());
+        let mut muted = engine(rules);
+        assert!(reasons(
+            &mut muted,
+            "global",
+            vec![test_message("a", "2", "builderman")]
+        )
+        .is_empty());
+        assert_eq!(
+            reasons(
+                &mut muted,
+                "deadbeef",
+                vec![test_message("b", "2", "builderman")]
+            ),
+            vec![MatchReason::Mention]
+        );
+
+        let mut disabled = engine(NotificationRules {
+            enabled: false,
+            ..NotificationRules::default()
+        });
+        assert!(reasons(
+            &mut disabled,
+            "global",
+            vec![test_message("c", "2", "builderman")]
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(CompiledRules::compile(NotificationRules {
+            patterns: vec!["(unclosed".to_string()],
+            ..NotificationRules::default()
+        })
+        .is_err());
+        assert!(compile_pattern(&"a".repeat(MAX_PATTERN_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn truncates_long_bodies() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("abcdefghijkl", 5), "abcd…");
+    }
+}
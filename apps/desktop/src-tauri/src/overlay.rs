@@ -0,0 +1,174 @@
+use anyhow::Result;
+use std::sync::Mutex;
+#[cfg(target_os = "windows")]
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+
+const OVERLAY_CHANGED_EVENT: &str = "overlay://changed";
+#[cfg(target_os = "windows")]
+const FOCUS_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const DEFAULT_FADE_OPACITY: f64 = 0.35;
+const MIN_FADE_OPACITY: f64 = 0.05;
+
+pub(crate) struct OverlayState {
+    inner: Mutex<OverlaySettings>,
+}
+
+struct OverlaySettings {
+    click_through: bool,
+    roblox_focused: bool,
+    fade_opacity: f64,
+}
+
+impl Default for OverlayState {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(OverlaySettings {
+                click_through: false,
+                roblox_focused: false,
+                fade_opacity: DEFAULT_FADE_OPACITY,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OverlayStatus {
+    click_through: bool,
+    roblox_focused: bool,
+    fade_opacity: f64,
+    /// Multiplier the UI applies on top of the user's GUI opacity.
+    opacity: f64,
+}
+
+impl OverlaySettings {
+    fn status(&self) -> OverlayStatus {
+        OverlayStatus {
+            click_through: self.click_through,
+            roblox_focused: self.roblox_focused,
+            fade_opacity: self.fade_opacity,
+            opacity: effective_opacity(self.click_through, self.roblox_focused, self.fade_opacity),
+        }
+    }
+}
+
+/// The chat only fades while it is a click-through overlay and the player is
+/// actually in Roblox; otherwise it stays fully visible.
+fn effective_opacity(click_through: bool, roblox_focused: bool, fade_opacity: f64) -> f64 {
+    if click_through && roblox_focused {
+        fade_opacity
+    } else {
+        1.0
+    }
+}
+
+fn normalize_fade_opacity(opacity: f64) -> f64 {
+    if !opacity.is_finite() {
+        return DEFAULT_FADE_OPACITY;
+    }
+
+    opacity.clamp(MIN_FADE_OPACITY, 1.0)
+}
+
+fn update_settings(
+    app: &AppHandle,
+    update: impl FnOnce(&mut OverlaySettings),
+) -> Result<OverlayStatus> {
+    let state = app.state::<OverlayState>();
+    let (previous, next) = {
+        let mut settings = state
+            .inner
+            .lock()
+            .map_err(|err| anyhow::anyhow!("lock overlay settings: {err}"))?;
+        let previous = settings.status();
+        update(&mut settings);
+        (previous, settings.status())
+    };
+
+    if previous.click_through != next.click_through {
+        for window in app.webview_windows().values() {
+            let _ = window.set_ignore_cursor_events(next.click_through);
+        }
+    }
+
+    if previous != next {
+        let _ = app.emit(OVERLAY_CHANGED_EVENT, next.clone());
+    }
+
+    Ok(next)
+}
+
+pub(crate) fn is_click_through(app: &AppHandle) -> bool {
+    app.state::<OverlayState>()
+        .inner
+        .lock()
+        .map(|settings| settings.click_through)
+        .unwrap_or(false)
+}
+
+/// Re-applies the current click-through mode, e.g. after a window is shown.
+pub(crate) fn apply_click_through(window: &WebviewWindow) {
+    let _ = window.set_ignore_cursor_events(is_click_through(window.app_handle()));
+}
+
+pub(crate) fn get_overlay_status(app: &AppHandle) -> Result<OverlayStatus> {
+    Ok(app
+        .state::<OverlayState>()
+        .inner
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock overlay settings: {err}"))?
+        .status())
+}
+
+pub(crate) fn set_click_through(app: &AppHandle, enabled: bool) -> Result<OverlayStatus> {
+    update_settings(app, |settings| settings.click_through = enabled)
+}
+
+pub(crate) fn toggle_click_through(app: &AppHandle) -> Result<OverlayStatus> {
+    update_settings(app, |settings| {
+        settings.click_through = !settings.click_through;
+    })
+}
+
+pub(crate) fn set_fade_opacity(app: &AppHandle, opacity: f64) -> Result<OverlayStatus> {
+    let opacity = normalize_fade_opacity(opacity);
+    update_settings(app, |settings| settings.fade_opacity = opacity)
+}
+
+/// Polls whether Roblox is the foreground window so the overlay can fade
+/// while the player is in game.
+#[cfg(target_os = "windows")]
+pub(crate) fn start_focus_watcher(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(FOCUS_POLL_INTERVAL);
+
+        let focused = crate::roblox::is_roblox_foreground();
+        if let Err(err) = update_settings(&app, |settings| settings.roblox_focused = focused) {
+            tracing::error!("Failed to update overlay focus state: {err:#}");
+        }
+    });
+}
+
+/// Roblox focus can't be detected here, so the overlay never fades.
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn start_focus_watcher(_app: AppHandle) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fades_only_when_click_through_and_roblox_focused() {
+        assert_eq!(effective_opacity(true, true, 0.3), 0.3);
+        assert_eq!(effective_opacity(true, false, 0.3), 1.0);
+        assert_eq!(effective_opacity(false, true, 0.3), 1.0);
+    }
+
+    #[test]
+    fn fade_opacity_is_clamped() {
+        assert_eq!(normalize_fade_opacity(0.0), MIN_FADE_OPACITY);
+        assert_eq!(normalize_fade_opacity(2.0), 1.0);
+        assert_eq!(normalize_fade_opacity(f64::NAN), DEFAULT_FADE_OPACITY);
+    }
+}
@@ -0,0 +1,406 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+use tauri::{
+    AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent,
+};
+
+const POPOUT_WINDOWS_KEY: &str = "popoutWindows";
+const POPOUT_LABEL_PREFIX: &str = "channel-";
+const POPOUT_WIDTH: f64 = 420.0;
+const POPOUT_HEIGHT: f64 = 520.0;
+const GLOBAL_CHANNEL: &str = "global";
+/// Emitted to the main window with its new [`ChannelBinding`], or `null` once
+/// it follows the Roblox server again.
+pub(crate) const WINDOW_CHANNEL_EVENT: &str = "window://channel";
+
+static USER_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d+$").expect("valid user id regex"));
+
+/// The chat channel a pop-out window, or the main window when told to
+/// switch, is bound to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum ChannelBinding {
+    Global,
+    #[serde(rename_all = "camelCase")]
+    PinnedServer {
+        job_id: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    FriendServer {
+        user_id: String,
+        job_id: String,
+    },
+}
+
+impl ChannelBinding {
+    /// The channel name used by `chat.subscribe`/`chat.publish`.
+    pub(crate) fn channel(&self) -> &str {
+        match self {
+            ChannelBinding::Global => GLOBAL_CHANNEL,
+            ChannelBinding::PinnedServer { job_id }
+            | ChannelBinding::FriendServer { job_id, .. } => job_id,
+        }
+    }
+
+    /// The binding for a channel given on the command line or in a link:
+    /// `global` or a Job ID.
+    pub(crate) fn for_channel(channel: &str) -> Result<ChannelBinding> {
+        let binding = if channel == GLOBAL_CHANNEL {
+            ChannelBinding::Global
+        } else {
+            ChannelBinding::PinnedServer {
+                job_id: channel.to_string(),
+            }
+        };
+        binding.validate()?;
+        Ok(binding)
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            ChannelBinding::Global => {}
+            ChannelBinding::PinnedServer { job_id } => validate_job_id(job_id)?,
+            ChannelBinding::FriendServer { user_id, job_id } => {
+                if !USER_ID_RE.is_match(user_id) {
+                    anyhow::bail!("Invalid Roblox user id");
+                }
+                validate_job_id(job_id)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_job_id(job_id: &str) -> Result<()> {
+    if job_id == GLOBAL_CHANNEL || !crate::roblox::is_valid_job_id(job_id) {
+        anyhow::bail!("Invalid Job ID");
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PopoutWindow {
+    label: String,
+    binding: ChannelBinding,
+    always_on_top: bool,
+}
+
+/// Open pop-out windows keyed by window label. Position, size and opacity are
+/// tracked per label by `window_state`.
+#[derive(Default)]
+pub(crate) struct PopoutState {
+    windows: Mutex<BTreeMap<String, PopoutWindow>>,
+    /// Set by `--channel` or `bloxchat channel`; the main window follows the
+    /// Roblox server while it is `None`.
+    main_binding: Mutex<Option<ChannelBinding>>,
+}
+
+fn next_label<'a>(existing: impl Iterator<Item = &'a String>) -> String {
+    let used: Vec<&String> = existing.collect();
+    let index = (1..)
+        .find(|index| {
+            let candidate = format!("{POPOUT_LABEL_PREFIX}{index}");
+            !used.iter().any(|label| **label == candidate)
+        })
+        .unwrap_or(1);
+    format!("{POPOUT_LABEL_PREFIX}{index}")
+}
+
+fn lock_windows(
+    app: &AppHandle,
+) -> Result<std::sync::MutexGuard<'_, BTreeMap<String, PopoutWindow>>> {
+    app.state::<PopoutState>()
+        .inner()
+        .windows
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock popout windows: {err}"))
+}
+
+fn save_windows(app: &AppHandle, windows: &BTreeMap<String, PopoutWindow>) -> Result<()> {
+    let store = crate::storage::store(app).context("open settings store")?;
+    let records: Vec<&PopoutWindow> = windows.values().collect();
+    store.set(
+        POPOUT_WINDOWS_KEY,
+        serde_json::to_value(records).context("serialize popout windows")?,
+    );
+    Ok(())
+}
+
+fn load_windows(app: &AppHandle) -> Vec<PopoutWindow> {
+    crate::storage::store(app)
+        .ok()
+        .and_then(|store| store.get(POPOUT_WINDOWS_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Whether the window with `label` should stay above other windows. The main
+/// chat window always does.
+pub(crate) fn always_on_top(app: &AppHandle, label: &str) -> bool {
+    lock_windows(app)
+        .ok()
+        .and_then(|windows| windows.get(label).map(|window| window.always_on_top))
+        .unwrap_or(true)
+}
+
+fn build_window(app: &AppHandle, popout: &PopoutWindow) -> Result<WebviewWindow> {
+    let builder =
+        WebviewWindowBuilder::new(app, &popout.label, WebviewUrl::App("index.html".into()))
+            .title(format!("BloxChat - {}", popout.binding.channel()))
+            .inner_size(POPOUT_WIDTH, POPOUT_HEIGHT)
+            .decorations(false)
+            .resizable(true)
+            .always_on_top(popout.always_on_top);
+    #[cfg(not(target_os = "macos"))]
+    let builder = builder.transparent(true);
+
+    let window = builder.build().context("create popout window")?;
+    crate::apply_window_display_prefs(&window);
+    if let Err(err) = crate::window_state::restore_window_geometry(&window) {
+        tracing::error!("Failed to restore popout window geometry: {err:#}");
+    }
+    crate::window_state::track_window_geometry(&window);
+
+    let app = app.clone();
+    let label = popout.label.clone();
+    window.on_window_event(move |event| {
+        // Only forget windows the user closes; windows torn down on app exit
+        // are reopened on the next launch.
+        if let WindowEvent::CloseRequested { .. } = event {
+            if let Err(err) = forget_window(&app, &label) {
+                tracing::error!("Failed to forget popout window: {err:#}");
+            }
+            if let Err(err) = crate::chat_client::forget_window(&app, &label) {
+                tracing::error!("Failed to drop popout chat subscriptions: {err:#}");
+            }
+        }
+    });
+
+    Ok(window)
+}
+
+fn forget_window(app: &AppHandle, label: &str) -> Result<()> {
+    let mut windows = lock_windows(app)?;
+    if windows.remove(label).is_some() {
+        save_windows(app, &windows)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn open_popout_window(
+    app: &AppHandle,
+    binding: ChannelBinding,
+    always_on_top: bool,
+) -> Result<PopoutWindow> {
+    binding.validate()?;
+
+    // Check and claim under one lock so two quick requests for the same
+    // channel can't both open a window.
+    let (popout, created) = {
+        let mut windows = lock_windows(app)?;
+        match windows
+            .values()
+            .find(|window| window.binding == binding)
+            .cloned()
+        {
+            Some(existing) => (existing, false),
+            None => {
+                let popout = PopoutWindow {
+                    label: next_label(windows.keys()),
+                    binding,
+                    always_on_top,
+                };
+                windows.insert(popout.label.clone(), popout.clone());
+                save_windows(app, &windows)?;
+                (popout, true)
+            }
+        }
+    };
+
+    if !created {
+        crate::show_window(app, &popout.label);
+        return Ok(popout);
+    }
+
+    if let Err(err) = build_window(app, &popout) {
+        let _ = forget_window(app, &popout.label);
+        return Err(err);
+    }
+
+    Ok(popout)
+}
+
+pub(crate) fn close_popout_window(app: &AppHandle, label: &str) -> Result<()> {
+    if !label.starts_with(POPOUT_LABEL_PREFIX) {
+        anyhow::bail!("Not a popout window: {label}");
+    }
+
+    forget_window(app, label)?;
+    crate::chat_client::forget_window(app, label)?;
+    if let Some(window) = app.get_webview_window(label) {
+        window.destroy().context("close popout window")?;
+    }
+    Ok(())
+}
+
+pub(crate) fn list_popout_windows(app: &AppHandle) -> Result<Vec<PopoutWindow>> {
+    Ok(lock_windows(app)?.values().cloned().collect())
+}
+
+pub(crate) fn set_popout_always_on_top(
+    app: &AppHandle,
+    label: &str,
+    enabled: bool,
+) -> Result<PopoutWindow> {
+    let updated = {
+        let mut windows = lock_windows(app)?;
+        let Some(window) = windows.get_mut(label) else {
+            anyhow::bail!("Unknown popout window: {label}");
+        };
+        window.always_on_top = enabled;
+        let updated = window.clone();
+        save_windows(app, &windows)?;
+        updated
+    };
+
+    if let Some(window) = app.get_webview_window(label) {
+        crate::apply_window_display_prefs(&window);
+    }
+    Ok(updated)
+}
+
+fn lock_main_binding(app: &AppHandle) -> Result<std::sync::MutexGuard<'_, Option<ChannelBinding>>> {
+    app.state::<PopoutState>()
+        .inner()
+        .main_binding
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock main window channel: {err}"))
+}
+
+/// The channel the main window was switched to, if any.
+pub(crate) fn main_channel(app: &AppHandle) -> Result<Option<ChannelBinding>> {
+    Ok(lock_main_binding(app)?.clone())
+}
+
+/// Keeps the main window on `binding` instead of the Roblox server, or lets
+/// it follow the server again with `None`. Lasts until the app exits.
+pub(crate) fn set_main_channel(app: &AppHandle, binding: Option<ChannelBinding>) -> Result<()> {
+    if let Some(binding) = &binding {
+        binding.validate()?;
+    }
+    *lock_main_binding(app)? = binding.clone();
+    app.emit_to(crate::CHAT_WINDOW_LABEL, WINDOW_CHANNEL_EVENT, binding)
+        .context("emit window channel")
+}
+
+/// Keeps the main window on `channel`, `global` or a Job ID, as
+/// `--channel` and `bloxchat channel` ask.
+pub(crate) fn switch_main_channel(app: &AppHandle, channel: &str) -> Result<()> {
+    set_main_channel(app, Some(ChannelBinding::for_channel(channel)?))
+}
+
+pub(crate) fn get_window_channel(app: &AppHandle, label: &str) -> Result<Option<ChannelBinding>> {
+    if label == crate::CHAT_WINDOW_LABEL {
+        return main_channel(app);
+    }
+    Ok(lock_windows(app)?
+        .get(label)
+        .map(|window| window.binding.clone()))
+}
+
+/// Reopens the pop-out windows that were open when the app last exited.
+pub(crate) fn restore_popout_windows(app: &AppHandle) {
+    let saved = load_windows(app);
+    {
+        let Ok(mut windows) = lock_windows(app) else {
+            return;
+        };
+        for popout in &saved {
+            if popout.binding.validate().is_ok() && popout.label.starts_with(POPOUT_LABEL_PREFIX) {
+                windows.insert(popout.label.clone(), popout.clone());
+            }
+        }
+    }
+
+    for popout in list_popout_windows(app).unwrap_or_default() {
+        if let Err(err) = build_window(app, &popout) {
+            tracing::error!("Failed to restore popout window {}: {err:#}", popout.label);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_maps_to_chat_channel() {
+        assert_eq!(ChannelBinding::Global.channel(), "global");
+        let server = ChannelBinding::FriendServer {
+            user_id: "123".to_string(),
+            job_id: "deadbeef-1111-4111-8111-111111111111".to_string(),
+        };
+        assert_eq!(server.channel(), "deadbeef-1111-4111-8111-111111111111");
+        assert!(server.validate().is_ok());
+    }
+
+    #[test]
+    fn channel_names_become_bindings() {
+        assert_eq!(
+            ChannelBinding::for_channel("global").unwrap(),
+            ChannelBinding::Global
+        );
+        assert_eq!(
+            ChannelBinding::for_channel("deadbeef-1111-4111-8111-111111111111").unwrap(),
+            ChannelBinding::PinnedServer {
+                job_id: "deadbeef-1111-4111-8111-111111111111".to_string()
+            }
+        );
+        assert!(ChannelBinding::for_channel("lobby").is_err());
+    }
+
+    #[test]
+    fn binding_validation_rejects_bad_ids() {
+        let bad_job = ChannelBinding::PinnedServer {
+            job_id: "not a job".to_string(),
+        };
+        assert!(bad_job.validate().is_err());
+
+        let pinned_global = ChannelBinding::PinnedServer {
+            job_id: "global".to_string(),
+        };
+        assert!(pinned_global.validate().is_err());
+
+        let bad_user = ChannelBinding::FriendServer {
+            user_id: "abc".to_string(),
+            job_id: "deadbeef".to_string(),
+        };
+        assert!(bad_user.validate().is_err());
+    }
+
+    #[test]
+    fn binding_serializes_with_kind_tag() {
+        let value = serde_json::to_value(ChannelBinding::PinnedServer {
+            job_id: "abc".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "kind": "pinnedServer", "jobId": "abc" })
+        );
+    }
+
+    #[test]
+    fn next_label_fills_gaps() {
+        let labels = ["channel-1".to_string(), "channel-3".to_string()];
+        assert_eq!(next_label(labels.iter()), "channel-2");
+        assert_eq!(next_label([].iter()), "channel-1");
+    }
+}
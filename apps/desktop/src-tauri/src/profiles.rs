@@ -0,0 +1,351 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+const PROFILES_KEY: &str = "profiles";
+pub(crate) const DEFAULT_PROFILE: &str = "default";
+/// Emitted with a [`ProfileSwitched`] once another profile is active.
+pub(crate) const PROFILE_SWITCHED_EVENT: &str = "profile://switched";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Profile {
+    /// Roblox account that switches to this profile when auto-switching.
+    linked_roblox_user_id: Option<String>,
+    /// Settings saved when the profile was last left. The active profile's
+    /// live settings are the ones in the store.
+    settings: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ProfileBook {
+    active: String,
+    auto_switch: bool,
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Default for ProfileBook {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.to_string(),
+            auto_switch: false,
+            profiles: BTreeMap::from([(DEFAULT_PROFILE.to_string(), Profile::default())]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProfileSummary {
+    name: String,
+    linked_roblox_user_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProfileList {
+    active: String,
+    auto_switch: bool,
+    profiles: Vec<ProfileSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProfileSwitched {
+    profile: String,
+    /// Set when the detected Roblox account caused the switch.
+    roblox_user_id: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct ProfileState {
+    book: Mutex<ProfileBook>,
+}
+
+impl ProfileBook {
+    /// Keeps the default profile and a valid active one, whatever was saved.
+    fn repair(&mut self) {
+        self.profiles
+            .retain(|name, _| crate::cli::is_valid_profile_name(name));
+        self.profiles
+            .entry(DEFAULT_PROFILE.to_string())
+            .or_default();
+        if !self.profiles.contains_key(&self.active) {
+            self.active = DEFAULT_PROFILE.to_string();
+        }
+    }
+
+    fn list(&self) -> ProfileList {
+        ProfileList {
+            active: self.active.clone(),
+            auto_switch: self.auto_switch,
+            profiles: self
+                .profiles
+                .iter()
+                .map(|(name, profile)| ProfileSummary {
+                    name: name.clone(),
+                    linked_roblox_user_id: profile.linked_roblox_user_id.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn get_mut(&mut self, name: &str) -> Result<&mut Profile> {
+        self.profiles
+            .get_mut(name)
+            .with_context(|| format!("No profile named {name}"))
+    }
+
+    fn create(&mut self, name: &str, settings: Map<String, Value>) -> Result<()> {
+        if !crate::cli::is_valid_profile_name(name) {
+            anyhow::bail!("Invalid profile name: {name}");
+        }
+        if self.profiles.contains_key(name) {
+            anyhow::bail!("A profile named {name} already exists");
+        }
+        self.profiles.insert(
+            name.to_string(),
+            Profile {
+                linked_roblox_user_id: None,
+                settings,
+            },
+        );
+        Ok(())
+    }
+
+    fn delete(&mut self, name: &str) -> Result<()> {
+        if name == DEFAULT_PROFILE {
+            anyhow::bail!("The default profile cannot be deleted");
+        }
+        if name == self.active {
+            anyhow::bail!("Switch to another profile before deleting {name}");
+        }
+        self.profiles
+            .remove(name)
+            .with_context(|| format!("No profile named {name}"))?;
+        Ok(())
+    }
+
+    /// Links `name` to a Roblox account, unlinking any other profile that
+    /// had it so detection stays unambiguous.
+    fn link(&mut self, name: &str, roblox_user_id: Option<String>) -> Result<()> {
+        let roblox_user_id = roblox_user_id
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty());
+        if let Some(id) = &roblox_user_id {
+            if !id.chars().all(|c| c.is_ascii_digit()) {
+                anyhow::bail!("Invalid Roblox user ID: {id}");
+            }
+            for profile in self.profiles.values_mut() {
+                if profile.linked_roblox_user_id.as_ref() == Some(id) {
+                    profile.linked_roblox_user_id = None;
+                }
+            }
+        }
+        self.get_mut(name)?.linked_roblox_user_id = roblox_user_id;
+        Ok(())
+    }
+
+    /// The profile to switch to for a detected Roblox account, if any.
+    fn profile_for_account(&self, roblox_user_id: &str) -> Option<&str> {
+        if !self.auto_switch {
+            return None;
+        }
+        self.profiles
+            .iter()
+            .find(|(_, profile)| profile.linked_roblox_user_id.as_deref() == Some(roblox_user_id))
+            .map(|(name, _)| name.as_str())
+            .filter(|name| *name != self.active)
+    }
+}
+
+fn lock_book(app: &AppHandle) -> Result<std::sync::MutexGuard<'_, ProfileBook>> {
+    app.state::<ProfileState>()
+        .inner()
+        .book
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock profiles: {err}"))
+}
+
+fn save_book(app: &AppHandle, book: &ProfileBook) -> Result<()> {
+    let store = crate::storage::store(app).context("open settings store")?;
+    store.set(
+        PROFILES_KEY,
+        serde_json::to_value(book).context("serialize profiles")?,
+    );
+    store.save().context("save settings store")
+}
+
+/// Loads the saved profiles; must run before anything reads the session.
+pub(crate) fn load_profiles(app: &AppHandle) {
+    let mut saved: ProfileBook = crate::storage::store(app)
+        .ok()
+        .and_then(|store| store.get(PROFILES_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+    saved.repair();
+
+    if let Ok(mut book) = lock_book(app) {
+        *book = saved;
+    }
+}
+
+pub(crate) fn active_profile(app: &AppHandle) -> String {
+    lock_book(app)
+        .map(|book| book.active.clone())
+        .unwrap_or_else(|_| DEFAULT_PROFILE.to_string())
+}
+
+fn update_book(
+    app: &AppHandle,
+    update: impl FnOnce(&mut ProfileBook) -> Result<()>,
+) -> Result<ProfileList> {
+    let mut book = lock_book(app)?;
+    let mut next = book.clone();
+    update(&mut next)?;
+    save_book(app, &next)?;
+    *book = next;
+    Ok(book.list())
+}
+
+pub(crate) fn list_profiles(app: &AppHandle) -> Result<ProfileList> {
+    Ok(lock_book(app)?.list())
+}
+
+/// Adds a profile that starts out with a copy of the current settings and
+/// no session.
+pub(crate) fn create_profile(app: &AppHandle, name: &str) -> Result<ProfileList> {
+    let settings = crate::settings::settings_values(app)?;
+    update_book(app, |book| book.create(name.trim(), settings))
+}
+
+pub(crate) fn delete_profile(app: &AppHandle, name: &str) -> Result<ProfileList> {
+    let list = update_book(app, |book| book.delete(name))?;
+    crate::auth_store::delete_profile_session(app, name)?;
+    Ok(list)
+}
+
+pub(crate) fn link_profile_account(
+    app: &AppHandle,
+    name: &str,
+    roblox_user_id: Option<String>,
+) -> Result<ProfileList> {
+    update_book(app, |book| book.link(name, roblox_user_id))
+}
+
+pub(crate) fn set_profile_auto_switch(app: &AppHandle, enabled: bool) -> Result<ProfileList> {
+    update_book(app, |book| {
+        book.auto_switch = enabled;
+        Ok(())
+    })
+}
+
+fn switch_to(app: &AppHandle, name: &str, roblox_user_id: Option<String>) -> Result<ProfileList> {
+    let target_settings = {
+        let mut book = lock_book(app)?;
+        if book.active == name {
+            return Ok(book.list());
+        }
+        let mut next = book.clone();
+        let target_settings = next.get_mut(name)?.settings.clone();
+        let current = next.active.clone();
+        next.get_mut(&current)?.settings = crate::settings::settings_values(app)?;
+        next.active = name.to_string();
+        save_book(app, &next)?;
+        *book = next;
+        target_settings
+    };
+
+    crate::settings::replace_settings(app, target_settings)?;
+    crate::auth_store::profile_switched(app)?;
+    crate::chat_client::reconnect_if_open(app)?;
+    app.emit(
+        PROFILE_SWITCHED_EVENT,
+        ProfileSwitched {
+            profile: name.to_string(),
+            roblox_user_id,
+        },
+    )
+    .context("emit profile switch")?;
+    list_profiles(app)
+}
+
+/// Makes `name` the active profile, saving the current one's settings and
+/// bringing in its own settings and session.
+pub(crate) fn switch_profile(app: &AppHandle, name: &str) -> Result<ProfileList> {
+    switch_to(app, name, None)
+}
+
+/// Switches to `name` for `--profile`, creating it from the current settings
+/// the first time it's asked for.
+pub(crate) fn activate_profile(app: &AppHandle, name: &str) -> Result<ProfileList> {
+    let exists = lock_book(app)?.profiles.contains_key(name);
+    if !exists {
+        create_profile(app, name)?;
+    }
+    switch_profile(app, name)
+}
+
+/// Called by the log watcher with the account Roblox is signed in to.
+pub(crate) fn roblox_account_detected(app: &AppHandle, roblox_user_id: &str) {
+    let target = match lock_book(app) {
+        Ok(book) => book.profile_for_account(roblox_user_id).map(str::to_string),
+        Err(err) => {
+            tracing::error!("Failed to check profiles for Roblox account: {err:#}");
+            return;
+        }
+    };
+    if let Some(name) = target {
+        if let Err(err) = switch_to(app, &name, Some(roblox_user_id.to_string())) {
+            tracing::error!("Failed to switch to profile {name}: {err:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_profiles_consistent() {
+        let mut book: ProfileBook = serde_json::from_value(serde_json::json!({
+            "active": "gone",
+            "profiles": { "../bad": {}, "alt": {} },
+        }))
+        .unwrap();
+        book.repair();
+        assert_eq!(book.active, DEFAULT_PROFILE);
+        assert_eq!(
+            book.profiles.keys().collect::<Vec<_>>(),
+            ["alt", DEFAULT_PROFILE]
+        );
+
+        assert!(book.create("alt", Map::new()).is_err());
+        assert!(book.create("no spaces", Map::new()).is_err());
+        book.create("main", Map::new()).unwrap();
+        assert!(book.delete(DEFAULT_PROFILE).is_err());
+        book.delete("main").unwrap();
+        assert!(book.delete("main").is_err());
+    }
+
+    #[test]
+    fn auto_switches_to_the_linked_profile() {
+        let mut book = ProfileBook::default();
+        book.create("alt", Map::new()).unwrap();
+        book.link("alt", Some(" 42 ".to_string())).unwrap();
+        assert!(book.link("alt", Some("abc".to_string())).is_err());
+        assert_eq!(book.profile_for_account("42"), None);
+
+        book.auto_switch = true;
+        assert_eq!(book.profile_for_account("42"), Some("alt"));
+        assert_eq!(book.profile_for_account("7"), None);
+
+        book.link(DEFAULT_PROFILE, Some("42".to_string())).unwrap();
+        assert_eq!(book.profiles["alt"].linked_roblox_user_id, None);
+        assert_eq!(book.profile_for_account("42"), None);
+    }
+}
@@ -0,0 +1,1066 @@
+use crate::chat_client::{ChatMessage, ChatUser};
+use crate::clock::now_ms;
+use anyhow::{Context, Result};
+use base64::Engine;
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::Message;
+
+pub(crate) const DEFAULT_RELAY_PORT: u16 = 4080;
+const FEED_CAPACITY: usize = 256;
+/// Largest HTTP request, head and body together, the relay reads.
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+/// How long a client gets to send its request or WebSocket handshake.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often to look again at a request head that hasn't fully arrived.
+const PEEK_INTERVAL: Duration = Duration::from_millis(20);
+/// The one verification session `auth.*` hands out when signing in guests.
+const GUEST_SESSION_ID: &str = "00000000-0000-4000-8000-000000000000";
+const GUEST_SESSION_TTL_MS: u64 = 10 * 60 * 1000;
+const GUEST_TOKEN_TTL_SECS: u64 = 60 * 60;
+
+/// Same defaults as `CHAT_DEFAULT_*` in `packages/api`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RelayLimits {
+    max_message_length: usize,
+    rate_limit_count: usize,
+    rate_limit_window_ms: u64,
+}
+
+impl Default for RelayLimits {
+    fn default() -> Self {
+        Self {
+            max_message_length: 280,
+            rate_limit_count: 4,
+            rate_limit_window_ms: 5_000,
+        }
+    }
+}
+
+fn data_frame(id: &Value, data: Value) -> Value {
+    json!({ "id": id, "jsonrpc": "2.0", "result": { "type": "data", "data": data } })
+}
+
+fn result_frame(id: &Value, kind: &str) -> Value {
+    json!({ "id": id, "jsonrpc": "2.0", "result": { "type": kind } })
+}
+
+/// A tRPC error response; `code` is the `TRPCError` code name.
+fn error_frame(id: &Value, code: &str, message: &str) -> Value {
+    let number = match code {
+        "BAD_REQUEST" => -32600,
+        "UNAUTHORIZED" => -32001,
+        "TOO_MANY_REQUESTS" => -32029,
+        _ => -32004,
+    };
+    json!({
+        "id": id,
+        "jsonrpc": "2.0",
+        "error": { "message": message, "code": number, "data": { "code": code } },
+    })
+}
+
+/// `httpStatus` tRPC gives an error code.
+fn http_status(code: &str) -> u16 {
+    match code {
+        "BAD_REQUEST" => 400,
+        "UNAUTHORIZED" => 401,
+        "NOT_FOUND" => 404,
+        "TOO_MANY_REQUESTS" => 429,
+        _ => 500,
+    }
+}
+
+/// Turns a WebSocket reply into the body the HTTP link expects for `path`.
+fn http_result(frame: &Value, path: &str) -> (u16, Value) {
+    let Some(error) = frame.get("error") else {
+        return (
+            200,
+            json!({ "result": { "data": frame["result"]["data"] } }),
+        );
+    };
+    let code = error["data"]["code"].as_str().unwrap_or_default();
+    let status = http_status(code);
+    let body = json!({
+        "error": {
+            "message": error["message"],
+            "code": error["code"],
+            "data": { "code": code, "httpStatus": status, "path": path },
+        },
+    });
+    (status, body)
+}
+
+/// An unsigned token for `user`, readable by [`user_from_token`].
+fn guest_token(user: &ChatUser, now: u64) -> String {
+    let encode =
+        |value: Value| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value.to_string());
+    let mut claims = json!(user);
+    claims["iat"] = json!(now / 1000);
+    claims["exp"] = json!(now / 1000 + GUEST_TOKEN_TTL_SECS);
+    format!(
+        "{}.{}.unsigned",
+        encode(json!({ "alg": "none", "typ": "JWT" })),
+        encode(claims)
+    )
+}
+
+/// Reads the user from a BloxChat JWT without checking its signature. The
+/// relay has no server secret, so on a LAN the token is taken at its word.
+fn user_from_token(token: &str) -> Option<ChatUser> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let user: ChatUser = serde_json::from_slice(&bytes).ok()?;
+    (!user.roblox_user_id.is_empty()).then_some(user)
+}
+
+fn bearer_user(authorization: Option<&str>) -> Option<ChatUser> {
+    authorization?
+        .strip_prefix("Bearer ")
+        .and_then(user_from_token)
+}
+
+/// The `chat.*` procedures of `packages/api`, shared by every connection.
+pub(crate) struct RelayHub {
+    limits: RelayLimits,
+    /// Author for requests without a token; `None` makes publishing require
+    /// one, like `protectedProcedure`.
+    guest: Option<ChatUser>,
+    /// Place shown by `auth.beginVerification`; set, `auth.*` signs everyone
+    /// in as the guest so the app's sign-in flow works without Roblox.
+    guest_place_id: Option<String>,
+    feed: broadcast::Sender<(String, ChatMessage)>,
+    buckets: Mutex<HashMap<String, VecDeque<u64>>>,
+    next_id: AtomicU64,
+    shutdown: watch::Sender<bool>,
+}
+
+impl RelayHub {
+    pub(crate) fn new(limits: RelayLimits, guest: Option<ChatUser>) -> Arc<Self> {
+        Self::build(limits, guest, None)
+    }
+
+    /// A hub whose `auth.*` procedures sign everyone in as `guest`, telling
+    /// them to verify in `place_id`.
+    pub(crate) fn with_guest_sign_in(
+        limits: RelayLimits,
+        guest: ChatUser,
+        place_id: &str,
+    ) -> Arc<Self> {
+        Self::build(limits, Some(guest), Some(place_id.to_string()))
+    }
+
+    fn build(
+        limits: RelayLimits,
+        guest: Option<ChatUser>,
+        guest_place_id: Option<String>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            limits,
+            guest,
+            guest_place_id,
+            feed: broadcast::channel(FEED_CAPACITY).0,
+            buckets: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            shutdown: watch::channel(false).0,
+        })
+    }
+
+    /// Sends a message to everyone subscribed to `channel`.
+    pub(crate) fn post(
+        &self,
+        channel: &str,
+        author: ChatUser,
+        content: &str,
+        reply_to_id: Option<String>,
+    ) -> ChatMessage {
+        let message = ChatMessage {
+            id: format!(
+                "{:x}-{}",
+                now_ms(),
+                self.next_id.fetch_add(1, Ordering::Relaxed)
+            ),
+            author,
+            content: content.to_string(),
+            reply_to_id,
+        };
+        let _ = self.feed.send((channel.to_string(), message.clone()));
+        message
+    }
+
+    /// Closes the listener and every open connection.
+    pub(crate) fn shut_down(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// Sliding-window limit, as `ratelimit` in `packages/api`, counted against
+    /// every key at once. Returns how long to wait when any limit is hit.
+    fn check_rate(&self, keys: &[String], now: u64) -> Result<(), u64> {
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return Ok(());
+        };
+        if let Some(cutoff) = now.checked_sub(self.limits.rate_limit_window_ms) {
+            // Drop whole buckets once they empty out, so senders who went
+            // quiet don't keep an entry forever.
+            buckets.retain(|_, bucket| {
+                while bucket.front().is_some_and(|timestamp| *timestamp <= cutoff) {
+                    bucket.pop_front();
+                }
+                !bucket.is_empty()
+            });
+        }
+        let mut wait: Option<u64> = None;
+        for key in keys {
+            let Some(bucket) = buckets.get(key) else {
+                continue;
+            };
+            if bucket.len() >= self.limits.rate_limit_count {
+                let oldest = bucket.front().copied().unwrap_or(now);
+                let remaining = self
+                    .limits
+                    .rate_limit_window_ms
+                    .saturating_sub(now - oldest);
+                wait = Some(wait.unwrap_or_default().max(remaining));
+            }
+        }
+
+        if let Some(wait) = wait {
+            return Err(wait);
+        }
+        for key in keys {
+            buckets.entry(key.clone()).or_default().push_back(now);
+        }
+        Ok(())
+    }
+
+    /// `chat.publish`. Tokens aren't verified, so the limit also counts
+    /// against the sender's address: a fresh made-up user doesn't reset it.
+    fn publish(
+        &self,
+        id: &Value,
+        input: &Value,
+        user: Option<&ChatUser>,
+        peer: IpAddr,
+        now: u64,
+    ) -> Value {
+        let Some(author) = user.or(self.guest.as_ref()) else {
+            return error_frame(id, "UNAUTHORIZED", "UNAUTHORIZED");
+        };
+        let channel = input["channel"].as_str().unwrap_or_default();
+        if !crate::roblox::is_valid_channel(channel) {
+            return error_frame(id, "BAD_REQUEST", "Invalid channel.");
+        }
+
+        let content = input["content"].as_str().unwrap_or_default().trim();
+        if content.is_empty() {
+            return error_frame(id, "BAD_REQUEST", "Message cannot be empty.");
+        }
+        let max_length = self.limits.max_message_length;
+        if content.chars().count() > max_length {
+            return error_frame(
+                id,
+                "BAD_REQUEST",
+                &format!("Message exceeds {max_length} characters."),
+            );
+        }
+        let keys = [
+            format!("user:{}", author.roblox_user_id),
+            format!("peer:{peer}"),
+        ];
+        if let Err(retry_after_ms) = self.check_rate(&keys, now) {
+            return error_frame(
+                id,
+                "TOO_MANY_REQUESTS",
+                &format!(
+                    "Rate limit hit. Try again in {}s.",
+                    retry_after_ms.div_ceil(1000)
+                ),
+            );
+        }
+
+        let reply_to_id = input["replyToId"].as_str().map(str::to_string);
+        let message = self.post(channel, author.clone(), content, reply_to_id);
+        data_frame(id, json!(message))
+    }
+
+    /// `auth.*` for a hub that signs everyone in as its guest; verification
+    /// succeeds as soon as it is checked.
+    fn sign_in(&self, id: &Value, path: &str, now: u64) -> Option<Value> {
+        let place_id = self.guest_place_id.as_ref()?;
+        let guest = self.guest.as_ref()?;
+        let session = json!({ "jwt": guest_token(guest, now), "user": guest });
+        let data = match path {
+            "auth.beginVerification" => json!({
+                "sessionId": GUEST_SESSION_ID,
+                "code": "000000",
+                "expiresAt": now + GUEST_SESSION_TTL_MS,
+                "placeId": place_id,
+            }),
+            "auth.checkVerification" => {
+                let mut verified = session;
+                verified["status"] = json!("verified");
+                verified
+            }
+            "auth.refresh" => session,
+            _ => return None,
+        };
+        Some(data_frame(id, data))
+    }
+
+    /// Answers one tRPC request, tracking `subscriptions` as `(id, channel)`.
+    fn handle_request(
+        &self,
+        frame: &Value,
+        user: Option<&ChatUser>,
+        peer: IpAddr,
+        subscriptions: &mut Vec<(Value, String)>,
+    ) -> Value {
+        let id = &frame["id"];
+        let input = &frame["params"]["input"];
+        let method = frame["method"].as_str().unwrap_or_default();
+        let path = frame["params"]["path"].as_str().unwrap_or_default();
+        let not_found = || {
+            error_frame(
+                id,
+                "NOT_FOUND",
+                &format!("No \"{method}\"-procedure on path \"{path}\""),
+            )
+        };
+
+        match (method, path) {
+            ("subscription.stop", _) => {
+                subscriptions.retain(|(subscription, _)| subscription != id);
+                result_frame(id, "stopped")
+            }
+            ("subscription", "chat.subscribe") => {
+                let channel = input["channel"].as_str().unwrap_or_default();
+                if !crate::roblox::is_valid_channel(channel) {
+                    return error_frame(id, "BAD_REQUEST", "Invalid channel.");
+                }
+                subscriptions.push((id.clone(), channel.to_string()));
+                result_frame(id, "started")
+            }
+            ("mutation", "chat.publish") => self.publish(id, input, user, peer, now_ms()),
+            ("query", "chat.limits") => data_frame(id, json!(self.limits)),
+            ("mutation", _) if path.starts_with("auth.") => {
+                self.sign_in(id, path, now_ms()).unwrap_or_else(not_found)
+            }
+            _ => not_found(),
+        }
+    }
+
+    /// Replies to a text frame holding one request or a batch of them.
+    fn handle_text(
+        &self,
+        text: &str,
+        user: Option<&ChatUser>,
+        peer: IpAddr,
+        subscriptions: &mut Vec<(Value, String)>,
+    ) -> Vec<Value> {
+        match serde_json::from_str::<Value>(text) {
+            Ok(Value::Array(frames)) => frames
+                .iter()
+                .map(|frame| self.handle_request(frame, user, peer, subscriptions))
+                .collect(),
+            Ok(frame) => vec![self.handle_request(&frame, user, peer, subscriptions)],
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Answers a request from tRPC's HTTP link. `target` names one procedure,
+    /// or several joined by commas with `?batch=1`; GETs are queries and
+    /// POSTs mutations.
+    fn handle_http(
+        &self,
+        method: &str,
+        target: &str,
+        body: &[u8],
+        user: Option<&ChatUser>,
+        peer: IpAddr,
+    ) -> (u16, Value) {
+        let Ok(url) = reqwest::Url::parse(&format!("http://relay{target}")) else {
+            return http_result(&error_frame(&Value::Null, "BAD_REQUEST", "Bad URL"), "");
+        };
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let batch = query.get("batch").is_some_and(|batch| batch == "1");
+        let (kind, input) = if method == "POST" {
+            (
+                "mutation",
+                serde_json::from_slice(body).unwrap_or(Value::Null),
+            )
+        } else {
+            let input = query
+                .get("input")
+                .and_then(|input| serde_json::from_str(input).ok());
+            ("query", input.unwrap_or(Value::Null))
+        };
+
+        let paths = url.path().trim_start_matches('/').replace("%2C", ",");
+        let mut results = paths.split(',').enumerate().map(|(index, path)| {
+            let input = if batch {
+                input[index.to_string().as_str()].clone()
+            } else {
+                input.clone()
+            };
+            let frame =
+                json!({ "id": null, "method": kind, "params": { "path": path, "input": input } });
+            http_result(
+                &self.handle_request(&frame, user, peer, &mut Vec::new()),
+                path,
+            )
+        });
+        if !batch {
+            return results.next().unwrap_or_default();
+        }
+
+        let (statuses, bodies): (Vec<u16>, Vec<Value>) = results.unzip();
+        let status = if statuses.windows(2).all(|pair| pair[0] == pair[1]) {
+            statuses.first().copied().unwrap_or(200)
+        } else {
+            207
+        };
+        (status, Value::Array(bodies))
+    }
+}
+
+/// A plain HTTP request, as much of it as the tRPC HTTP link uses.
+struct HttpRequest {
+    method: String,
+    target: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+fn find_head_end(bytes: &[u8]) -> Option<usize> {
+    bytes
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|start| start + 4)
+}
+
+/// Whether the connection opens with a WebSocket handshake. The head is only
+/// peeked, so the handshake can still read it.
+async fn is_websocket(stream: &TcpStream) -> Result<bool> {
+    let mut buffer = vec![0; MAX_REQUEST_SIZE];
+    loop {
+        let read = stream.peek(&mut buffer).await.context("read request")?;
+        if read == 0 {
+            anyhow::bail!("connection closed before its request");
+        }
+        if let Some(end) = find_head_end(&buffer[..read]) {
+            let head = String::from_utf8_lossy(&buffer[..end]).to_ascii_lowercase();
+            return Ok(head
+                .lines()
+                .any(|line| line.starts_with("upgrade:") && line.contains("websocket")));
+        }
+        if read == buffer.len() {
+            anyhow::bail!("request head too large");
+        }
+        tokio::time::sleep(PEEK_INTERVAL).await;
+    }
+}
+
+async fn read_http_request(stream: &mut TcpStream) -> Result<HttpRequest> {
+    let mut bytes = Vec::new();
+    let mut chunk = [0; 4096];
+    let head_end = loop {
+        if let Some(end) = find_head_end(&bytes) {
+            break end;
+        }
+        if bytes.len() >= MAX_REQUEST_SIZE {
+            anyhow::bail!("request head too large");
+        }
+        let read = stream.read(&mut chunk).await.context("read request")?;
+        if read == 0 {
+            anyhow::bail!("connection closed mid-request");
+        }
+        bytes.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&bytes[..head_end]).into_owned();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or("/").to_string();
+    let mut content_length = 0;
+    let mut authorization = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.trim().parse().context("bad length")?,
+            "authorization" => authorization = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    if head_end + content_length > MAX_REQUEST_SIZE {
+        anyhow::bail!("request body too large");
+    }
+
+    let mut body = bytes.split_off(head_end);
+    body.truncate(content_length);
+    let mut rest = vec![0; content_length - body.len()];
+    stream
+        .read_exact(&mut rest)
+        .await
+        .context("read request body")?;
+    body.extend_from_slice(&rest);
+    Ok(HttpRequest {
+        method,
+        target,
+        authorization,
+        body,
+    })
+}
+
+fn http_response(status: u16, body: &str) -> String {
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        207 => "Multi-Status",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        _ => "Internal Server Error",
+    };
+    format!(
+        "HTTP/1.1 {status} {reason}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Authorization, Content-Type, trpc-accept\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Answers one HTTP request (or CORS preflight) and closes the connection.
+async fn serve_http(mut stream: TcpStream, peer: IpAddr, hub: Arc<RelayHub>) -> Result<()> {
+    let request = tokio::time::timeout(REQUEST_TIMEOUT, read_http_request(&mut stream))
+        .await
+        .context("request timed out")??;
+    let (status, body) = if request.method == "OPTIONS" {
+        (204, String::new())
+    } else {
+        let user = bearer_user(request.authorization.as_deref());
+        let (status, body) = hub.handle_http(
+            &request.method,
+            &request.target,
+            &request.body,
+            user.as_ref(),
+            peer,
+        );
+        (status, body.to_string())
+    };
+    stream
+        .write_all(http_response(status, &body).as_bytes())
+        .await
+        .context("write response")?;
+    let _ = stream.shutdown().await;
+    Ok(())
+}
+
+/// Serves tRPC's WebSocket link and, for plain requests, its HTTP link.
+async fn serve_connection(stream: TcpStream, peer: IpAddr, hub: Arc<RelayHub>) -> Result<()> {
+    let websocket = tokio::time::timeout(REQUEST_TIMEOUT, is_websocket(&stream))
+        .await
+        .context("request timed out")??;
+    if !websocket {
+        return serve_http(stream, peer, hub).await;
+    }
+
+    let mut user = None;
+    let callback = |request: &Request, response: Response| {
+        user = bearer_user(
+            request
+                .headers()
+                .get("Authorization")
+                .and_then(|value| value.to_str().ok()),
+        );
+        Ok(response)
+    };
+    let socket = tokio_tungstenite::accept_hdr_async(stream, callback)
+        .await
+        .context("accept relay connection")?;
+    let (mut sink, mut stream) = socket.split();
+    let mut feed = hub.feed.subscribe();
+    let mut shutdown = hub.shutdown.subscribe();
+    let mut subscriptions: Vec<(Value, String)> = Vec::new();
+
+    loop {
+        tokio::select! {
+            frame = stream.next() => match frame {
+                Some(Ok(Message::Text(text))) => {
+                    for reply in hub.handle_text(text.as_str(), user.as_ref(), peer, &mut subscriptions) {
+                        sink.send(Message::text(reply.to_string())).await?;
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => return Ok(()),
+            },
+            received = feed.recv() => match received {
+                Ok((channel, message)) => {
+                    for (id, subscribed) in &subscriptions {
+                        if *subscribed == channel {
+                            let frame = data_frame(id, json!(message));
+                            sink.send(Message::text(frame.to_string())).await?;
+                        }
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = shutdown.changed() => {
+                let _ = sink.send(Message::Close(None)).await;
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Accepts connections until the hub is shut down.
+pub(crate) async fn serve(listener: TcpListener, hub: Arc<RelayHub>) {
+    let mut shutdown = hub.shutdown.subscribe();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, peer)) = accepted else {
+                    continue;
+                };
+                let hub = hub.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = serve_connection(stream, peer.ip(), hub).await {
+                        tracing::warn!("Relay connection ended: {err:#}");
+                    }
+                });
+            }
+            _ = shutdown.changed() => return,
+        }
+    }
+}
+
+/// The address the OS would route LAN traffic through. Nothing is sent.
+fn routed_address() -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(10, 254, 254, 254), 1)).ok()?;
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V4(ip) => Some(ip),
+        IpAddr::V6(_) => None,
+    }
+}
+
+/// IPv4 addresses of every interface that is up.
+#[cfg(unix)]
+fn interface_addresses() -> Vec<Ipv4Addr> {
+    let mut addresses = Vec::new();
+    let mut list: *mut libc::ifaddrs = std::ptr::null_mut();
+    // SAFETY: `getifaddrs` fills `list` with a linked list we only read and
+    // then hand back to `freeifaddrs`.
+    unsafe {
+        if libc::getifaddrs(&mut list) != 0 {
+            return addresses;
+        }
+        let mut entry = list;
+        while !entry.is_null() {
+            let interface = &*entry;
+            let up = interface.ifa_flags & libc::IFF_UP as libc::c_uint != 0;
+            let address = interface.ifa_addr;
+            if up && !address.is_null() && i32::from((*address).sa_family) == libc::AF_INET {
+                let address = &*(address as *const libc::sockaddr_in);
+                addresses.push(Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr)));
+            }
+            entry = interface.ifa_next;
+        }
+        libc::freeifaddrs(list);
+    }
+    addresses
+}
+
+/// IPv4 addresses of every adapter that is up.
+#[cfg(windows)]
+fn interface_addresses() -> Vec<Ipv4Addr> {
+    use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+    use windows::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER,
+        GAA_FLAG_SKIP_MULTICAST, IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+    use windows::Win32::Networking::WinSock::{AF_INET, SOCKADDR_IN};
+
+    let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    let mut addresses = Vec::new();
+    let mut size = 16 * 1024u32;
+    // `u64` keeps the buffer aligned for `IP_ADAPTER_ADDRESSES_LH`.
+    let mut buffer: Vec<u64> = Vec::new();
+    // SAFETY: the adapter list lives in `buffer`, which outlives every read,
+    // and each pointer is checked for null before it is followed.
+    unsafe {
+        let mut result = ERROR_BUFFER_OVERFLOW.0;
+        for _ in 0..3 {
+            buffer.resize((size as usize).div_ceil(8), 0);
+            result = GetAdaptersAddresses(
+                u32::from(AF_INET.0),
+                flags,
+                None,
+                Some(buffer.as_mut_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>()),
+                &mut size,
+            );
+            if result != ERROR_BUFFER_OVERFLOW.0 {
+                break;
+            }
+        }
+        if result != ERROR_SUCCESS.0 {
+            return addresses;
+        }
+
+        let mut adapter = buffer.as_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>();
+        while !adapter.is_null() {
+            if (*adapter).OperStatus == IfOperStatusUp {
+                let mut unicast = (*adapter).FirstUnicastAddress;
+                while !unicast.is_null() {
+                    let address = (*unicast).Address.lpSockaddr;
+                    if !address.is_null() && (*address).sa_family == AF_INET {
+                        let address = &*(address as *const SOCKADDR_IN);
+                        addresses.push(Ipv4Addr::from(u32::from_be(address.sin_addr.S_un.S_addr)));
+                    }
+                    unicast = (*unicast).Next;
+                }
+            }
+            adapter = (*adapter).Next;
+        }
+    }
+    addresses
+}
+
+#[cfg(not(any(unix, windows)))]
+fn interface_addresses() -> Vec<Ipv4Addr> {
+    Vec::new()
+}
+
+/// The addresses other machines can reach this one on, the routed one first
+/// since it is the likeliest to work.
+fn lan_addresses() -> Vec<Ipv4Addr> {
+    let mut addresses: Vec<Ipv4Addr> = Vec::new();
+    for ip in routed_address().into_iter().chain(interface_addresses()) {
+        if !ip.is_loopback() && !ip.is_unspecified() && !addresses.contains(&ip) {
+            addresses.push(ip);
+        }
+    }
+    addresses
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RelayInfo {
+    port: u16,
+    /// API URLs for other clients on the network, one per interface.
+    join_addresses: Vec<String>,
+    /// API URL for this machine.
+    local_address: String,
+    limits: RelayLimits,
+}
+
+struct RunningRelay {
+    info: RelayInfo,
+    hub: Arc<RelayHub>,
+}
+
+#[derive(Default)]
+pub(crate) struct RelayState {
+    relay: Mutex<Option<RunningRelay>>,
+}
+
+fn lock_relay(app: &AppHandle) -> Result<std::sync::MutexGuard<'_, Option<RunningRelay>>> {
+    app.state::<RelayState>()
+        .inner()
+        .relay
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock relay: {err}"))
+}
+
+/// Starts hosting chat for the local network on `port`, listening on every
+/// interface so clients can join over whichever network they share.
+pub(crate) fn start_relay(app: &AppHandle, port: Option<u16>) -> Result<RelayInfo> {
+    let mut relay = lock_relay(app)?;
+    if let Some(running) = relay.as_ref() {
+        return Ok(running.info.clone());
+    }
+
+    let address = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port.unwrap_or(DEFAULT_RELAY_PORT)));
+    let listener = std::net::TcpListener::bind(address)
+        .with_context(|| format!("Port {} is not available", address.port()))?;
+    listener
+        .set_nonblocking(true)
+        .context("configure relay listener")?;
+    let port = listener.local_addr().context("read relay port")?.port();
+
+    let limits = RelayLimits::default();
+    let info = RelayInfo {
+        port,
+        join_addresses: lan_addresses()
+            .into_iter()
+            .map(|ip| format!("http://{ip}:{port}"))
+            .collect(),
+        local_address: format!("http://{}:{port}", Ipv4Addr::LOCALHOST),
+        limits,
+    };
+    let hub = RelayHub::new(limits, None);
+
+    let serving = hub.clone();
+    tauri::async_runtime::spawn(async move {
+        match TcpListener::from_std(listener) {
+            Ok(listener) => serve(listener, serving).await,
+            Err(err) => tracing::error!("Failed to start relay: {err}"),
+        }
+    });
+
+    *relay = Some(RunningRelay {
+        info: info.clone(),
+        hub,
+    });
+    Ok(info)
+}
+
+pub(crate) fn stop_relay(app: &AppHandle) -> Result<()> {
+    if let Some(running) = lock_relay(app)?.take() {
+        running.hub.shut_down();
+    }
+    Ok(())
+}
+
+pub(crate) fn get_relay_info(app: &AppHandle) -> Result<Option<RelayInfo>> {
+    Ok(lock_relay(app)?
+        .as_ref()
+        .map(|running| running.info.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    const PEER: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    fn token(user: &Value) -> String {
+        let encode = |value: &Value| {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value.to_string())
+        };
+        format!(
+            "{}.{}.signature",
+            encode(&json!({ "alg": "HS256" })),
+            encode(user)
+        )
+    }
+
+    fn builderman() -> Value {
+        json!({
+            "robloxUserId": "156",
+            "username": "builderman",
+            "displayName": "Builderman",
+            "picture": "",
+            "iat": 1
+        })
+    }
+
+    fn publish(content: &str) -> Value {
+        json!({ "channel": "global", "content": content, "replyToId": null })
+    }
+
+    #[test]
+    fn reads_users_from_tokens() {
+        let user = user_from_token(&token(&builderman())).unwrap();
+        assert_eq!(user.username, "builderman");
+        assert!(user_from_token("not-a-token").is_none());
+        assert!(user_from_token(&token(&json!({ "robloxUserId": "" }))).is_none());
+    }
+
+    #[test]
+    fn publish_follows_the_api_rules() {
+        let hub = RelayHub::new(RelayLimits::default(), None);
+        let user = user_from_token(&token(&builderman()));
+        let id = json!(1);
+
+        let reply = hub.publish(&id, &publish("hi"), None, PEER, 0);
+        assert_eq!(reply["error"]["data"]["code"], "UNAUTHORIZED");
+
+        let reply = hub.publish(&id, &publish("   "), user.as_ref(), PEER, 0);
+        assert_eq!(reply["error"]["message"], "Message cannot be empty.");
+        let reply = hub.publish(&id, &publish(&"a".repeat(281)), user.as_ref(), PEER, 0);
+        assert_eq!(reply["error"]["message"], "Message exceeds 280 characters.");
+
+        for now in 0..4 {
+            let reply = hub.publish(&id, &publish(" hi "), user.as_ref(), PEER, now);
+            assert_eq!(reply["result"]["data"]["content"], "hi");
+            assert_eq!(reply["result"]["data"]["author"]["robloxUserId"], "156");
+        }
+        let reply = hub.publish(&id, &publish("hi"), user.as_ref(), PEER, 1_000);
+        assert_eq!(reply["error"]["data"]["code"], "TOO_MANY_REQUESTS");
+        assert_eq!(
+            reply["error"]["message"],
+            "Rate limit hit. Try again in 4s."
+        );
+        let reply = hub.publish(&id, &publish("hi"), user.as_ref(), PEER, 5_001);
+        assert!(reply.get("result").is_some());
+    }
+
+    #[test]
+    fn made_up_users_share_their_address_limit() {
+        let hub = RelayHub::new(RelayLimits::default(), None);
+        let id = json!(1);
+        for user_id in 1..=4 {
+            let mut claims = builderman();
+            claims["robloxUserId"] = json!(user_id.to_string());
+            let user = user_from_token(&token(&claims));
+            let reply = hub.publish(&id, &publish("hi"), user.as_ref(), PEER, 0);
+            assert!(reply.get("result").is_some());
+        }
+
+        let user = user_from_token(&token(&builderman()));
+        let reply = hub.publish(&id, &publish("hi"), user.as_ref(), PEER, 0);
+        assert_eq!(reply["error"]["data"]["code"], "TOO_MANY_REQUESTS");
+        let elsewhere = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20));
+        let reply = hub.publish(&id, &publish("hi"), user.as_ref(), elsewhere, 0);
+        assert!(reply.get("result").is_some());
+    }
+
+    #[test]
+    fn quiet_senders_lose_their_rate_bucket() {
+        let hub = RelayHub::new(RelayLimits::default(), None);
+        let keys = |name: &str| vec![name.to_string()];
+        assert!(hub.check_rate(&keys("first"), 0).is_ok());
+        assert!(hub.check_rate(&keys("second"), 6_000).is_ok());
+
+        let buckets = hub.buckets.lock().unwrap();
+        assert!(!buckets.contains_key("first"));
+        assert_eq!(buckets.len(), 1);
+    }
+
+    #[test]
+    fn guests_can_publish_when_allowed() {
+        let guest = ChatUser {
+            roblox_user_id: "0".to_string(),
+            username: "guest".to_string(),
+            display_name: "Guest".to_string(),
+            picture: String::new(),
+        };
+        let hub = RelayHub::new(RelayLimits::default(), Some(guest));
+        let reply = hub.publish(&json!(1), &publish("hi"), None, PEER, 0);
+        assert_eq!(reply["result"]["data"]["author"]["username"], "guest");
+    }
+
+    #[test]
+    fn answers_the_http_link() {
+        let hub = RelayHub::new(RelayLimits::default(), None);
+        let (status, body) = hub.handle_http(
+            "GET",
+            "/chat.limits%2Cauth.refresh?batch=1&input=%7B%220%22%3A%7B%7D%7D",
+            b"",
+            None,
+            PEER,
+        );
+        assert_eq!(status, 207);
+        assert_eq!(body[0]["result"]["data"]["maxMessageLength"], 280);
+        assert_eq!(body[1]["error"]["data"]["code"], "NOT_FOUND");
+        assert_eq!(body[1]["error"]["data"]["httpStatus"], 404);
+        assert_eq!(body[1]["error"]["data"]["path"], "auth.refresh");
+
+        let user = user_from_token(&token(&builderman()));
+        let input = json!({ "0": publish("hi") }).to_string();
+        let (status, body) = hub.handle_http(
+            "POST",
+            "/chat.publish?batch=1",
+            input.as_bytes(),
+            user.as_ref(),
+            PEER,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(
+            body[0]["result"]["data"]["author"]["username"],
+            "builderman"
+        );
+
+        let (status, body) = hub.handle_http("POST", "/chat.publish", b"{}", None, PEER);
+        assert_eq!(status, 401);
+        assert_eq!(body["error"]["data"]["code"], "UNAUTHORIZED");
+    }
+
+    #[test]
+    fn guest_sign_in_hands_out_readable_tokens() {
+        let guest = user_from_token(&token(&builderman())).unwrap();
+        let hub = RelayHub::with_guest_sign_in(RelayLimits::default(), guest.clone(), "1");
+        let (_, body) = hub.handle_http("POST", "/auth.beginVerification", b"", None, PEER);
+        assert_eq!(body["result"]["data"]["placeId"], "1");
+
+        let (_, body) = hub.handle_http("POST", "/auth.refresh", b"{}", None, PEER);
+        let jwt = body["result"]["data"]["jwt"].as_str().unwrap();
+        assert_eq!(user_from_token(jwt), Some(guest));
+    }
+
+    #[tokio::test]
+    async fn relays_messages_between_clients() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let hub = RelayHub::new(RelayLimits::default(), None);
+        tokio::spawn(serve(listener, hub.clone()));
+
+        let (mut listener_socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        listener_socket
+            .send(Message::text(
+                r#"[{"id":1,"jsonrpc":"2.0","method":"subscription","params":{"path":"chat.subscribe","input":{"channel":"global"}}},
+                    {"id":2,"jsonrpc":"2.0","method":"query","params":{"path":"chat.limits","input":{"channel":"global"}}}]"#,
+            ))
+            .await
+            .unwrap();
+        for expected in ["started", "maxMessageLength"] {
+            let Some(Ok(Message::Text(reply))) = listener_socket.next().await else {
+                panic!("expected a reply");
+            };
+            assert!(reply.as_str().contains(expected));
+        }
+
+        let mut request = url.as_str().into_client_request().unwrap();
+        request.headers_mut().insert(
+            "Authorization",
+            format!("Bearer {}", token(&builderman())).parse().unwrap(),
+        );
+        let (mut sender, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+        let frame = json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "method": "mutation",
+            "params": { "path": "chat.publish", "input": publish("gg") },
+        });
+        sender.send(Message::text(frame.to_string())).await.unwrap();
+
+        let Some(Ok(Message::Text(data))) = listener_socket.next().await else {
+            panic!("expected a message");
+        };
+        let data: Value = serde_json::from_str(data.as_str()).unwrap();
+        assert_eq!(data["id"], 1);
+        assert_eq!(data["result"]["data"]["author"]["username"], "builderman");
+        assert_eq!(data["result"]["data"]["content"], "gg");
+
+        hub.shut_down();
+        assert!(matches!(
+            listener_socket.next().await,
+            Some(Ok(Message::Close(_))) | None
+        ));
+    }
+}
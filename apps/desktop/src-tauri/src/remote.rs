@@ -0,0 +1,243 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const REMOTE_SEND_EVENT: &str = "remote://send-message";
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A `bloxchat <command>` invocation meant for the running instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RemoteCommand {
+    Send { content: String },
+    Show,
+    Channel { channel: String },
+    Status,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RemoteSendMessage {
+    content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteStatus {
+    running: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    job_id: Option<String>,
+    /// The channel the chat window shows: the one it was switched to, or
+    /// else the Roblox server's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    channel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    click_through: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chat_visible: Option<bool>,
+}
+
+impl RemoteStatus {
+    fn not_running() -> Self {
+        Self {
+            running: false,
+            version: None,
+            pid: None,
+            job_id: None,
+            channel: None,
+            click_through: None,
+            chat_visible: None,
+        }
+    }
+
+    fn collect(app: &AppHandle) -> Self {
+        let job_id =
+            crate::roblox::get_job_id(&app.state::<crate::roblox::LogSettingsState>()).ok();
+        let chat_visible = app
+            .get_webview_window(crate::CHAT_WINDOW_LABEL)
+            .and_then(|window| window.is_visible().ok());
+        let channel = crate::popout::main_channel(app)
+            .ok()
+            .flatten()
+            .map(|binding| binding.channel().to_string())
+            .or_else(|| job_id.clone());
+
+        Self {
+            running: true,
+            version: Some(app.package_info().version.to_string()),
+            pid: Some(std::process::id()),
+            job_id,
+            channel,
+            click_through: Some(crate::overlay::is_click_through(app)),
+            chat_visible,
+        }
+    }
+}
+
+/// Where the running instance leaves the port its status server listens on.
+/// Per user, since the temp dir can be shared.
+fn status_port_file() -> PathBuf {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    std::env::temp_dir().join(format!("bloxchat-{user}.status-port"))
+}
+
+fn write_status(stream: &mut TcpStream, status: &RemoteStatus) -> Result<()> {
+    let body = serde_json::to_vec(status).context("serialize status")?;
+    stream.write_all(&body).context("write status reply")?;
+    Ok(())
+}
+
+fn read_status(stream: &mut TcpStream) -> Result<RemoteStatus> {
+    stream
+        .set_read_timeout(Some(REPLY_TIMEOUT))
+        .context("configure status stream")?;
+    let mut reply = String::new();
+    stream
+        .read_to_string(&mut reply)
+        .context("read status reply")?;
+    serde_json::from_str(&reply).context("parse status reply")
+}
+
+/// Answers `bloxchat status` on a loopback port so the client can tell
+/// whether an instance is running without starting the app itself.
+pub(crate) fn start_status_server(app: AppHandle) -> Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).context("open status socket")?;
+    let port = listener.local_addr().context("read status port")?.port();
+    std::fs::write(status_port_file(), port.to_string()).context("write status port")?;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream
+                .context("accept status request")
+                .and_then(|mut stream| write_status(&mut stream, &RemoteStatus::collect(&app)));
+            if let Err(err) = result {
+                tracing::warn!("Failed to answer status request: {err:#}");
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Runs a command forwarded from another `bloxchat` process.
+pub(crate) fn dispatch(app: &AppHandle, command: RemoteCommand) {
+    match command {
+        RemoteCommand::Send { content } => {
+            // Sent by the main window's chat, which owns the current channel.
+            crate::show_chat_window(app);
+            let _ = app.emit_to(
+                crate::CHAT_WINDOW_LABEL,
+                REMOTE_SEND_EVENT,
+                RemoteSendMessage { content },
+            );
+        }
+        RemoteCommand::Show => crate::show_chat_window(app),
+        RemoteCommand::Channel { channel } => {
+            crate::show_chat_window(app);
+            if let Err(err) = crate::popout::switch_main_channel(app, &channel) {
+                tracing::error!("Failed to switch to channel {channel}: {err:#}");
+            }
+        }
+        // Answered by the status server without going through the handoff.
+        RemoteCommand::Status => {}
+    }
+}
+
+/// Handles a command given to the process that ended up as the primary
+/// instance, i.e. when nothing else was running. Returns the exit code if the
+/// app should not start.
+pub(crate) fn handle_own_command(app: &AppHandle, command: &RemoteCommand) -> Option<i32> {
+    match command {
+        RemoteCommand::Status => Some(1),
+        RemoteCommand::Send { .. } => {
+            crate::cli::attach_console();
+            eprintln!("BloxChat is not running");
+            Some(1)
+        }
+        RemoteCommand::Channel { channel } => {
+            if let Err(err) = crate::popout::switch_main_channel(app, channel) {
+                tracing::error!("Failed to switch to channel {channel}: {err:#}");
+            }
+            None
+        }
+        RemoteCommand::Show => None,
+    }
+}
+
+fn request_status() -> Result<RemoteStatus> {
+    let Some(port) = std::fs::read_to_string(status_port_file())
+        .ok()
+        .and_then(|port| port.trim().parse::<u16>().ok())
+    else {
+        return Ok(RemoteStatus::not_running());
+    };
+
+    // A port left behind by an instance that has exited refuses the
+    // connection.
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    match TcpStream::connect_timeout(&address, REPLY_TIMEOUT) {
+        Ok(mut stream) => read_status(&mut stream),
+        Err(_) => Ok(RemoteStatus::not_running()),
+    }
+}
+
+/// Client side of `bloxchat status`: prints the running instance's status
+/// and returns the process exit code.
+pub(crate) fn run_status_client() -> i32 {
+    crate::cli::attach_console();
+    match request_status() {
+        Ok(status) => {
+            match serde_json::to_string_pretty(&status) {
+                Ok(json) => println!("{json}"),
+                Err(err) => eprintln!("{err}"),
+            }
+            if status.running {
+                0
+            } else {
+                1
+            }
+        }
+        Err(err) => {
+            eprintln!("{err:#}");
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_reply_round_trips_over_loopback() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let status = RemoteStatus {
+            job_id: Some("global".to_string()),
+            channel: Some("deadbeef-1111-4111-8111-111111111111".to_string()),
+            click_through: Some(false),
+            ..RemoteStatus::not_running()
+        };
+
+        let mut client = TcpStream::connect(address).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        write_status(&mut server, &status).unwrap();
+        drop(server);
+        assert_eq!(read_status(&mut client).unwrap(), status);
+    }
+
+    #[test]
+    fn not_running_status_omits_details() {
+        assert_eq!(
+            serde_json::to_value(RemoteStatus::not_running()).unwrap(),
+            serde_json::json!({ "running": false })
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use regex::Regex;
+use serde::Serialize;
 #[cfg(target_os = "windows")]
 use std::ffi::OsString;
 use std::fs::File;
@@ -9,22 +10,26 @@ use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::os::windows::ffi::OsStringExt;
 use std::path::{Path, PathBuf};
 use std::sync::{mpsc, LazyLock, Mutex};
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter, Manager};
 #[cfg(target_os = "windows")]
-use tauri::Manager;
-#[cfg(target_os = "windows")]
-use windows::Win32::Foundation::{HWND, MAX_PATH};
+use windows::Win32::Foundation::{HWND, MAX_PATH, RECT};
 #[cfg(target_os = "windows")]
 use windows::Win32::System::Threading::{
     OpenProcess, QueryFullProcessImageNameW, PROCESS_QUERY_LIMITED_INFORMATION,
 };
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::WindowsAndMessaging::{
-    FindWindowW, GetForegroundWindow, GetWindowThreadProcessId, IsIconic, SetForegroundWindow,
-    ShowWindow, SW_RESTORE,
+    FindWindowW, GetForegroundWindow, GetWindowRect, GetWindowThreadProcessId, IsIconic,
+    SetForegroundWindow, ShowWindow, SW_RESTORE,
 };
 #[cfg(target_os = "windows")]
 use windows_strings::PCWSTR;
+#[cfg(target_os = "linux")]
+use x11rb::connection::Connection;
+#[cfg(target_os = "linux")]
+use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, MapState, Window as X11Window};
+#[cfg(target_os = "linux")]
+use x11rb::rust_connection::RustConnection;
 
 pub(crate) struct LogSettingsState {
     pub(crate) logs_path: Mutex<PathBuf>,
@@ -32,13 +37,42 @@ pub(crate) struct LogSettingsState {
 }
 
 const DEFAULT_JOB_ID: &str = "global";
+/// Emitted by the log watcher when the player joins or leaves a server.
+pub(crate) const JOB_ID_EVENT: &str = "roblox://job-id";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct JobIdChanged {
+    pub(crate) job_id: String,
+}
+
 static JOIN_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"Joining game '([a-f0-9-]+)'").expect("valid join regex"));
+static PLACE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"Joining game '([a-f0-9-]+)' place (\d+)").expect("valid place regex")
+});
+/// From the join report, e.g. `placeid:1818, visitid:0, userid:12345678`.
+static USER_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\buserid:(\d+)").expect("valid user id regex"));
+static JOB_ID_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[a-f0-9]{8}-[a-f0-9]{4}-[a-f0-9]{4}-[a-f0-9]{4}-[a-f0-9]{12}$")
+        .expect("valid job id regex")
+});
 static LEAVE_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"Disconnect from game|leaveGameInternal|leaveUGCGameInternal")
         .expect("valid leave regex")
 });
 
+/// Whether `value` has the shape of a Job ID as logged on join.
+pub(crate) fn is_valid_job_id(value: &str) -> bool {
+    JOB_ID_RE.is_match(value)
+}
+
+/// Whether `value` names a chat channel: the global channel or a Job ID.
+pub(crate) fn is_valid_channel(value: &str) -> bool {
+    value == DEFAULT_JOB_ID || is_valid_job_id(value)
+}
+
 fn home_dir_fallback() -> PathBuf {
     home::home_dir().unwrap_or_else(|| PathBuf::from("."))
 }
@@ -74,6 +108,17 @@ pub(crate) fn default_roblox_logs_path() -> PathBuf {
     home_dir_fallback()
 }
 
+/// Folders the default logs path is chosen from, in order of preference.
+#[cfg(target_os = "linux")]
+pub(crate) fn logs_path_candidates() -> Vec<PathBuf> {
+    sober_roblox_logs_path_candidates()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn logs_path_candidates() -> Vec<PathBuf> {
+    vec![default_roblox_logs_path()]
+}
+
 #[cfg(target_os = "linux")]
 fn sober_roblox_logs_path_candidates() -> Vec<PathBuf> {
     let home = home_dir_fallback();
@@ -100,14 +145,23 @@ pub(crate) fn get_roblox_logs_path(state: &LogSettingsState) -> Result<PathBuf>
         .clone())
 }
 
-pub(crate) fn set_roblox_logs_path(state: &LogSettingsState, path: &str) -> Result<PathBuf> {
-    let next_path = validate_logs_path(path)?;
+/// The folder to watch for a configured `logsPath`; unset means the default.
+pub(crate) fn resolve_logs_path(configured: &str) -> PathBuf {
+    match configured.trim() {
+        "" => default_roblox_logs_path(),
+        path => PathBuf::from(path),
+    }
+}
 
+fn switch_logs_path(state: &LogSettingsState, next_path: PathBuf) -> Result<()> {
     {
         let mut current = state
             .logs_path
             .lock()
             .map_err(|err| anyhow::anyhow!("lock logs_path: {err}"))?;
+        if *current == next_path {
+            return Ok(());
+        }
         *current = next_path.clone();
     }
 
@@ -117,13 +171,33 @@ pub(crate) fn set_roblox_logs_path(state: &LogSettingsState, path: &str) -> Resu
         .map_err(|err| anyhow::anyhow!("lock watcher_control: {err}"))?
         .as_ref()
     {
-        let _ = tx.send(next_path.clone());
+        let _ = tx.send(next_path);
     }
 
-    Ok(next_path)
+    Ok(())
+}
+
+/// Points log watching at the configured folder now and whenever `logsPath`
+/// changes.
+pub(crate) fn follow_settings(app: &AppHandle) {
+    let apply = |app: &AppHandle, configured: &str| {
+        let state = app.state::<LogSettingsState>();
+        if let Err(err) = switch_logs_path(&state, resolve_logs_path(configured)) {
+            tracing::error!("Failed to switch Roblox logs folder: {err:#}");
+        }
+    };
+
+    match crate::settings::get_settings(app) {
+        Ok(settings) => apply(app, &settings.logs_path),
+        Err(err) => tracing::error!("Failed to read logs folder setting: {err:#}"),
+    }
+    let handle = app.clone();
+    crate::settings::on_change(app, "logsPath", move |settings| {
+        apply(&handle, &settings.logs_path)
+    });
 }
 
-fn validate_logs_path(path: &str) -> Result<PathBuf> {
+pub(crate) fn validate_logs_path(path: &str) -> Result<PathBuf> {
     let trimmed = path.trim();
     if trimmed.is_empty() {
         anyhow::bail!("Path cannot be empty");
@@ -146,6 +220,38 @@ pub(crate) fn get_job_id(state: &LogSettingsState) -> Result<String> {
     Ok(job_id_from_logs_dir(&logs_path))
 }
 
+/// The place the player joined server `job_id` in, from the latest log.
+pub(crate) fn get_place_id(state: &LogSettingsState, job_id: &str) -> Result<Option<String>> {
+    let logs_path = state
+        .logs_path
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock logs_path: {err}"))?
+        .clone();
+    let Some(latest_log) = latest_player_log(&logs_path) else {
+        return Ok(None);
+    };
+
+    let mut file = File::open(latest_log).context("open log file")?;
+    search_file_tail(&mut file, |text| place_id_from_text(text, job_id))
+}
+
+fn place_id_from_text(text: &str, job_id: &str) -> Option<String> {
+    PLACE_RE
+        .captures_iter(text)
+        .filter(|caps| &caps[1] == job_id)
+        .last()
+        .map(|caps| caps[2].to_string())
+}
+
+/// The Roblox account that last joined a game in `text`.
+fn user_id_from_text(text: &str) -> Option<String> {
+    USER_ID_RE
+        .captures_iter(text)
+        .last()
+        .map(|caps| caps[1].to_string())
+        .filter(|user_id| user_id != "0")
+}
+
 fn job_id_from_logs_dir(logs_dir: &Path) -> String {
     let Some(latest_log) = latest_player_log(logs_dir) else {
         return DEFAULT_JOB_ID.to_string();
@@ -158,7 +264,7 @@ fn job_id_from_logs_dir(logs_dir: &Path) -> String {
     job_id_from_file_tail(&mut file).unwrap_or_else(|_| DEFAULT_JOB_ID.to_string())
 }
 
-fn latest_player_log(logs_dir: &Path) -> Option<PathBuf> {
+pub(crate) fn latest_player_log(logs_dir: &Path) -> Option<PathBuf> {
     std::fs::read_dir(logs_dir).ok().and_then(|entries| {
         entries
             .filter_map(|entry| entry.ok())
@@ -223,10 +329,12 @@ fn path_has_supported_logs(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
-fn job_id_from_file_tail(file: &mut File) -> Result<String> {
+/// Runs `find` over a growing window at the end of `file`, stopping at the
+/// first match or once the window reaches 8 MiB.
+fn search_file_tail<T>(file: &mut File, find: impl Fn(&str) -> Option<T>) -> Result<Option<T>> {
     let len = file.metadata().context("stat log file")?.len();
     if len == 0 {
-        return Ok(DEFAULT_JOB_ID.to_string());
+        return Ok(None);
     }
 
     const INITIAL_WINDOW: u64 = 256 * 1024;
@@ -241,16 +349,24 @@ fn job_id_from_file_tail(file: &mut File) -> Result<String> {
         let mut bytes = Vec::with_capacity((len - start) as usize);
         file.read_to_end(&mut bytes).context("read log tail")?;
 
-        let text = String::from_utf8_lossy(&bytes);
-        if let Some(job_id) = job_id_from_text_slice(&text) {
-            return Ok(job_id);
+        if let Some(found) = find(&String::from_utf8_lossy(&bytes)) {
+            return Ok(Some(found));
         }
 
         if start == 0 || window >= MAX_WINDOW {
-            break;
+            return Ok(None);
         }
         window = (window * 2).min(MAX_WINDOW).min(len);
     }
+}
+
+fn job_id_from_file_tail(file: &mut File) -> Result<String> {
+    if file.metadata().context("stat log file")?.len() == 0 {
+        return Ok(DEFAULT_JOB_ID.to_string());
+    }
+    if let Some(job_id) = search_file_tail(file, job_id_from_text_slice)? {
+        return Ok(job_id);
+    }
 
     file.seek(SeekFrom::Start(0))
         .context("seek log file start")?;
@@ -267,6 +383,15 @@ fn job_id_from_file_tail(file: &mut File) -> Result<String> {
     Ok(current)
 }
 
+/// The part of `bytes` up to and including the last newline. A line Roblox
+/// is still writing is left for the next read.
+fn complete_lines(bytes: &[u8]) -> &[u8] {
+    match bytes.iter().rposition(|byte| *byte == b'\n') {
+        Some(end) => &bytes[..=end],
+        None => &[],
+    }
+}
+
 fn job_id_from_text_slice(text: &str) -> Option<String> {
     let last_join = JOIN_RE
         .captures_iter(text)
@@ -310,34 +435,52 @@ pub(crate) fn should_steal_focus(app: AppHandle) -> bool {
             }
         }
 
-        let mut pid: u32 = 0;
-        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        window_belongs_to_roblox(hwnd)
+    }
+}
 
-        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid);
-        if handle.is_err() {
-            return false;
-        }
-        let handle = handle.unwrap();
+#[cfg(target_os = "windows")]
+unsafe fn window_belongs_to_roblox(hwnd: HWND) -> bool {
+    let mut pid: u32 = 0;
+    GetWindowThreadProcessId(hwnd, Some(&mut pid));
 
-        let mut buffer = [0u16; MAX_PATH as usize];
-        let mut size = buffer.len() as u32;
+    let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid);
+    if handle.is_err() {
+        return false;
+    }
+    let handle = handle.unwrap();
+
+    let mut buffer = [0u16; MAX_PATH as usize];
+    let mut size = buffer.len() as u32;
+
+    if QueryFullProcessImageNameW(
+        handle,
+        windows::Win32::System::Threading::PROCESS_NAME_FORMAT(0),
+        windows_strings::PWSTR(&mut buffer[0]),
+        &mut size,
+    )
+    .is_err()
+    {
+        return false;
+    }
 
-        if QueryFullProcessImageNameW(
-            handle,
-            windows::Win32::System::Threading::PROCESS_NAME_FORMAT(0),
-            windows_strings::PWSTR(&mut buffer[0]),
-            &mut size,
-        )
-        .is_err()
-        {
+    let exe = OsString::from_wide(&buffer[..size as usize])
+        .to_string_lossy()
+        .to_lowercase();
+
+    exe.contains("robloxplayerbeta.exe")
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn is_roblox_foreground() -> bool {
+    unsafe {
+        let hwnd: HWND = GetForegroundWindow();
+
+        if hwnd.0 == std::ptr::null_mut() {
             return false;
         }
 
-        let exe = OsString::from_wide(&buffer[..size as usize])
-            .to_string_lossy()
-            .to_lowercase();
-
-        exe.contains("robloxplayerbeta.exe")
+        window_belongs_to_roblox(hwnd)
     }
 }
 
@@ -347,7 +490,7 @@ pub(crate) fn should_steal_focus(_app: AppHandle) -> bool {
 }
 
 #[cfg(target_os = "windows")]
-pub(crate) fn focus_roblox(_app: AppHandle) -> bool {
+fn find_roblox_window() -> Option<HWND> {
     const CLASS_NAME: &[u16] = &[
         b'R' as u16,
         b'o' as u16,
@@ -379,9 +522,20 @@ pub(crate) fn focus_roblox(_app: AppHandle) -> bool {
         });
 
         if hwnd.0 == std::ptr::null_mut() {
-            return false;
+            return None;
         }
 
+        Some(hwnd)
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn focus_roblox(_app: AppHandle) -> bool {
+    let Some(hwnd) = find_roblox_window() else {
+        return false;
+    };
+
+    unsafe {
         if IsIconic(hwnd).as_bool() {
             let _ = ShowWindow(hwnd, SW_RESTORE);
         }
@@ -400,9 +554,217 @@ pub(crate) fn focus_roblox(_app: AppHandle) -> bool {
     false
 }
 
-pub(crate) fn start_log_watcher(initial_path: PathBuf, path_updates_rx: mpsc::Receiver<PathBuf>) {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RobloxWindowBounds {
+    pub(crate) x: i32,
+    pub(crate) y: i32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) minimized: bool,
+}
+
+/// Finds the Roblox window and reports its on-screen geometry. On Linux it
+/// keeps an X11 connection open between lookups since it is polled.
+#[derive(Default)]
+pub(crate) struct RobloxWindowLocator {
+    #[cfg(target_os = "linux")]
+    x11: Option<(RustConnection, X11Window)>,
+}
+
+#[cfg(target_os = "windows")]
+impl RobloxWindowLocator {
+    pub(crate) fn bounds(&mut self) -> Option<RobloxWindowBounds> {
+        let hwnd = find_roblox_window()?;
+
+        unsafe {
+            let mut rect = RECT::default();
+            GetWindowRect(hwnd, &mut rect).ok()?;
+
+            Some(RobloxWindowBounds {
+                x: rect.left,
+                y: rect.top,
+                width: (rect.right - rect.left).max(0) as u32,
+                height: (rect.bottom - rect.top).max(0) as u32,
+                minimized: IsIconic(hwnd).as_bool(),
+            })
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl RobloxWindowLocator {
+    pub(crate) fn bounds(&mut self) -> Option<RobloxWindowBounds> {
+        if self.x11.is_none() {
+            let (conn, screen_num) = x11rb::connect(None).ok()?;
+            let root = conn.setup().roots.get(screen_num)?.root;
+            self.x11 = Some((conn, root));
+        }
+
+        let (conn, root) = self.x11.as_ref()?;
+        match x11_roblox_window_bounds(conn, *root) {
+            Ok(bounds) => bounds,
+            Err(_) => {
+                // The X server went away or the connection broke; reconnect on
+                // the next lookup.
+                self.x11 = None;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+impl RobloxWindowLocator {
+    pub(crate) fn bounds(&mut self) -> Option<RobloxWindowBounds> {
+        None
+    }
+}
+
+/// Matches the WM_CLASS parts of the Roblox player under Sober or Wine.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn is_roblox_window_class(class: &str) -> bool {
+    let normalized = class.trim().to_ascii_lowercase();
+    matches!(
+        normalized.as_str(),
+        "robloxapp" | "sober" | "org.vinegarhq.sober" | "robloxplayerbeta.exe"
+    )
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn is_roblox_window_title(title: &str) -> bool {
+    matches!(title.trim(), "Roblox" | "Sober")
+}
+
+#[cfg(target_os = "linux")]
+fn x11_intern_atom(conn: &RustConnection, name: &[u8]) -> Result<Atom> {
+    Ok(conn.intern_atom(false, name)?.reply()?.atom)
+}
+
+#[cfg(target_os = "linux")]
+fn x11_client_windows(conn: &RustConnection, root: X11Window) -> Result<Vec<X11Window>> {
+    let client_list_atom = x11_intern_atom(conn, b"_NET_CLIENT_LIST")?;
+    let client_list = conn
+        .get_property(false, root, client_list_atom, AtomEnum::WINDOW, 0, u32::MAX)?
+        .reply()?;
+    let clients: Vec<X11Window> = client_list
+        .value32()
+        .map(|values| values.collect())
+        .unwrap_or_default();
+    if !clients.is_empty() {
+        return Ok(clients);
+    }
+
+    // Without a window manager (e.g. under Xvfb) there is no client list, so
+    // walk the window tree instead.
+    let mut windows = Vec::new();
+    let mut pending = vec![root];
+    while let Some(window) = pending.pop() {
+        let tree = conn.query_tree(window)?.reply()?;
+        windows.extend_from_slice(&tree.children);
+        pending.extend_from_slice(&tree.children);
+    }
+
+    Ok(windows)
+}
+
+#[cfg(target_os = "linux")]
+fn x11_is_roblox_window(conn: &RustConnection, window: X11Window) -> Result<bool> {
+    let class = conn
+        .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)?
+        .reply()?;
+    let class = String::from_utf8_lossy(&class.value);
+    if class.split('\0').any(is_roblox_window_class) {
+        return Ok(true);
+    }
+
+    let title = conn
+        .get_property(false, window, AtomEnum::WM_NAME, AtomEnum::ANY, 0, 1024)?
+        .reply()?;
+    Ok(is_roblox_window_title(&String::from_utf8_lossy(
+        &title.value,
+    )))
+}
+
+#[cfg(target_os = "linux")]
+fn x11_is_minimized(conn: &RustConnection, window: X11Window) -> Result<bool> {
+    let attributes = conn.get_window_attributes(window)?.reply()?;
+    if attributes.map_state != MapState::VIEWABLE {
+        return Ok(true);
+    }
+
+    let wm_state_atom = x11_intern_atom(conn, b"_NET_WM_STATE")?;
+    let hidden_atom = x11_intern_atom(conn, b"_NET_WM_STATE_HIDDEN")?;
+    let wm_state = conn
+        .get_property(false, window, wm_state_atom, AtomEnum::ATOM, 0, 1024)?
+        .reply()?;
+    Ok(wm_state
+        .value32()
+        .is_some_and(|mut states| states.any(|state| state == hidden_atom)))
+}
+
+#[cfg(target_os = "linux")]
+fn x11_roblox_window_bounds(
+    conn: &RustConnection,
+    root: X11Window,
+) -> Result<Option<RobloxWindowBounds>> {
+    let mut roblox_window = None;
+    for window in x11_client_windows(conn, root)? {
+        if x11_is_roblox_window(conn, window)? {
+            roblox_window = Some(window);
+            break;
+        }
+    }
+
+    let Some(window) = roblox_window else {
+        return Ok(None);
+    };
+
+    let geometry = conn.get_geometry(window)?.reply()?;
+    let origin = conn.translate_coordinates(window, root, 0, 0)?.reply()?;
+    Ok(Some(RobloxWindowBounds {
+        x: origin.dst_x.into(),
+        y: origin.dst_y.into(),
+        width: geometry.width.into(),
+        height: geometry.height.into(),
+        minimized: x11_is_minimized(conn, window)?,
+    }))
+}
+
+fn publish_user_id(app: &AppHandle, user_id: &str) {
+    if crate::session::is_replaying(app) {
+        return;
+    }
+    crate::profiles::roblox_account_detected(app, user_id);
+}
+
+fn publish_job_id(app: &AppHandle, job_id: &str) {
+    crate::diagnostics::record_job_id(app, job_id);
+    crate::session::record_job_id(app, job_id);
+    if crate::session::is_replaying(app) {
+        return;
+    }
+    let _ = app.emit(
+        JOB_ID_EVENT,
+        JobIdChanged {
+            job_id: job_id.to_string(),
+        },
+    );
+}
+
+/// Watches the folder in [`LogSettingsState`], following later switches.
+pub(crate) fn start_log_watcher(app: AppHandle) -> Result<()> {
+    let state = app.state::<LogSettingsState>();
+    let (tx, path_updates_rx) = mpsc::channel::<PathBuf>();
+    *state
+        .watcher_control
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock watcher_control: {err}"))? = Some(tx);
+    let initial_path = get_roblox_logs_path(&state)?;
+
     std::thread::spawn(move || {
         let mut log_dir = initial_path;
+        let mut last_job_id: Option<String> = None;
+        let mut last_user_id: Option<String> = None;
 
         loop {
             let (tx, rx) = mpsc::channel();
@@ -433,12 +795,31 @@ pub(crate) fn start_log_watcher(initial_path: PathBuf, path_updates_rx: mpsc::Re
             let mut last_file: Option<PathBuf> = None;
             let mut last_pos: u64 = 0;
 
-            let process_file = |path: &Path, pos: &mut u64| {
-                if let Ok(file) = File::open(path) {
-                    let mut reader = BufReader::new(file);
-                    let _ = reader.seek(SeekFrom::Start(*pos));
-                    for _line in reader.by_ref().lines().flatten() {}
-                    *pos = reader.get_ref().metadata().map(|m| m.len()).unwrap_or(*pos);
+            let mut process_file = |path: &Path, pos: &mut u64| {
+                if let Ok(mut file) = File::open(path) {
+                    // A log that shrank was replaced; start over.
+                    if file.metadata().is_ok_and(|m| m.len() < *pos) {
+                        *pos = 0;
+                    }
+                    let _ = file.seek(SeekFrom::Start(*pos));
+                    let mut bytes = Vec::new();
+                    let _ = file.read_to_end(&mut bytes);
+                    let lines = complete_lines(&bytes);
+                    *pos += lines.len() as u64;
+
+                    let text = String::from_utf8_lossy(lines);
+                    if let Some(job_id) = job_id_from_text_slice(&text) {
+                        if last_job_id.as_ref() != Some(&job_id) {
+                            publish_job_id(&app, &job_id);
+                            last_job_id = Some(job_id);
+                        }
+                    }
+                    if let Some(user_id) = user_id_from_text(&text) {
+                        if last_user_id.as_ref() != Some(&user_id) {
+                            publish_user_id(&app, &user_id);
+                            last_user_id = Some(user_id);
+                        }
+                    }
                 }
             };
 
@@ -477,6 +858,7 @@ pub(crate) fn start_log_watcher(initial_path: PathBuf, path_updates_rx: mpsc::Re
             }
         }
     });
+    Ok(())
 }
 
 #[cfg(test)]
@@ -498,6 +880,62 @@ mod tests {
         assert_eq!(job_id_from_text_slice(&text).unwrap(), DEFAULT_JOB_ID);
     }
 
+    #[test]
+    fn job_ids_must_be_uuids() {
+        assert!(is_valid_job_id("deadbeef-1111-2222-3333-444455556666"));
+        assert!(!is_valid_job_id("-"));
+        assert!(!is_valid_job_id("deadbeef"));
+        assert!(!is_valid_job_id("deadbeef-1111-2222-3333-44445555666"));
+        assert!(!is_valid_job_id("DEADBEEF-1111-2222-3333-444455556666"));
+    }
+
+    #[test]
+    fn place_id_is_read_from_the_join_line() {
+        let text = "! Joining game 'a1b2c3d4-1111' place 606849621 at 10.0.0.1\n\
+                    ! Joining game 'deadbeef-2222' place 920587237 at 10.0.0.2\n";
+        assert_eq!(
+            place_id_from_text(text, "deadbeef-2222").as_deref(),
+            Some("920587237")
+        );
+        assert_eq!(place_id_from_text(text, "ffff"), None);
+    }
+
+    #[test]
+    fn only_complete_lines_are_read() {
+        assert_eq!(complete_lines(b"one\ntwo\nJoining ga"), b"one\ntwo\n");
+        assert_eq!(complete_lines(b"one\n"), b"one\n");
+        assert_eq!(complete_lines(b"Joining ga"), b"");
+    }
+
+    #[test]
+    fn file_tail_search_finds_the_join_line() {
+        let path = std::env::temp_dir().join(format!("bloxchat-tail-{}.log", std::process::id()));
+        let filler = "x".repeat(300 * 1024);
+        std::fs::write(
+            &path,
+            format!("! Joining game 'deadbeef-2222' place 920587237\n{filler}\n"),
+        )
+        .unwrap();
+
+        let mut file = File::open(&path).unwrap();
+        let found = search_file_tail(&mut file, |text| place_id_from_text(text, "deadbeef-2222"));
+        let missing = search_file_tail(&mut file, |text| place_id_from_text(text, "ffff"));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(found.unwrap().as_deref(), Some("920587237"));
+        assert_eq!(missing.unwrap(), None);
+    }
+
+    #[test]
+    fn user_id_is_read_from_the_join_report() {
+        let text = "[FLog::GameJoinLoadTime] Report game_join_loadtime: placeid:606849621, \
+                    visitid:0, userid:0, openByTeleport:false\n\
+                    [FLog::GameJoinLoadTime] Report game_join_loadtime: placeid:606849621, \
+                    visitid:0, userid:12345678, openByTeleport:false\n";
+        assert_eq!(user_id_from_text(text).as_deref(), Some("12345678"));
+        assert_eq!(user_id_from_text("otheruserid:5"), None);
+        assert_eq!(user_id_from_text("userid:0"), None);
+    }
+
     #[test]
     fn supported_log_files_include_windows_and_linux_patterns() {
         assert!(is_supported_log_file(Path::new("2026-03-18_14-38-28.log")));
@@ -509,4 +947,66 @@ mod tests {
         assert!(!is_supported_log_file(Path::new("archive.log")));
         assert!(!is_supported_log_file(Path::new("2026-03-18_14-38.log")));
     }
+
+    #[test]
+    fn roblox_window_matching_covers_sober_and_wine() {
+        assert!(is_roblox_window_class("org.vinegarhq.Sober"));
+        assert!(is_roblox_window_class("RobloxPlayerBeta.exe"));
+        assert!(is_roblox_window_class("RobloxApp"));
+        assert!(!is_roblox_window_class("firefox"));
+        assert!(is_roblox_window_title("Roblox"));
+        assert!(!is_roblox_window_title("Roblox Studio"));
+    }
+
+    /// Runs against a real X server when one is available, e.g.
+    /// `xvfb-run cargo test x11_locator`.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn x11_locator_finds_roblox_window() {
+        use x11rb::protocol::xproto::{CreateWindowAux, PropMode, WindowClass};
+        use x11rb::wrapper::ConnectionExt as _;
+
+        if std::env::var_os("DISPLAY").is_none() {
+            return;
+        }
+
+        let (conn, screen_num) = x11rb::connect(None).expect("connect to X server");
+        let screen = &conn.setup().roots[screen_num];
+        let window = conn.generate_id().expect("allocate window id");
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            40,
+            60,
+            800,
+            600,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new(),
+        )
+        .expect("create window");
+        conn.change_property8(
+            PropMode::REPLACE,
+            window,
+            AtomEnum::WM_CLASS,
+            AtomEnum::STRING,
+            b"sober\0org.vinegarhq.Sober\0",
+        )
+        .expect("set WM_CLASS");
+        conn.map_window(window).expect("map window");
+        conn.sync().expect("sync X connection");
+
+        let bounds = RobloxWindowLocator::default()
+            .bounds()
+            .expect("find Roblox window");
+        assert_eq!((bounds.x, bounds.y), (40, 60));
+        assert_eq!((bounds.width, bounds.height), (800, 600));
+        assert!(!bounds.minimized);
+
+        conn.unmap_window(window).expect("unmap window");
+        conn.sync().expect("sync X connection");
+        assert!(RobloxWindowLocator::default().bounds().unwrap().minimized);
+    }
 }
@@ -0,0 +1,480 @@
+use crate::chat_client::ChatMessageBatch;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+const SESSION_FORMAT: &str = "bloxchat-session";
+const SESSION_VERSION: u32 = 1;
+const REPLAY_FINISHED_EVENT: &str = "session://replay-finished";
+const MIN_REPLAY_SPEED: f64 = 0.1;
+const MAX_REPLAY_SPEED: f64 = 100.0;
+/// How often a sleeping replay checks whether it was stopped.
+const REPLAY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// First line of a session file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionHeader {
+    format: String,
+    version: u32,
+    started_at: i64,
+    app_version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum SessionEvent {
+    Messages(ChatMessageBatch),
+    #[serde(rename_all = "camelCase")]
+    JobId {
+        job_id: String,
+    },
+}
+
+/// One line after the header: an event and when it happened, relative to the
+/// start of the recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionEntry {
+    offset_ms: u64,
+    #[serde(flatten)]
+    event: SessionEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecordingSummary {
+    path: PathBuf,
+    event_count: usize,
+    duration_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplaySummary {
+    path: PathBuf,
+    event_count: usize,
+    duration_ms: u64,
+    speed: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionStatus {
+    recording: Option<PathBuf>,
+    replaying: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReplayFinished {
+    path: PathBuf,
+    completed: bool,
+}
+
+struct Recorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    started: Instant,
+    event_count: usize,
+}
+
+impl Recorder {
+    fn create(path: &Path, app_version: &str) -> Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent).context("create recording folder")?;
+        }
+        let file =
+            File::create(path).with_context(|| format!("create recording {}", path.display()))?;
+        let mut recorder = Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+            started: Instant::now(),
+            event_count: 0,
+        };
+
+        let header = SessionHeader {
+            format: SESSION_FORMAT.to_string(),
+            version: SESSION_VERSION,
+            started_at: crate::clock::now_ms() as i64,
+            app_version: app_version.to_string(),
+        };
+        recorder.write_line(&header)?;
+        Ok(recorder)
+    }
+
+    fn write_line(&mut self, value: &impl Serialize) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, value).context("write recording")?;
+        self.writer.write_all(b"\n").context("write recording")?;
+        // Flushed per event so a crash still leaves a usable recording.
+        self.writer.flush().context("write recording")?;
+        Ok(())
+    }
+
+    fn record(&mut self, event: SessionEvent) -> Result<()> {
+        let entry = SessionEntry {
+            offset_ms: self.started.elapsed().as_millis() as u64,
+            event,
+        };
+        self.write_line(&entry)?;
+        self.event_count += 1;
+        Ok(())
+    }
+
+    fn summary(&self) -> RecordingSummary {
+        RecordingSummary {
+            path: self.path.clone(),
+            event_count: self.event_count,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+struct Replay {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    /// Job ID as of the last replayed transition, served by `get_job_id`.
+    job_id: Arc<Mutex<Option<String>>>,
+}
+
+#[derive(Default)]
+pub(crate) struct SessionState {
+    recorder: Mutex<Option<Recorder>>,
+    replay: Mutex<Option<Replay>>,
+    /// `--replay`, held until the chat window is listening.
+    queued_replay: Mutex<Option<(PathBuf, f64)>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<std::sync::MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock session state: {err}"))
+}
+
+fn read_session(reader: impl BufRead) -> Result<Vec<SessionEntry>> {
+    let mut lines = reader.lines();
+    let header = lines
+        .next()
+        .context("Recording is empty")?
+        .context("read recording")?;
+    let header: SessionHeader =
+        serde_json::from_str(&header).context("Not a BloxChat session recording")?;
+    if header.format != SESSION_FORMAT {
+        anyhow::bail!("Not a BloxChat session recording");
+    }
+    crate::storage::check_version("Recording", header.version, SESSION_VERSION)?;
+
+    let mut entries = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let line = line.context("read recording")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: SessionEntry = serde_json::from_str(&line)
+            .with_context(|| format!("Invalid event on line {}", index + 2))?;
+        entries.push(entry);
+    }
+    // Offsets only ever grow while recording; sort anyway so an edited file
+    // can't make the replay go backwards.
+    entries.sort_by_key(|entry| entry.offset_ms);
+    Ok(entries)
+}
+
+fn validate_speed(speed: f64) -> Result<f64> {
+    if !speed.is_finite() || !(MIN_REPLAY_SPEED..=MAX_REPLAY_SPEED).contains(&speed) {
+        anyhow::bail!("Replay speed must be between {MIN_REPLAY_SPEED} and {MAX_REPLAY_SPEED}");
+    }
+    Ok(speed)
+}
+
+/// When an event recorded at `offset_ms` plays back at `speed`.
+fn replay_delay(offset_ms: u64, speed: f64) -> Duration {
+    Duration::from_secs_f64(offset_ms as f64 / 1000.0 / speed)
+}
+
+/// Sleeps until `deadline`, returning false if the replay was stopped first.
+fn sleep_until(deadline: Instant, stop: &AtomicBool) -> bool {
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return false;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        std::thread::sleep((deadline - now).min(REPLAY_POLL_INTERVAL));
+    }
+}
+
+pub(crate) fn start_recording(app: &AppHandle, path: &Path) -> Result<()> {
+    let state = app.state::<SessionState>();
+    if lock(&state.replay)?.is_some() {
+        anyhow::bail!("Can't record while a session is replaying");
+    }
+
+    let mut recorder = Recorder::create(path, &app.package_info().version.to_string())?;
+    // Start from the current server so the replay opens the right channel.
+    if let Ok(job_id) = crate::roblox::get_job_id(&app.state::<crate::roblox::LogSettingsState>()) {
+        recorder.record(SessionEvent::JobId { job_id })?;
+    }
+    *lock(&state.recorder)? = Some(recorder);
+    Ok(())
+}
+
+pub(crate) fn stop_recording(app: &AppHandle) -> Result<Option<RecordingSummary>> {
+    let recorder = lock(&app.state::<SessionState>().recorder)?.take();
+    Ok(recorder.map(|recorder| recorder.summary()))
+}
+
+fn record(app: &AppHandle, event: SessionEvent) {
+    let state = app.state::<SessionState>();
+    let Ok(mut recorder) = lock(&state.recorder) else {
+        return;
+    };
+    let Some(active) = recorder.as_mut() else {
+        return;
+    };
+    if let Err(err) = active.record(event) {
+        tracing::warn!("Stopping session recording: {err:#}");
+        *recorder = None;
+    }
+}
+
+pub(crate) fn record_messages(app: &AppHandle, batch: &ChatMessageBatch) {
+    record(app, SessionEvent::Messages(batch.clone()));
+}
+
+pub(crate) fn record_job_id(app: &AppHandle, job_id: &str) {
+    record(
+        app,
+        SessionEvent::JobId {
+            job_id: job_id.to_string(),
+        },
+    );
+}
+
+pub(crate) fn is_replaying(app: &AppHandle) -> bool {
+    let state = app.state::<SessionState>();
+    lock(&state.replay).is_ok_and(|replay| replay.is_some())
+}
+
+/// The replayed Job ID, if a replay is running and has reached one.
+pub(crate) fn replay_job_id(app: &AppHandle) -> Option<String> {
+    let state = app.state::<SessionState>();
+    let replay = lock(&state.replay).ok()?;
+    let job_id = replay.as_ref()?.job_id.lock().ok()?.clone();
+    job_id
+}
+
+fn emit_event(app: &AppHandle, event: SessionEvent, job_id: &Mutex<Option<String>>) {
+    match event {
+        SessionEvent::Messages(batch) => {
+            if let Some(moderated) = crate::moderation::moderate(app, batch) {
+                let _ = app.emit(crate::chat_client::MESSAGES_EVENT, moderated);
+            }
+        }
+        SessionEvent::JobId { job_id: next } => {
+            if let Ok(mut current) = job_id.lock() {
+                *current = Some(next.clone());
+            }
+            let _ = app.emit(
+                crate::roblox::JOB_ID_EVENT,
+                crate::roblox::JobIdChanged { job_id: next },
+            );
+        }
+    }
+}
+
+/// Plays a recording back through the live chat and Job ID events. The live
+/// chat connection is closed and log watcher updates are held back until the
+/// replay ends, when the windows' channels are joined again.
+pub(crate) fn start_replay(app: &AppHandle, path: &Path, speed: f64) -> Result<ReplaySummary> {
+    let speed = validate_speed(speed)?;
+    let file = File::open(path).with_context(|| format!("open recording {}", path.display()))?;
+    let entries = read_session(BufReader::new(file))?;
+
+    let state = app.state::<SessionState>();
+    if lock(&state.recorder)?.is_some() {
+        anyhow::bail!("Stop recording before replaying a session");
+    }
+    let mut replay = lock(&state.replay)?;
+    if let Some(previous) = replay.take() {
+        previous.stop.store(true, Ordering::Relaxed);
+    }
+
+    crate::chat_client::close_connection(app)?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let job_id = Arc::new(Mutex::new(None));
+    *replay = Some(Replay {
+        path: path.to_path_buf(),
+        stop: stop.clone(),
+        job_id: job_id.clone(),
+    });
+
+    let summary = ReplaySummary {
+        path: path.to_path_buf(),
+        event_count: entries.len(),
+        duration_ms: entries.last().map(|entry| entry.offset_ms).unwrap_or(0),
+        speed,
+    };
+
+    let app = app.clone();
+    let path = path.to_path_buf();
+    std::thread::spawn(move || {
+        let started = Instant::now();
+        let mut completed = true;
+        for entry in entries {
+            if !sleep_until(started + replay_delay(entry.offset_ms, speed), &stop) {
+                completed = false;
+                break;
+            }
+            emit_event(&app, entry.event, &job_id);
+        }
+
+        // A newer replay may already have replaced this one, and then keeps
+        // the connection closed.
+        let replaced = match lock(&app.state::<SessionState>().replay) {
+            Ok(mut replay) => {
+                if replay
+                    .as_ref()
+                    .is_some_and(|current| Arc::ptr_eq(&current.stop, &stop))
+                {
+                    *replay = None;
+                }
+                replay.is_some()
+            }
+            Err(_) => false,
+        };
+        if !replaced {
+            if let Err(err) = crate::chat_client::resume_after_replay(&app) {
+                tracing::error!("Failed to reconnect chat after replay: {err:#}");
+            }
+        }
+        let _ = app.emit(REPLAY_FINISHED_EVENT, ReplayFinished { path, completed });
+    });
+
+    Ok(summary)
+}
+
+/// Holds a replay back until [`start_queued_replay`], so events aren't
+/// emitted before the chat window listens for them.
+pub(crate) fn queue_replay(app: &AppHandle, path: &Path, speed: f64) -> Result<()> {
+    let speed = validate_speed(speed)?;
+    *lock(&app.state::<SessionState>().queued_replay)? = Some((path.to_path_buf(), speed));
+    Ok(())
+}
+
+/// Starts the replay queued at startup, if any; called once the chat window
+/// is ready.
+pub(crate) fn start_queued_replay(app: &AppHandle) -> Result<Option<ReplaySummary>> {
+    let queued = lock(&app.state::<SessionState>().queued_replay)?.take();
+    queued
+        .map(|(path, speed)| start_replay(app, &path, speed))
+        .transpose()
+}
+
+pub(crate) fn stop_replay(app: &AppHandle) -> Result<()> {
+    if let Some(replay) = lock(&app.state::<SessionState>().replay)?.take() {
+        replay.stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+pub(crate) fn get_session_status(app: &AppHandle) -> Result<SessionStatus> {
+    let state = app.state::<SessionState>();
+    let recording = lock(&state.recorder)?
+        .as_ref()
+        .map(|recorder| recorder.path.clone());
+    let replaying = lock(&state.replay)?
+        .as_ref()
+        .map(|replay| replay.path.clone());
+    Ok(SessionStatus {
+        recording,
+        replaying,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat_client::test_message;
+    use std::io::Cursor;
+
+    fn batch() -> ChatMessageBatch {
+        ChatMessageBatch {
+            channel: "global".to_string(),
+            messages: vec![test_message("m1", "1", "hello")],
+        }
+    }
+
+    #[test]
+    fn recordings_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("bloxchat-session-{}.jsonl", std::process::id()));
+        let mut recorder = Recorder::create(&path, "1.2.3").unwrap();
+        recorder
+            .record(SessionEvent::JobId {
+                job_id: "deadbeef-1111".to_string(),
+            })
+            .unwrap();
+        recorder.record(SessionEvent::Messages(batch())).unwrap();
+        assert_eq!(recorder.summary().event_count, 2);
+        drop(recorder);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].contains("\"format\":\"bloxchat-session\""));
+        assert!(lines[1].contains("\"kind\":\"jobId\""));
+
+        let entries = read_session(Cursor::new(text)).unwrap();
+        assert_eq!(
+            entries[0].event,
+            SessionEvent::JobId {
+                job_id: "deadbeef-1111".to_string()
+            }
+        );
+        assert_eq!(entries[1].event, SessionEvent::Messages(batch()));
+    }
+
+    #[test]
+    fn rejects_foreign_or_newer_files() {
+        assert!(read_session(Cursor::new("")).is_err());
+        assert!(read_session(Cursor::new("{\"hello\":1}\n")).is_err());
+        assert!(read_session(Cursor::new(
+            "{\"format\":\"bloxchat-session\",\"version\":99,\"startedAt\":0,\"appVersion\":\"9\"}\n"
+        ))
+        .is_err());
+        assert!(read_session(Cursor::new(
+            "{\"format\":\"bloxchat-session\",\"version\":1,\"startedAt\":0,\"appVersion\":\"1\"}\n\
+             {\"offsetMs\":5,\"kind\":\"unknown\"}\n"
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn replay_timing_scales_with_speed() {
+        assert_eq!(replay_delay(3_000, 1.0), Duration::from_secs(3));
+        assert_eq!(replay_delay(3_000, 4.0), Duration::from_millis(750));
+        assert!(validate_speed(0.0).is_err());
+        assert!(validate_speed(f64::NAN).is_err());
+        assert!(validate_speed(1000.0).is_err());
+        assert_eq!(validate_speed(2.5).unwrap(), 2.5);
+
+        let stop = AtomicBool::new(true);
+        assert!(!sleep_until(
+            Instant::now() + Duration::from_secs(60),
+            &stop
+        ));
+    }
+}
@@ -0,0 +1,600 @@
+use crate::logging::LogLevel;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+const VERSION_KEY: &str = "settingsVersion";
+/// Emitted with a [`SettingsChanged`] after every successful update.
+pub(crate) const SETTINGS_CHANGED_EVENT: &str = "settings://changed";
+pub(crate) const DEFAULT_API_URL: &str = "https://bloxchat.logix.lol";
+const DEFAULT_JOIN_MESSAGE: &str = "joined the channel";
+const DEFAULT_DISCORD_RPC_APP_ID: &str = "1183656313130078298";
+const DISCORD_RPC_DISABLED: &str = "-1";
+const MIN_GUI_OPACITY: f64 = 0.2;
+const MAX_GUI_OPACITY: f64 = 1.0;
+
+/// Store keys owned by [`Settings`], one per field.
+const SETTING_KEYS: [&str; 9] = [
+    "apiUrl",
+    "logsPath",
+    "imageLoadingEnabled",
+    "guiOpacity",
+    "windowCollapseDirection",
+    "joinMessage",
+    "discordRpcAppId",
+    "favoritedMedia",
+    "logLevel",
+];
+
+/// `MIGRATIONS[n]` upgrades stored values from version `n` to `n + 1`.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[migrate_unversioned];
+const SETTINGS_VERSION: u64 = MIGRATIONS.len() as u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CollapseDirection {
+    Top,
+    #[default]
+    Bottom,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Settings {
+    pub(crate) api_url: String,
+    /// Empty means the platform's default Roblox logs folder.
+    pub(crate) logs_path: String,
+    pub(crate) image_loading_enabled: bool,
+    pub(crate) gui_opacity: f64,
+    pub(crate) window_collapse_direction: CollapseDirection,
+    pub(crate) join_message: String,
+    /// Digits, or `-1` to turn Discord presence off.
+    pub(crate) discord_rpc_app_id: String,
+    pub(crate) favorited_media: Vec<String>,
+    pub(crate) log_level: LogLevel,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            logs_path: String::new(),
+            image_loading_enabled: false,
+            gui_opacity: MAX_GUI_OPACITY,
+            window_collapse_direction: CollapseDirection::Bottom,
+            join_message: DEFAULT_JOIN_MESSAGE.to_string(),
+            discord_rpc_app_id: DEFAULT_DISCORD_RPC_APP_ID.to_string(),
+            favorited_media: Vec::new(),
+            log_level: LogLevel::Info,
+        }
+    }
+}
+
+/// Fields to change; the rest are left as they are.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct SettingsPatch {
+    pub(crate) api_url: Option<String>,
+    pub(crate) logs_path: Option<String>,
+    pub(crate) image_loading_enabled: Option<bool>,
+    pub(crate) gui_opacity: Option<f64>,
+    pub(crate) window_collapse_direction: Option<CollapseDirection>,
+    pub(crate) join_message: Option<String>,
+    pub(crate) discord_rpc_app_id: Option<String>,
+    pub(crate) favorited_media: Option<Vec<String>>,
+    pub(crate) log_level: Option<LogLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SettingsChanged {
+    pub(crate) settings: Settings,
+    /// Store keys whose value changed.
+    pub(crate) changed: Vec<String>,
+}
+
+/// Values that apply to this run only, from the command line, `BLOXCHAT_*`
+/// variables or the demo. [`get_settings`] shows them over the saved
+/// settings, but they are never written to the store.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SettingsOverrides {
+    pub(crate) api_url: Option<String>,
+    pub(crate) logs_path: Option<String>,
+}
+
+impl SettingsOverrides {
+    fn validate(self) -> Result<SettingsOverrides> {
+        let api_url = self
+            .api_url
+            .map(|api_url| crate::cli::normalize_api_url(&api_url))
+            .transpose()?;
+        let logs_path = self.logs_path.map(|path| path.trim().to_string());
+        if let Some(path) = logs_path.as_deref().filter(|path| !path.is_empty()) {
+            crate::roblox::validate_logs_path(path)?;
+        }
+        Ok(SettingsOverrides { api_url, logs_path })
+    }
+
+    fn apply_to(&self, saved: &Settings) -> Settings {
+        let mut settings = saved.clone();
+        if let Some(api_url) = &self.api_url {
+            settings.api_url = api_url.clone();
+        }
+        if let Some(logs_path) = &self.logs_path {
+            settings.logs_path = logs_path.clone();
+        }
+        settings
+    }
+
+    /// Drops the overrides for the fields `patch` sets, so a change made in
+    /// the app takes effect.
+    fn release(&mut self, patch: &SettingsPatch) {
+        if patch.api_url.is_some() {
+            self.api_url = None;
+        }
+        if patch.logs_path.is_some() {
+            self.logs_path = None;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CurrentSettings {
+    saved: Settings,
+    overrides: SettingsOverrides,
+}
+
+impl CurrentSettings {
+    fn effective(&self) -> Settings {
+        self.overrides.apply_to(&self.saved)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct SettingsState {
+    settings: Mutex<CurrentSettings>,
+}
+
+fn normalize_discord_rpc_app_id(value: &str) -> Result<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() || trimmed == DISCORD_RPC_DISABLED {
+        return Ok(DISCORD_RPC_DISABLED.to_string());
+    }
+    if !trimmed.bytes().all(|byte| byte.is_ascii_digit()) {
+        anyhow::bail!("Discord RPC App ID must be numeric or -1 to disable.");
+    }
+    Ok(trimmed.to_string())
+}
+
+impl Settings {
+    /// Validates `patch` and returns the settings with it applied.
+    fn apply(&self, patch: SettingsPatch) -> Result<Settings> {
+        let mut next = self.clone();
+        if let Some(api_url) = patch.api_url {
+            next.api_url = if api_url.trim().is_empty() {
+                DEFAULT_API_URL.to_string()
+            } else {
+                crate::cli::normalize_api_url(&api_url)?
+            };
+        }
+        if let Some(logs_path) = patch.logs_path {
+            next.logs_path = logs_path.trim().to_string();
+        }
+        if let Some(enabled) = patch.image_loading_enabled {
+            next.image_loading_enabled = enabled;
+        }
+        if let Some(opacity) = patch.gui_opacity {
+            if !(MIN_GUI_OPACITY..=MAX_GUI_OPACITY).contains(&opacity) {
+                anyhow::bail!(
+                    "GUI opacity must be between {MIN_GUI_OPACITY} and {MAX_GUI_OPACITY}"
+                );
+            }
+            next.gui_opacity = opacity;
+        }
+        if let Some(direction) = patch.window_collapse_direction {
+            next.window_collapse_direction = direction;
+        }
+        if let Some(join_message) = patch.join_message {
+            let trimmed = join_message.trim();
+            next.join_message = if trimmed.is_empty() {
+                DEFAULT_JOIN_MESSAGE.to_string()
+            } else {
+                trimmed.to_string()
+            };
+        }
+        if let Some(app_id) = patch.discord_rpc_app_id {
+            next.discord_rpc_app_id = normalize_discord_rpc_app_id(&app_id)?;
+        }
+        if let Some(media) = patch.favorited_media {
+            let mut favorites: Vec<String> = Vec::with_capacity(media.len());
+            for url in media {
+                let url = url.trim();
+                if !url.is_empty() && !favorites.iter().any(|existing| existing == url) {
+                    favorites.push(url.to_string());
+                }
+            }
+            next.favorited_media = favorites;
+        }
+        if let Some(level) = patch.log_level {
+            next.log_level = level;
+        }
+        Ok(next)
+    }
+
+    /// Builds settings from migrated store values. A value that fails
+    /// validation falls back to its default rather than losing the rest.
+    fn from_stored(values: Map<String, Value>) -> Settings {
+        let mut settings = Settings::default();
+        for (key, value) in values {
+            if value.is_null() {
+                continue;
+            }
+            let next = serde_json::from_value::<SettingsPatch>(json!({ key.as_str(): value }))
+                .map_err(anyhow::Error::from)
+                .and_then(|patch| settings.apply(patch));
+            match next {
+                Ok(next) => settings = next,
+                Err(err) => tracing::warn!("Ignoring invalid setting {key}: {err:#}"),
+            }
+        }
+        settings
+    }
+
+    fn to_values(&self) -> Map<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(values)) => values,
+            _ => Map::new(),
+        }
+    }
+
+    fn changed_keys(&self, next: &Settings) -> Vec<String> {
+        let before = self.to_values();
+        next.to_values()
+            .into_iter()
+            .filter(|(key, value)| before.get(key) != Some(value))
+            .map(|(key, _)| key)
+            .collect()
+    }
+}
+
+/// Version 0 is every store written before settings were versioned, when
+/// only the frontend checked values and did so as it read them.
+fn migrate_unversioned(values: &mut Map<String, Value>) {
+    // Opacity could be saved as the slider's string and was clamped on read.
+    if let Some(opacity) = values.get("guiOpacity") {
+        let numeric = match opacity {
+            Value::String(text) => text.trim().parse::<f64>().ok(),
+            other => other.as_f64(),
+        };
+        match numeric.filter(|opacity| opacity.is_finite()) {
+            Some(opacity) => {
+                values.insert(
+                    "guiOpacity".to_string(),
+                    json!(opacity.clamp(MIN_GUI_OPACITY, MAX_GUI_OPACITY)),
+                );
+            }
+            None => {
+                values.remove("guiOpacity");
+            }
+        }
+    }
+    // Anything but "top" read as "bottom".
+    if values.get("windowCollapseDirection") != Some(&json!("top")) {
+        values.remove("windowCollapseDirection");
+    }
+    // An App ID that failed validation read as disabled, not as the default.
+    if let Some(app_id) = values.get("discordRpcAppId") {
+        let app_id = app_id
+            .as_str()
+            .and_then(|app_id| normalize_discord_rpc_app_id(app_id).ok())
+            .unwrap_or_else(|| DISCORD_RPC_DISABLED.to_string());
+        values.insert("discordRpcAppId".to_string(), json!(app_id));
+    }
+    if let Some(Value::Array(media)) = values.get_mut("favoritedMedia") {
+        media.retain(Value::is_string);
+    }
+}
+
+/// Runs the migrations needed to bring values saved at `version` up to date.
+/// Returns whether anything ran.
+fn migrate(values: &mut Map<String, Value>, version: u64) -> Result<bool> {
+    crate::storage::check_version("Settings store", version, SETTINGS_VERSION)?;
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(values);
+    }
+    Ok(version < SETTINGS_VERSION)
+}
+
+fn lock_settings(app: &AppHandle) -> Result<std::sync::MutexGuard<'_, CurrentSettings>> {
+    app.state::<SettingsState>()
+        .inner()
+        .settings
+        .lock()
+        .map_err(|err| anyhow::anyhow!("lock settings: {err}"))
+}
+
+/// Reads and migrates the saved settings. Migrated values are written back;
+/// settings from a newer version are read as far as possible and left alone.
+pub(crate) fn load_settings(app: &AppHandle) {
+    let store = match crate::storage::store(app) {
+        Ok(store) => store,
+        Err(err) => {
+            tracing::error!("Failed to open settings store: {err:#}");
+            return;
+        }
+    };
+    let mut values: Map<String, Value> = SETTING_KEYS
+        .iter()
+        .filter_map(|key| store.get(key).map(|value| (key.to_string(), value)))
+        .collect();
+    let version = store
+        .get(VERSION_KEY)
+        .and_then(|value| value.as_u64())
+        .unwrap_or(0);
+    let migrated = migrate(&mut values, version).unwrap_or_else(|err| {
+        tracing::warn!("{err:#}");
+        false
+    });
+    let settings = Settings::from_stored(values);
+
+    if migrated {
+        for (key, value) in settings.to_values() {
+            store.set(key, value);
+        }
+        store.set(VERSION_KEY, SETTINGS_VERSION);
+        if let Err(err) = store.save() {
+            tracing::error!("Failed to save migrated settings: {err:#}");
+        }
+    }
+    if let Ok(mut current) = lock_settings(app) {
+        current.saved = settings;
+    }
+}
+
+/// The settings in effect, overrides included.
+pub(crate) fn get_settings(app: &AppHandle) -> Result<Settings> {
+    Ok(lock_settings(app)?.effective())
+}
+
+/// The settings as saved, without this run's overrides.
+pub(crate) fn saved_settings(app: &AppHandle) -> Result<Settings> {
+    Ok(lock_settings(app)?.saved.clone())
+}
+
+fn check_logs_path(patch: &SettingsPatch) -> Result<()> {
+    if let Some(logs_path) = patch.logs_path.as_deref() {
+        if !logs_path.trim().is_empty() {
+            crate::roblox::validate_logs_path(logs_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// The saved settings `patch` would produce, without saving them.
+pub(crate) fn preview_update(app: &AppHandle, patch: SettingsPatch) -> Result<Settings> {
+    check_logs_path(&patch)?;
+    lock_settings(app)?.saved.apply(patch)
+}
+
+/// Emits [`SETTINGS_CHANGED_EVENT`] if the settings in effect changed and
+/// returns them.
+fn publish(app: &AppHandle, before: &Settings, after: Settings) -> Result<Settings> {
+    let changed = before.changed_keys(&after);
+    if changed.is_empty() {
+        return Ok(after);
+    }
+    let change = SettingsChanged {
+        settings: after.clone(),
+        changed,
+    };
+    app.emit(SETTINGS_CHANGED_EVENT, change)
+        .context("emit settings change")?;
+    Ok(after)
+}
+
+/// Saves what `next` makes of the saved settings, releases the overrides
+/// `release` sets, and emits [`SETTINGS_CHANGED_EVENT`] if the settings in
+/// effect changed.
+fn commit(
+    app: &AppHandle,
+    release: Option<&SettingsPatch>,
+    next: impl FnOnce(&Settings) -> Result<Settings>,
+) -> Result<Settings> {
+    let (before, after) = {
+        let mut current = lock_settings(app)?;
+        let before = current.effective();
+        let next = next(&current.saved)?;
+        let changed = current.saved.changed_keys(&next);
+        if !changed.is_empty() {
+            let store = crate::storage::store(app).context("open settings store")?;
+            let values = next.to_values();
+            for key in &changed {
+                if let Some(value) = values.get(key) {
+                    store.set(key.as_str(), value.clone());
+                }
+            }
+            store.set(VERSION_KEY, SETTINGS_VERSION);
+            store.save().context("save settings store")?;
+        }
+        current.saved = next;
+        if let Some(patch) = release {
+            current.overrides.release(patch);
+        }
+        (before, current.effective())
+    };
+    publish(app, &before, after)
+}
+
+/// Validates and saves `patch`. A field set here replaces its override.
+pub(crate) fn update_settings(app: &AppHandle, patch: SettingsPatch) -> Result<Settings> {
+    check_logs_path(&patch)?;
+    let release = patch.clone();
+    commit(app, Some(&release), |current| current.apply(patch))
+}
+
+/// Replaces this run's overrides, validating them first.
+pub(crate) fn set_overrides(app: &AppHandle, overrides: SettingsOverrides) -> Result<Settings> {
+    let overrides = overrides.validate()?;
+    let (before, after) = {
+        let mut current = lock_settings(app)?;
+        let before = current.effective();
+        current.overrides = overrides;
+        (before, current.effective())
+    };
+    publish(app, &before, after)
+}
+
+/// Replaces every setting with `values` as saved by [`settings_values`],
+/// migrating them first. Missing or invalid ones go back to their defaults.
+pub(crate) fn replace_settings(
+    app: &AppHandle,
+    mut values: Map<String, Value>,
+) -> Result<Settings> {
+    let version = values
+        .remove(VERSION_KEY)
+        .and_then(|value| value.as_u64())
+        .unwrap_or(0);
+    migrate(&mut values, version)?;
+    commit(app, None, |_| Ok(Settings::from_stored(values)))
+}
+
+/// The saved settings as store values, with their version.
+pub(crate) fn settings_values(app: &AppHandle) -> Result<Map<String, Value>> {
+    let mut values = lock_settings(app)?.saved.to_values();
+    values.insert(VERSION_KEY.to_string(), json!(SETTINGS_VERSION));
+    Ok(values)
+}
+
+/// Calls `handler` with the new settings whenever the setting stored under
+/// `key` changes.
+pub(crate) fn on_change(
+    app: &AppHandle,
+    key: &'static str,
+    handler: impl Fn(&Settings) + Send + Sync + 'static,
+) {
+    app.listen(
+        SETTINGS_CHANGED_EVENT,
+        move |event| match serde_json::from_str::<SettingsChanged>(event.payload()) {
+            Ok(change) if change.changed.iter().any(|changed| changed == key) => {
+                handler(&change.settings)
+            }
+            Ok(_) => {}
+            Err(err) => tracing::error!("Failed to read settings change: {err}"),
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(values) => values,
+            _ => panic!("expected an object"),
+        }
+    }
+
+    #[test]
+    fn migrates_unversioned_values_as_the_frontend_read_them() {
+        let mut values = stored(json!({
+            "guiOpacity": "0.05",
+            "windowCollapseDirection": "sideways",
+            "discordRpcAppId": "not-a-number",
+            "favoritedMedia": ["https://a.example/cat.gif", 7, ""],
+            "apiUrl": "chat.example.com/",
+        }));
+        assert!(migrate(&mut values, 0).unwrap());
+        let settings = Settings::from_stored(values);
+
+        assert_eq!(settings.gui_opacity, MIN_GUI_OPACITY);
+        assert_eq!(
+            settings.window_collapse_direction,
+            CollapseDirection::Bottom
+        );
+        assert_eq!(settings.discord_rpc_app_id, DISCORD_RPC_DISABLED);
+        assert_eq!(settings.favorited_media, ["https://a.example/cat.gif"]);
+        assert_eq!(settings.api_url, "https://chat.example.com");
+        assert_eq!(settings.join_message, DEFAULT_JOIN_MESSAGE);
+
+        let mut current = stored(json!({ "guiOpacity": 0.5 }));
+        assert!(!migrate(&mut current, SETTINGS_VERSION).unwrap());
+        assert!(migrate(&mut current, SETTINGS_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn invalid_stored_values_fall_back_to_defaults() {
+        let settings = Settings::from_stored(stored(json!({
+            "guiOpacity": 3,
+            "imageLoadingEnabled": "yes",
+            "joinMessage": "  says hi  ",
+        })));
+        assert_eq!(settings.gui_opacity, MAX_GUI_OPACITY);
+        assert!(!settings.image_loading_enabled);
+        assert_eq!(settings.join_message, "says hi");
+    }
+
+    #[test]
+    fn updates_are_validated_and_report_changed_keys() {
+        let current = Settings::default();
+        let patch = |value: Value| serde_json::from_value::<SettingsPatch>(value).unwrap();
+
+        assert!(current.apply(patch(json!({ "guiOpacity": 0.1 }))).is_err());
+        assert!(current
+            .apply(patch(json!({ "discordRpcAppId": "12a" })))
+            .is_err());
+        assert!(current
+            .apply(patch(json!({ "apiUrl": "https://" })))
+            .is_err());
+        assert!(serde_json::from_value::<SettingsPatch>(json!({ "auth": {} })).is_err());
+
+        let next = current
+            .apply(patch(json!({
+                "guiOpacity": 0.6,
+                "joinMessage": "",
+                "discordRpcAppId": " ",
+                "favoritedMedia": ["b", " a ", "b"],
+            })))
+            .unwrap();
+        assert_eq!(next.join_message, DEFAULT_JOIN_MESSAGE);
+        assert_eq!(next.discord_rpc_app_id, DISCORD_RPC_DISABLED);
+        assert_eq!(next.favorited_media, ["b", "a"]);
+        assert_eq!(
+            current.changed_keys(&next),
+            ["discordRpcAppId", "favoritedMedia", "guiOpacity"]
+        );
+    }
+
+    #[test]
+    fn overrides_show_over_saved_settings_until_changed() {
+        let overrides = SettingsOverrides {
+            api_url: Some("chat.example.com/".to_string()),
+            logs_path: None,
+        };
+        let mut current = CurrentSettings {
+            saved: Settings::default(),
+            overrides: overrides.validate().unwrap(),
+        };
+        assert_eq!(current.effective().api_url, "https://chat.example.com");
+        assert_eq!(current.saved.api_url, DEFAULT_API_URL);
+
+        current.overrides.release(&SettingsPatch {
+            gui_opacity: Some(0.5),
+            ..Default::default()
+        });
+        assert_eq!(current.effective().api_url, "https://chat.example.com");
+        current.overrides.release(&SettingsPatch {
+            api_url: Some(String::new()),
+            ..Default::default()
+        });
+        assert_eq!(current.effective().api_url, DEFAULT_API_URL);
+
+        let bad = SettingsOverrides {
+            api_url: Some("https://".to_string()),
+            logs_path: None,
+        };
+        assert!(bad.validate().is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use crate::hotkeys::HotkeyBindings;
+use crate::message_guard::GuardRules;
+use crate::moderation::ModerationSettings;
+use crate::notifications::NotificationRules;
+use crate::settings::SettingsPatch;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+use tauri::AppHandle;
+
+const BUNDLE_FORMAT: &str = "bloxchat-settings";
+const BUNDLE_VERSION: u32 = 1;
+/// Points at a folder on this machine, so it stays behind.
+const LOCAL_SETTING_KEYS: [&str; 1] = ["logsPath"];
+
+/// Everything needed to set BloxChat up the same way elsewhere. Missing
+/// sections are left alone on import.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsBundle {
+    format: String,
+    version: u32,
+    #[serde(default)]
+    settings: Option<Map<String, Value>>,
+    #[serde(default)]
+    moderation: Option<ModerationSettings>,
+    #[serde(default)]
+    notification_rules: Option<NotificationRules>,
+    #[serde(default)]
+    message_guard: Option<GuardRules>,
+    #[serde(default)]
+    hotkeys: Option<HotkeyBindings>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SettingChange {
+    section: &'static str,
+    key: String,
+    before: Value,
+    after: Value,
+}
+
+/// What an import changes, per top-level key of each section.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImportPreview {
+    changes: Vec<SettingChange>,
+}
+
+/// Validated sections of a bundle, ready to apply.
+#[derive(Default)]
+struct PendingImport {
+    settings: Option<SettingsPatch>,
+    moderation: Option<ModerationSettings>,
+    notification_rules: Option<NotificationRules>,
+    message_guard: Option<GuardRules>,
+    hotkeys: Option<HotkeyBindings>,
+}
+
+fn parse_bundle(text: &str) -> Result<SettingsBundle> {
+    let bundle: SettingsBundle =
+        serde_json::from_str(text).context("Not a BloxChat settings file")?;
+    if bundle.format != BUNDLE_FORMAT {
+        anyhow::bail!("Not a BloxChat settings file");
+    }
+    crate::storage::check_version("Settings file", bundle.version, BUNDLE_VERSION)?;
+    Ok(bundle)
+}
+
+fn to_value(value: impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// Adds a change for every top-level key that differs between `before` and
+/// `after`.
+fn diff_section(
+    section: &'static str,
+    before: &Value,
+    after: &Value,
+    changes: &mut Vec<SettingChange>,
+) {
+    let empty = Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let old = before.get(key).cloned().unwrap_or(Value::Null);
+        let new = after.get(key).cloned().unwrap_or(Value::Null);
+        if old != new {
+            changes.push(SettingChange {
+                section,
+                key: key.clone(),
+                before: old,
+                after: new,
+            });
+        }
+    }
+}
+
+/// Validates every section before anything is applied, so a bad file
+/// changes nothing.
+fn prepare_import(
+    app: &AppHandle,
+    bundle: SettingsBundle,
+) -> Result<(PendingImport, ImportPreview)> {
+    let mut pending = PendingImport::default();
+    let mut changes = Vec::new();
+
+    if let Some(mut values) = bundle.settings {
+        for key in LOCAL_SETTING_KEYS {
+            values.remove(key);
+        }
+        let patch: SettingsPatch =
+            serde_json::from_value(Value::Object(values)).context("read settings")?;
+        let current = crate::settings::saved_settings(app)?;
+        let next = crate::settings::preview_update(app, patch.clone())?;
+        diff_section(
+            "settings",
+            &to_value(current),
+            &to_value(next),
+            &mut changes,
+        );
+        pending.settings = Some(patch);
+    }
+    if let Some(moderation) = bundle.moderation {
+        let next = crate::moderation::validate_settings(moderation)?;
+        let current = crate::moderation::get_moderation_settings(app)?;
+        diff_section(
+            "moderation",
+            &to_value(current),
+            &to_value(&next),
+            &mut changes,
+        );
+        pending.moderation = Some(next);
+    }
+    if let Some(rules) = bundle.notification_rules {
+        let next = crate::notifications::validate_rules(rules)?;
+        let current = crate::notifications::get_notification_rules(app)?;
+        diff_section(
+            "notificationRules",
+            &to_value(current),
+            &to_value(&next),
+            &mut changes,
+        );
+        pending.notification_rules = Some(next);
+    }
+    if let Some(rules) = bundle.message_guard {
+        let next = crate::message_guard::validate_rules(rules)?;
+        let current = crate::message_guard::get_message_guard_rules(app)?;
+        diff_section(
+            "messageGuard",
+            &to_value(current),
+            &to_value(&next),
+            &mut changes,
+        );
+        pending.message_guard = Some(next);
+    }
+    if let Some(bindings) = bundle.hotkeys {
+        let next = crate::hotkeys::validate_bindings(bindings)?;
+        let current = crate::hotkeys::get_hotkeys(app)?;
+        diff_section(
+            "hotkeys",
+            &to_value(current),
+            &to_value(&next),
+            &mut changes,
+        );
+        pending.hotkeys = Some(next);
+    }
+
+    Ok((pending, ImportPreview { changes }))
+}
+
+fn read_bundle(path: &Path) -> Result<SettingsBundle> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("read settings file {}", path.display()))?;
+    parse_bundle(&text)
+}
+
+pub(crate) fn export_settings(app: &AppHandle, path: &Path) -> Result<()> {
+    let mut settings = match to_value(crate::settings::saved_settings(app)?) {
+        Value::Object(values) => values,
+        _ => Map::new(),
+    };
+    for key in LOCAL_SETTING_KEYS {
+        settings.remove(key);
+    }
+    let bundle = SettingsBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        settings: Some(settings),
+        moderation: Some(crate::moderation::get_moderation_settings(app)?),
+        notification_rules: Some(crate::notifications::get_notification_rules(app)?),
+        message_guard: Some(crate::message_guard::get_message_guard_rules(app)?),
+        hotkeys: Some(crate::hotkeys::get_hotkeys(app)?),
+    };
+    let json = serde_json::to_string_pretty(&bundle).context("serialize settings")?;
+    std::fs::write(path, json).with_context(|| format!("write settings to {}", path.display()))
+}
+
+pub(crate) fn preview_settings_import(app: &AppHandle, path: &Path) -> Result<ImportPreview> {
+    let (_, preview) = prepare_import(app, read_bundle(path)?)?;
+    Ok(preview)
+}
+
+/// Applies a settings file and returns what changed.
+pub(crate) fn import_settings(app: &AppHandle, path: &Path) -> Result<ImportPreview> {
+    let (pending, preview) = prepare_import(app, read_bundle(path)?)?;
+
+    if let Some(patch) = pending.settings {
+        crate::settings::update_settings(app, patch)?;
+    }
+    if let Some(moderation) = pending.moderation {
+        crate::moderation::set_moderation_settings(app, moderation)?;
+    }
+    if let Some(rules) = pending.notification_rules {
+        crate::notifications::set_notification_rules(app, rules)?;
+    }
+    if let Some(rules) = pending.message_guard {
+        crate::message_guard::set_message_guard_rules(app, rules)?;
+    }
+    if let Some(bindings) = pending.hotkeys {
+        crate::hotkeys::set_hotkeys(app, bindings)?;
+    }
+    Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(parse_bundle("{}").is_err());
+        assert!(parse_bundle(r#"{"format":"bloxchat-moderation","version":1}"#).is_err());
+        assert!(parse_bundle(r#"{"format":"bloxchat-settings","version":99}"#).is_err());
+
+        let bundle = parse_bundle(r#"{"format":"bloxchat-settings","version":1}"#).unwrap();
+        assert!(bundle.settings.is_none() && bundle.moderation.is_none());
+        assert!(bundle.hotkeys.is_none());
+    }
+
+    #[test]
+    fn previews_changed_keys_only() {
+        let mut changes = Vec::new();
+        diff_section(
+            "settings",
+            &json!({ "guiOpacity": 1.0, "joinMessage": "hi", "imageLoadingEnabled": false }),
+            &json!({ "guiOpacity": 0.5, "joinMessage": "hi", "favoritedMedia": ["a"] }),
+            &mut changes,
+        );
+
+        let keys: Vec<_> = changes.iter().map(|change| change.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            ["favoritedMedia", "guiOpacity", "imageLoadingEnabled"]
+        );
+        assert_eq!(changes[1].before, json!(1.0));
+        assert_eq!(changes[1].after, json!(0.5));
+        assert_eq!(changes[2].after, Value::Null);
+    }
+}
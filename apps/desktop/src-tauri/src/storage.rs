@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, OnceLock};
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+
+const STORE_FILE: &str = "store.json";
+/// Placed next to the executable, keeps all data beside it.
+const PORTABLE_MARKER: &str = "portable.txt";
+const PORTABLE_DATA_DIR: &str = "data";
+/// Holds one data dir per instance started beside the main one.
+const INSTANCES_DIR: &str = "instances";
+
+/// Set before the app starts when this process runs beside the main
+/// instance; see [`use_separate_instance`].
+static INSTANCE: OnceLock<String> = OnceLock::new();
+
+static PORTABLE_DATA: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?;
+    dir.join(PORTABLE_MARKER)
+        .is_file()
+        .then(|| dir.join(PORTABLE_DATA_DIR))
+});
+
+/// Whether the portable marker sits next to the executable.
+pub(crate) fn is_portable() -> bool {
+    PORTABLE_DATA.is_some()
+}
+
+/// Fails if `found`, the format version of something BloxChat saved, is
+/// newer than the `supported` one this build reads.
+pub(crate) fn check_version<T>(what: &str, found: T, supported: T) -> Result<()>
+where
+    T: PartialOrd + std::fmt::Display,
+{
+    if found > supported {
+        anyhow::bail!("{what} is from a newer version of BloxChat (v{found})");
+    }
+    Ok(())
+}
+
+/// Keeps this process's files in `instances/<name>` so it can run beside
+/// the main instance without sharing its store. Must be called before the
+/// app starts.
+pub(crate) fn use_separate_instance(name: String) {
+    let _ = INSTANCE.set(name);
+}
+
+fn resolve_data_dir(app: &AppHandle) -> tauri::Result<PathBuf> {
+    let base = match PORTABLE_DATA.as_ref() {
+        Some(dir) => dir.clone(),
+        None => app.path().app_data_dir()?,
+    };
+    Ok(match INSTANCE.get() {
+        Some(name) => base.join(INSTANCES_DIR).join(name),
+        None => base,
+    })
+}
+
+/// Where BloxChat keeps its files: the app data dir, or `data` beside the
+/// executable in portable mode. Separate instances get a folder of their own
+/// inside it.
+pub(crate) fn data_dir(app: &AppHandle) -> Result<PathBuf> {
+    resolve_data_dir(app).context("resolve app data dir")
+}
+
+/// The settings store in [`data_dir`].
+pub(crate) fn store(app: &AppHandle) -> tauri_plugin_store::Result<Arc<Store<Wry>>> {
+    if PORTABLE_DATA.is_none() && INSTANCE.get().is_none() {
+        return app.store(STORE_FILE);
+    }
+    app.store(resolve_data_dir(app)?.join(STORE_FILE))
+}
@@ -0,0 +1,378 @@
+use crate::archive::ArchivedMessage;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TranscriptFormat {
+    Json,
+    Markdown,
+    Html,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptMessage<'a> {
+    id: &'a str,
+    author_id: &'a str,
+    username: &'a str,
+    display_name: &'a str,
+    content: &'a str,
+    reply_to_id: Option<&'a str>,
+    received_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Transcript<'a> {
+    channel: &'a str,
+    job_id: Option<&'a str>,
+    place_id: Option<&'a str>,
+    session_start: Option<i64>,
+    session_end: Option<i64>,
+    exported_at: i64,
+    messages: Vec<TranscriptMessage<'a>>,
+}
+
+impl<'a> Transcript<'a> {
+    fn new(channel: &'a str, messages: &'a [ArchivedMessage], exported_at: i64) -> Self {
+        Self {
+            channel,
+            job_id: crate::roblox::is_valid_job_id(channel).then_some(channel),
+            place_id: messages
+                .iter()
+                .find_map(|message| message.place_id.as_deref()),
+            session_start: messages.first().map(|message| message.received_at),
+            session_end: messages.last().map(|message| message.received_at),
+            exported_at,
+            messages: messages
+                .iter()
+                .map(|archived| TranscriptMessage {
+                    id: &archived.message.id,
+                    author_id: &archived.message.author.roblox_user_id,
+                    username: &archived.message.author.username,
+                    display_name: &archived.message.author.display_name,
+                    content: &archived.message.content,
+                    reply_to_id: archived.message.reply_to_id.as_deref(),
+                    received_at: archived.received_at,
+                })
+                .collect(),
+        }
+    }
+
+    /// Usernames by message id, to name the target of replies.
+    fn authors(&self) -> HashMap<&'a str, &'a str> {
+        self.messages
+            .iter()
+            .map(|message| (message.id, message.username))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TranscriptExport {
+    path: PathBuf,
+    message_count: usize,
+}
+
+/// Formats a unix timestamp in milliseconds as `YYYY-MM-DD HH:MM:SS UTC`.
+pub(crate) fn format_utc(ms: i64) -> String {
+    let secs = ms.div_euclid(1000);
+    let days = secs.div_euclid(86_400);
+    let time = secs.rem_euclid(86_400);
+
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+fn format_optional_utc(ms: Option<i64>) -> String {
+    ms.map(format_utc).unwrap_or_else(|| "-".to_string())
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]<>#|~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render_json(transcript: &Transcript) -> Result<String> {
+    serde_json::to_string_pretty(transcript).context("serialize transcript")
+}
+
+fn render_markdown(transcript: &Transcript) -> String {
+    let authors = transcript.authors();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# BloxChat transcript: {}\n",
+        escape_markdown(transcript.channel)
+    );
+    if let Some(job_id) = transcript.job_id {
+        let _ = writeln!(out, "- Job ID: `{job_id}`");
+    }
+    if let Some(place_id) = transcript.place_id {
+        let _ = writeln!(out, "- Place ID: `{place_id}`");
+    }
+    let _ = writeln!(
+        out,
+        "- Session: {} to {}",
+        format_optional_utc(transcript.session_start),
+        format_optional_utc(transcript.session_end)
+    );
+    let _ = writeln!(out, "- Exported: {}", format_utc(transcript.exported_at));
+    let _ = writeln!(out, "- Messages: {}", transcript.messages.len());
+
+    for message in &transcript.messages {
+        let _ = write!(
+            out,
+            "\n<a id=\"msg-{}\"></a>\n**{}** (@{}, `{}`) · {}\n",
+            escape_html(message.id),
+            escape_markdown(message.display_name),
+            escape_markdown(message.username),
+            message.author_id,
+            format_utc(message.received_at)
+        );
+        if let Some(reply_to_id) = message.reply_to_id {
+            match authors.get(reply_to_id) {
+                Some(username) => {
+                    let _ = writeln!(
+                        out,
+                        "> ↪ [reply to @{}](#msg-{})",
+                        escape_markdown(username),
+                        escape_html(reply_to_id)
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "> ↪ reply to message `{reply_to_id}`");
+                }
+            }
+        }
+        for line in message.content.lines() {
+            let _ = writeln!(out, "> {}", escape_markdown(line));
+        }
+    }
+    out
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;background:#111318;color:#e6e6e6;\
+max-width:860px;margin:2rem auto;padding:0 1rem}header{border-bottom:1px solid #333;\
+margin-bottom:1rem}dl{display:grid;grid-template-columns:max-content 1fr;gap:.25rem 1rem}\
+dt{color:#999}article{padding:.5rem 0;border-bottom:1px solid #222}article:target{background:#23283a}\
+.meta{color:#999;font-size:.85rem}.name{color:#fff;font-weight:600}.reply{font-size:.85rem;\
+color:#8ab4f8}.content{white-space:pre-wrap;word-break:break-word;margin-top:.25rem}a{color:#8ab4f8}";
+
+fn render_html(transcript: &Transcript) -> String {
+    let authors = transcript.authors();
+    let title = format!("BloxChat transcript: {}", transcript.channel);
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n<header>\n<h1>{}</h1>\n<dl>\n",
+        escape_html(&title),
+        escape_html(&title)
+    );
+    if let Some(job_id) = transcript.job_id {
+        let _ = writeln!(out, "<dt>Job ID</dt><dd><code>{job_id}</code></dd>");
+    }
+    if let Some(place_id) = transcript.place_id {
+        let _ = writeln!(out, "<dt>Place ID</dt><dd><code>{place_id}</code></dd>");
+    }
+    let _ = writeln!(
+        out,
+        "<dt>Session</dt><dd>{} to {}</dd>\n<dt>Exported</dt><dd>{}</dd>\n\
+         <dt>Messages</dt><dd>{}</dd>\n</dl>\n</header>\n<main>",
+        format_optional_utc(transcript.session_start),
+        format_optional_utc(transcript.session_end),
+        format_utc(transcript.exported_at),
+        transcript.messages.len()
+    );
+
+    for message in &transcript.messages {
+        let _ = write!(
+            out,
+            "<article id=\"msg-{}\">\n<div class=\"meta\"><span class=\"name\">{}</span> \
+             @{} · <code>{}</code> · <time>{}</time></div>\n",
+            escape_html(message.id),
+            escape_html(message.display_name),
+            escape_html(message.username),
+            escape_html(message.author_id),
+            format_utc(message.received_at)
+        );
+        if let Some(reply_to_id) = message.reply_to_id {
+            let _ = match authors.get(reply_to_id) {
+                Some(username) => writeln!(
+                    out,
+                    "<div class=\"reply\">↪ <a href=\"#msg-{}\">reply to @{}</a></div>",
+                    escape_html(reply_to_id),
+                    escape_html(username)
+                ),
+                None => writeln!(
+                    out,
+                    "<div class=\"reply\">↪ reply to message <code>{}</code></div>",
+                    escape_html(reply_to_id)
+                ),
+            };
+        }
+        let _ = writeln!(
+            out,
+            "<div class=\"content\">{}</div>\n</article>",
+            escape_html(message.content)
+        );
+    }
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+fn render(transcript: &Transcript, format: TranscriptFormat) -> Result<String> {
+    match format {
+        TranscriptFormat::Json => render_json(transcript),
+        TranscriptFormat::Markdown => Ok(render_markdown(transcript)),
+        TranscriptFormat::Html => Ok(render_html(transcript)),
+    }
+}
+
+/// Writes the archived messages of `channel` between `from` and `to` (unix
+/// ms, inclusive) to `path`.
+pub(crate) fn export_transcript(
+    app: &AppHandle,
+    channel: &str,
+    from: Option<i64>,
+    to: Option<i64>,
+    format: TranscriptFormat,
+    path: &Path,
+) -> Result<TranscriptExport> {
+    if !crate::roblox::is_valid_channel(channel) {
+        anyhow::bail!("Invalid channel: {channel}");
+    }
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            anyhow::bail!("Transcript range starts after it ends");
+        }
+    }
+
+    let messages = crate::archive::archived_range(app, channel, from, to)?;
+    let exported_at = crate::clock::now_ms() as i64;
+    let transcript = Transcript::new(channel, &messages, exported_at);
+    let contents = render(&transcript, format)?;
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).context("create transcript folder")?;
+    }
+    std::fs::write(path, contents)
+        .with_context(|| format!("write transcript to {}", path.display()))?;
+
+    Ok(TranscriptExport {
+        path: path.to_path_buf(),
+        message_count: messages.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat_client::test_message;
+
+    const JOB_ID: &str = "3f2b1c4d-0000-4000-8000-1234567890ab";
+
+    fn sample() -> Vec<ArchivedMessage> {
+        [
+            test_message("a", "156", "report <script>alert(1)</script>"),
+            test_message("b", "261", "*seen* it").replying_to("a"),
+            test_message("c", "156", "thanks").replying_to("missing"),
+        ]
+        .into_iter()
+        .map(|message| ArchivedMessage::new(JOB_ID, Some("606849621"), 1_760_000_000_000, message))
+        .collect()
+    }
+
+    #[test]
+    fn formats_utc_timestamps() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_utc(1_760_000_000_000), "2025-10-09 08:53:20 UTC");
+        assert_eq!(format_utc(951_782_400_000), "2000-02-29 00:00:00 UTC");
+    }
+
+    #[test]
+    fn json_keeps_author_ids_and_threading() {
+        let messages = sample();
+        let transcript = Transcript::new(JOB_ID, &messages, 1_760_000_100_000);
+        let value: serde_json::Value =
+            serde_json::from_str(&render_json(&transcript).unwrap()).unwrap();
+
+        assert_eq!(value["jobId"], JOB_ID);
+        assert_eq!(value["placeId"], "606849621");
+        assert_eq!(value["sessionStart"], 1_760_000_000_000i64);
+        assert_eq!(value["messages"][0]["authorId"], "156");
+        assert_eq!(value["messages"][1]["replyToId"], "a");
+        assert_eq!(value["messages"][0]["replyToId"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn markdown_and_html_link_replies_and_escape_content() {
+        let messages = sample();
+        let transcript = Transcript::new(JOB_ID, &messages, 1_760_000_100_000);
+
+        let markdown = render_markdown(&transcript);
+        assert!(markdown.contains(&format!("- Job ID: `{JOB_ID}`")));
+        assert!(markdown.contains("[reply to @user156](#msg-a)"));
+        assert!(markdown.contains("reply to message `missing`"));
+        assert!(markdown.contains("> \\*seen\\* it"));
+
+        let html = render_html(&transcript);
+        assert!(html.contains("<article id=\"msg-a\">"));
+        assert!(html.contains("<a href=\"#msg-a\">reply to @user156</a>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn global_transcripts_have_no_job_id() {
+        let transcript = Transcript::new("global", &[], 0);
+        assert_eq!(transcript.job_id, None);
+        assert!(render_markdown(&transcript).contains("- Session: - to -"));
+    }
+}
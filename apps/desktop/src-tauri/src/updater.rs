@@ -151,7 +151,7 @@ fn notify_about_to_install(app: &AppHandle, latest_version: &str) {
     };
 
     if let Err(err) = app.emit(UPDATE_NOTICE_EVENT, payload) {
-        eprintln!("updater notice failed: {err}");
+        tracing::warn!("updater notice failed: {err}");
     }
 }
 
@@ -166,7 +166,7 @@ pub(crate) async fn check_for_startup_update(app: AppHandle) {
     }
 
     if let Err(err) = try_startup_update(&app).await {
-        eprintln!("updater failed: {err:#}");
+        tracing::error!("updater failed: {err:#}");
     }
 }
 
@@ -187,7 +187,7 @@ async fn try_startup_update(app: &AppHandle) -> Result<()> {
     }
 
     let Some(msi_url) = release_msi_url(&latest_release) else {
-        eprintln!("updater skipped: release missing a BloxChat .msi asset");
+        tracing::info!("updater skipped: release missing a BloxChat .msi asset");
         return Ok(());
     };
 
@@ -0,0 +1,468 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{
+    AppHandle, Emitter, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow,
+    WindowEvent,
+};
+
+const WINDOW_LAYOUTS_KEY: &str = "windowLayouts";
+const SNAP_THRESHOLD: i32 = 16;
+const MIN_OPACITY: f64 = 0.2;
+const MAX_OPACITY: f64 = 1.0;
+/// Logical height of a collapsed window, which only shows the chat header.
+const COLLAPSED_HEIGHT: f64 = 40.0;
+const WINDOW_OPACITY_EVENT: &str = "window://opacity";
+
+/// Saved geometry per monitor layout key, then per window label.
+type WindowLayouts = HashMap<String, HashMap<String, WindowGeometry>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bounds {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Bounds {
+    fn right(&self) -> i64 {
+        self.x as i64 + self.width as i64
+    }
+
+    fn bottom(&self) -> i64 {
+        self.y as i64 + self.height as i64
+    }
+
+    fn overlap_area(&self, other: &Bounds) -> i64 {
+        let width = self.right().min(other.right()) - (self.x.max(other.x) as i64);
+        let height = self.bottom().min(other.bottom()) - (self.y.max(other.y) as i64);
+        width.max(0) * height.max(0)
+    }
+
+    fn center_distance_sq(&self, other: &Bounds) -> i64 {
+        let dx =
+            (self.x as i64 * 2 + self.width as i64) - (other.x as i64 * 2 + other.width as i64);
+        let dy =
+            (self.y as i64 * 2 + self.height as i64) - (other.y as i64 * 2 + other.height as i64);
+        dx * dx + dy * dy
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MonitorBounds {
+    area: Bounds,
+    work_area: Bounds,
+    scale_factor: f64,
+}
+
+impl From<&Monitor> for MonitorBounds {
+    fn from(monitor: &Monitor) -> Self {
+        let work_area = monitor.work_area();
+        Self {
+            area: Bounds {
+                x: monitor.position().x,
+                y: monitor.position().y,
+                width: monitor.size().width,
+                height: monitor.size().height,
+            },
+            work_area: Bounds {
+                x: work_area.position.x,
+                y: work_area.position.y,
+                width: work_area.size.width,
+                height: work_area.size.height,
+            },
+            scale_factor: monitor.scale_factor(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    opacity: f64,
+    collapsed: bool,
+}
+
+impl WindowGeometry {
+    fn bounds(&self) -> Bounds {
+        Bounds {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+fn monitor_bounds(window: &WebviewWindow) -> Result<Vec<MonitorBounds>> {
+    let monitors = window
+        .available_monitors()
+        .context("list available monitors")?;
+    Ok(monitors.iter().map(MonitorBounds::from).collect())
+}
+
+/// Identifies a monitor arrangement so each setup (laptop only, docked, ...)
+/// keeps its own window placement.
+fn layout_key(monitors: &[MonitorBounds]) -> String {
+    let mut parts: Vec<String> = monitors
+        .iter()
+        .map(|monitor| {
+            format!(
+                "{}x{}+{}+{}@{}",
+                monitor.area.width,
+                monitor.area.height,
+                monitor.area.x,
+                monitor.area.y,
+                (monitor.scale_factor * 100.0).round() as i64
+            )
+        })
+        .collect();
+    parts.sort();
+    parts.join(";")
+}
+
+fn normalize_opacity(opacity: f64) -> f64 {
+    if !opacity.is_finite() {
+        return MAX_OPACITY;
+    }
+
+    opacity.clamp(MIN_OPACITY, MAX_OPACITY)
+}
+
+fn clamp_axis(start: i32, length: u32, area_start: i32, area_length: u32) -> (i32, u32) {
+    let length = length.min(area_length).max(1);
+    let max_start = area_start as i64 + area_length as i64 - length as i64;
+    let start = (start as i64).clamp(area_start as i64, max_start) as i32;
+    (start, length)
+}
+
+/// Moves (and if needed shrinks) `bounds` so it lies fully inside the work
+/// area of the monitor it overlaps most, or the nearest monitor if it is
+/// entirely off-screen.
+fn clamp_to_monitors(bounds: Bounds, monitors: &[MonitorBounds]) -> Bounds {
+    let Some(target) = monitors
+        .iter()
+        .max_by_key(|monitor| monitor.work_area.overlap_area(&bounds))
+        .filter(|monitor| monitor.work_area.overlap_area(&bounds) > 0)
+        .or_else(|| {
+            monitors
+                .iter()
+                .min_by_key(|monitor| monitor.work_area.center_distance_sq(&bounds))
+        })
+    else {
+        return bounds;
+    };
+
+    let area = target.work_area;
+    let (x, width) = clamp_axis(bounds.x, bounds.width, area.x, area.width);
+    let (y, height) = clamp_axis(bounds.y, bounds.height, area.y, area.height);
+    Bounds {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+fn snap_axis(start: i32, length: u32, area_start: i32, area_length: u32, threshold: i32) -> i32 {
+    let end = start as i64 + length as i64;
+    let area_end = area_start as i64 + area_length as i64;
+
+    if (start - area_start).abs() <= threshold {
+        return area_start;
+    }
+
+    if (end - area_end).abs() <= threshold as i64 {
+        return (area_end - length as i64) as i32;
+    }
+
+    start
+}
+
+/// Returns the snapped position when `bounds` sits within `threshold` pixels
+/// of an edge of the work area it overlaps most.
+fn snap_to_edges(bounds: Bounds, monitors: &[MonitorBounds], threshold: i32) -> Option<(i32, i32)> {
+    let area = monitors
+        .iter()
+        .map(|monitor| monitor.work_area)
+        .filter(|area| area.overlap_area(&bounds) > 0)
+        .max_by_key(|area| area.overlap_area(&bounds))?;
+
+    let x = snap_axis(bounds.x, bounds.width, area.x, area.width, threshold);
+    let y = snap_axis(bounds.y, bounds.height, area.y, area.height, threshold);
+    if x == bounds.x && y == bounds.y {
+        return None;
+    }
+
+    Some((x, y))
+}
+
+fn read_layouts(app: &AppHandle) -> Result<WindowLayouts> {
+    let store = crate::storage::store(app).context("open settings store")?;
+    let Some(value) = store.get(WINDOW_LAYOUTS_KEY) else {
+        return Ok(WindowLayouts::new());
+    };
+
+    Ok(serde_json::from_value(value).unwrap_or_default())
+}
+
+fn write_layouts(app: &AppHandle, layouts: &WindowLayouts) -> Result<()> {
+    let store = crate::storage::store(app).context("open settings store")?;
+    store.set(
+        WINDOW_LAYOUTS_KEY,
+        serde_json::to_value(layouts).context("serialize window layouts")?,
+    );
+    Ok(())
+}
+
+fn current_geometry(window: &WebviewWindow) -> Result<WindowGeometry> {
+    let position = window.outer_position().context("read window position")?;
+    let size = window.inner_size().context("read window size")?;
+    Ok(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        opacity: MAX_OPACITY,
+        collapsed: false,
+    })
+}
+
+fn update_geometry(
+    window: &WebviewWindow,
+    update: impl FnOnce(&mut WindowGeometry, &WindowGeometry),
+) -> Result<WindowGeometry> {
+    let monitors = monitor_bounds(window)?;
+    if monitors.is_empty() {
+        anyhow::bail!("No monitors available");
+    }
+
+    let app = window.app_handle();
+    let live = current_geometry(window)?;
+    let mut layouts = read_layouts(app)?;
+    let entry = layouts
+        .entry(layout_key(&monitors))
+        .or_default()
+        .entry(window.label().to_string())
+        .or_insert_with(|| live.clone());
+
+    update(entry, &live);
+    let next = entry.clone();
+    write_layouts(app, &layouts)?;
+    Ok(next)
+}
+
+fn record_live_geometry(window: &WebviewWindow) -> Result<()> {
+    update_geometry(window, |saved, live| {
+        saved.x = live.x;
+        saved.y = live.y;
+        saved.width = live.width;
+        // A collapsed window only shows its header; keep the expanded height so
+        // the next launch opens at full size.
+        if !saved.collapsed {
+            saved.height = live.height;
+        }
+    })?;
+    Ok(())
+}
+
+pub(crate) fn get_window_geometry(window: &WebviewWindow) -> Result<Option<WindowGeometry>> {
+    let monitors = monitor_bounds(window)?;
+    let layouts = read_layouts(window.app_handle())?;
+    Ok(layouts
+        .get(&layout_key(&monitors))
+        .and_then(|windows| windows.get(window.label()))
+        .cloned())
+}
+
+pub(crate) fn set_window_opacity(window: &WebviewWindow, opacity: f64) -> Result<WindowGeometry> {
+    let opacity = normalize_opacity(opacity);
+    let geometry = update_geometry(window, |saved, _live| saved.opacity = opacity)?;
+    let _ = window.emit_to(window.label(), WINDOW_OPACITY_EVENT, geometry.opacity);
+    Ok(geometry)
+}
+
+pub(crate) fn set_window_collapsed(
+    window: &WebviewWindow,
+    collapsed: bool,
+) -> Result<WindowGeometry> {
+    update_geometry(window, |saved, live| {
+        if !collapsed && saved.collapsed {
+            saved.height = live.height;
+        }
+        saved.collapsed = collapsed;
+    })
+}
+
+/// Applies the geometry saved for the current monitor layout, clamped so the
+/// window is fully visible even if a monitor was rearranged or resized. A
+/// collapsed window comes back collapsed; the UI reads the saved opacity and
+/// expanded height through `get_window_geometry` when it loads.
+pub(crate) fn restore_window_geometry(window: &WebviewWindow) -> Result<()> {
+    let monitors = monitor_bounds(window)?;
+    if monitors.is_empty() {
+        return Ok(());
+    }
+
+    let Some(saved) = get_window_geometry(window)? else {
+        return Ok(());
+    };
+
+    let mut wanted = saved.bounds();
+    if saved.collapsed {
+        let scale_factor = window.scale_factor().context("read window scale")?;
+        wanted.height = (COLLAPSED_HEIGHT * scale_factor).round() as u32;
+    }
+    let bounds = clamp_to_monitors(wanted, &monitors);
+    window
+        .set_size(PhysicalSize::new(bounds.width, bounds.height))
+        .context("restore window size")?;
+    window
+        .set_position(PhysicalPosition::new(bounds.x, bounds.y))
+        .context("restore window position")?;
+    Ok(())
+}
+
+fn snap_window_to_edges(window: &WebviewWindow, position: PhysicalPosition<i32>) -> Result<()> {
+    let size = window.outer_size().context("read window size")?;
+    let bounds = Bounds {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+
+    if let Some((x, y)) = snap_to_edges(bounds, &monitor_bounds(window)?, SNAP_THRESHOLD) {
+        window
+            .set_position(PhysicalPosition::new(x, y))
+            .context("snap window to edge")?;
+    }
+
+    Ok(())
+}
+
+/// Persists the window's position and size whenever it is moved or resized,
+/// snapping it to nearby screen edges along the way.
+pub(crate) fn track_window_geometry(window: &WebviewWindow) {
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        let result = match event {
+            // An anchored window is positioned relative to Roblox, so snapping
+            // to screen edges would fight the anchor.
+            WindowEvent::Moved(_) if crate::anchor::is_anchored(tracked.app_handle()) => {
+                record_live_geometry(&tracked)
+            }
+            WindowEvent::Moved(position) => snap_window_to_edges(&tracked, *position)
+                .and_then(|_| record_live_geometry(&tracked)),
+            WindowEvent::Resized(_) => record_live_geometry(&tracked),
+            _ => Ok(()),
+        };
+
+        if let Err(err) = result {
+            tracing::error!("Failed to save window geometry: {err:#}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(x: i32, y: i32, width: u32, height: u32) -> MonitorBounds {
+        let area = Bounds {
+            x,
+            y,
+            width,
+            height,
+        };
+        MonitorBounds {
+            area,
+            work_area: Bounds {
+                height: height - 40,
+                ..area
+            },
+            scale_factor: 1.0,
+        }
+    }
+
+    #[test]
+    fn layout_key_ignores_monitor_order() {
+        let left = monitor(0, 0, 1920, 1080);
+        let right = monitor(1920, 0, 2560, 1440);
+        assert_eq!(layout_key(&[left, right]), layout_key(&[right, left]));
+        assert_ne!(layout_key(&[left]), layout_key(&[left, right]));
+    }
+
+    #[test]
+    fn clamp_pulls_offscreen_window_onto_nearest_monitor() {
+        let monitors = [monitor(0, 0, 1920, 1080), monitor(1920, 0, 1920, 1080)];
+        let bounds = Bounds {
+            x: 5000,
+            y: 200,
+            width: 420,
+            height: 520,
+        };
+        assert_eq!(
+            clamp_to_monitors(bounds, &monitors),
+            Bounds {
+                x: 3420,
+                y: 200,
+                width: 420,
+                height: 520,
+            }
+        );
+    }
+
+    #[test]
+    fn clamp_keeps_window_inside_work_area() {
+        let monitors = [monitor(0, 0, 1920, 1080)];
+        let bounds = Bounds {
+            x: -100,
+            y: 900,
+            width: 420,
+            height: 2000,
+        };
+        assert_eq!(
+            clamp_to_monitors(bounds, &monitors),
+            Bounds {
+                x: 0,
+                y: 0,
+                width: 420,
+                height: 1040,
+            }
+        );
+    }
+
+    #[test]
+    fn snap_only_when_near_an_edge() {
+        let monitors = [monitor(0, 0, 1920, 1080)];
+        let near = Bounds {
+            x: 10,
+            y: 1040 - 520 - 12,
+            width: 420,
+            height: 520,
+        };
+        assert_eq!(snap_to_edges(near, &monitors, 16), Some((0, 520)));
+
+        let far = Bounds {
+            x: 200,
+            y: 200,
+            width: 420,
+            height: 520,
+        };
+        assert_eq!(snap_to_edges(far, &monitors, 16), None);
+    }
+
+    #[test]
+    fn opacity_is_clamped_like_gui_opacity() {
+        assert_eq!(normalize_opacity(0.05), MIN_OPACITY);
+        assert_eq!(normalize_opacity(3.0), MAX_OPACITY);
+        assert_eq!(normalize_opacity(f64::NAN), MAX_OPACITY);
+        assert_eq!(normalize_opacity(0.5), 0.5);
+    }
+}
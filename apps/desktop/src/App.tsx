@@ -2,6 +2,7 @@ import { useEffect, useState } from "react";
 import { HashRouter as Router, Routes, Route } from "react-router-dom";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import { MainLayout } from "./pages/Layout";
 import { MainChat } from "./pages/MainChat";
 import { SettingsPage } from "./pages/SettingsPage";
@@ -9,8 +10,17 @@ import { ChatProvider } from "./contexts/ChatContext";
 import { AuthProvider } from "./contexts/AuthContext";
 import { LoginPage } from "./pages/LoginPage";
 import { RequireAuth } from "./components/RequireAuth";
-import { getGuiOpacity, getLogsPath } from "./lib/store";
+import { getGuiOpacity } from "./lib/store";
+import { getWindowGeometry } from "./lib/windowState";
 import { DiscordRpcBridge } from "./components/DiscordRpcBridge";
+import { DeepLinkBridge } from "./components/DeepLinkBridge";
+
+type OverlayStatus = {
+  clickThrough: boolean;
+  robloxFocused: boolean;
+  fadeOpacity: number;
+  opacity: number;
+};
 
 type UpdateInstallingNotice = {
   version: string;
@@ -23,30 +33,59 @@ export default function App() {
   );
 
   useEffect(() => {
-    const applyGuiOpacity = async () => {
-      const opacity = await getGuiOpacity();
+    const appWindow = getCurrentWindow();
+    let guiOpacity = 1;
+    let windowOpacity = 1;
+    const applyOpacity = () => {
       document.documentElement.style.setProperty(
         "--gui-opacity",
-        opacity.toString(),
+        (guiOpacity * windowOpacity).toString(),
       );
     };
 
-    applyGuiOpacity().catch((error) => {
+    const applySavedOpacity = async () => {
+      const [opacity, geometry] = await Promise.all([
+        getGuiOpacity(),
+        getWindowGeometry(),
+      ]);
+      guiOpacity = opacity;
+      windowOpacity = geometry?.opacity ?? 1;
+      applyOpacity();
+    };
+
+    applySavedOpacity().catch((error) => {
       console.error("Failed to apply GUI opacity:", error);
     });
+
+    const unlisten = appWindow.listen<number>("window://opacity", (event) => {
+      windowOpacity = event.payload;
+      applyOpacity();
+    });
+
+    return () => {
+      unlisten.then((cleanup) => cleanup());
+    };
   }, []);
 
   useEffect(() => {
-    const syncLogsPath = async () => {
-      const logsPath = (await getLogsPath()).trim();
-      if (!logsPath) return;
-
-      await invoke("set_roblox_logs_path", { path: logsPath });
+    // The overlay fades the whole window while Roblox has focus.
+    const applyOverlayOpacity = (status: OverlayStatus) => {
+      document.documentElement.style.opacity = status.opacity.toString();
     };
 
-    syncLogsPath().catch((error) => {
-      console.error("Failed to sync Roblox logs path:", error);
+    invoke<OverlayStatus>("get_overlay_status")
+      .then(applyOverlayOpacity)
+      .catch((error) => {
+        console.error("Failed to read overlay status:", error);
+      });
+
+    const unlisten = listen<OverlayStatus>("overlay://changed", (event) => {
+      applyOverlayOpacity(event.payload);
     });
+
+    return () => {
+      unlisten.then((cleanup) => cleanup());
+    };
   }, []);
 
   useEffect(() => {
@@ -62,6 +101,17 @@ export default function App() {
     };
   }, []);
 
+  useEffect(() => {
+    // Another profile brings its own session and settings; start over.
+    const unlisten = listen("profile://switched", () => {
+      window.location.reload();
+    });
+
+    return () => {
+      unlisten.then((cleanup) => cleanup());
+    };
+  }, []);
+
   return (
     <AuthProvider>
       <ChatProvider>
@@ -75,6 +125,7 @@ export default function App() {
         )}
         <Router>
           <DiscordRpcBridge />
+          <DeepLinkBridge />
           <Routes>
             <Route path="/" element={<MainLayout />}>
               <Route path="auth" element={<LoginPage />} />
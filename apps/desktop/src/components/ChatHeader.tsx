@@ -1,4 +1,4 @@
-import { useRef, useState } from "react";
+import { useEffect, useRef, useState } from "react";
 import {
   PhysicalPosition,
   PhysicalSize,
@@ -11,6 +11,7 @@ import {
   getWindowCollapseDirection,
   type WindowCollapseDirection,
 } from "../lib/store";
+import { getWindowGeometry, setWindowCollapsed } from "../lib/windowState";
 import { Button } from "./ui/button";
 
 const MIN_COLLAPSED_HEIGHT = 32;
@@ -30,6 +31,31 @@ export const ChatHeader = () => {
   const [isCollapsed, setIsCollapsed] = useState(false);
   const [isTogglingCollapse, setIsTogglingCollapse] = useState(false);
 
+  useEffect(() => {
+    // The window is restored collapsed by the Rust side; pick up where it
+    // left off so expanding returns to the saved size.
+    const restoreCollapsed = async () => {
+      const geometry = await getWindowGeometry();
+      if (!geometry?.collapsed) return;
+
+      const [currentSize, collapseDirection] = await Promise.all([
+        getCurrentWindow().innerSize(),
+        getWindowCollapseDirection(),
+      ]);
+      expandedSizeRef.current = {
+        width: geometry.width,
+        height: geometry.height,
+      };
+      collapsedHeightRef.current = currentSize.height;
+      collapseDirectionRef.current = collapseDirection;
+      setIsCollapsed(true);
+    };
+
+    restoreCollapsed().catch((error) => {
+      console.error("Failed to restore collapsed mode:", error);
+    });
+  }, []);
+
   const toggleCollapsed = async () => {
     if (isTogglingCollapse) return;
 
@@ -77,12 +103,14 @@ export const ChatHeader = () => {
           );
         }
 
+        await setWindowCollapsed(true);
         setIsCollapsed(true);
         return;
       }
 
       const expandedSize = expandedSizeRef.current;
       if (!expandedSize) {
+        await setWindowCollapsed(false);
         setIsCollapsed(false);
         return;
       }
@@ -100,6 +128,7 @@ export const ChatHeader = () => {
       await appWindow.setPosition(
         new PhysicalPosition(currentPosition.x, nextY),
       );
+      await setWindowCollapsed(false);
       setIsCollapsed(false);
     } catch (error) {
       console.error("Failed to toggle collapsed mode:", error);
@@ -0,0 +1,6 @@
+import { useDeepLinks } from "../hooks/useDeepLinks";
+
+export const DeepLinkBridge = () => {
+  useDeepLinks();
+  return null;
+};
@@ -1,5 +1,6 @@
 import React from "react";
 import { replaceEmojiShortcodes } from "../lib/emoji";
+import { openMessageLink } from "../lib/links";
 
 interface FormattedTextProps {
   content: string;
@@ -175,6 +176,13 @@ export const FormattedText = ({
           target="_blank"
           rel="noopener noreferrer"
           className={toneClasses.link}
+          onClick={(event) => {
+            // Links open through the safety check, never straight from here.
+            event.preventDefault();
+            openMessageLink(matchedText).catch((err) => {
+              console.error("Failed to open link:", err);
+            });
+          }}
         >
           {matchedText}
         </a>,
@@ -0,0 +1,103 @@
+import { useEffect, useState } from "react";
+import { Button } from "./ui/button";
+import {
+  getHotkeys,
+  onHotkeysChanged,
+  setHotkeys,
+  type HotkeyBindings,
+} from "../lib/hotkeys";
+
+const splitKeys = (value: string) =>
+  value
+    .split(",")
+    .map((key) => key.trim())
+    .filter(Boolean);
+
+export const HotkeySettings = () => {
+  const [overlay, setOverlay] = useState("");
+  const [chat, setChat] = useState("");
+  const [saved, setSaved] = useState({ overlay: "", chat: "" });
+  const [isBusy, setIsBusy] = useState(true);
+  const [error, setError] = useState("");
+
+  useEffect(() => {
+    const show = (bindings: HotkeyBindings) => {
+      const next = {
+        overlay: bindings.overlay,
+        chat: bindings.chat.join(", "),
+      };
+      setOverlay(next.overlay);
+      setChat(next.chat);
+      setSaved(next);
+    };
+
+    getHotkeys()
+      .then(show)
+      .catch((err) => setError(String(err)))
+      .finally(() => setIsBusy(false));
+    // Imports can change the bindings while this page is open.
+    const unlisten = onHotkeysChanged(show);
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  const hasChanges = overlay !== saved.overlay || chat !== saved.chat;
+
+  const save = async () => {
+    setIsBusy(true);
+    setError("");
+    try {
+      await setHotkeys({ overlay, chat: splitKeys(chat) });
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setIsBusy(false);
+    }
+  };
+
+  return (
+    <div className="rounded-lg border border-border bg-card p-4 space-y-3">
+      <div className="space-y-1">
+        <h2 className="text-sm font-semibold">Hotkeys</h2>
+        <p className="text-xs text-muted-foreground">
+          Global shortcuts, written like Ctrl+Shift+O or a single key like
+          Slash.
+        </p>
+      </div>
+      <div className="space-y-2">
+        <label htmlFor="overlay-hotkey" className="text-sm font-medium">
+          Toggle Click-Through
+        </label>
+        <input
+          id="overlay-hotkey"
+          className="w-full rounded-md border border-border bg-background px-3 py-2 text-sm"
+          value={overlay}
+          onChange={(event) => setOverlay(event.target.value)}
+          disabled={isBusy}
+          placeholder="Ctrl+Shift+O"
+        />
+      </div>
+      <div className="space-y-2">
+        <label htmlFor="chat-hotkeys" className="text-sm font-medium">
+          Open Chat
+        </label>
+        <input
+          id="chat-hotkeys"
+          className="w-full rounded-md border border-border bg-background px-3 py-2 text-sm"
+          value={chat}
+          onChange={(event) => setChat(event.target.value)}
+          disabled={isBusy}
+          placeholder="Slash, NumpadDivide"
+        />
+        <p className="text-xs text-muted-foreground">
+          Separate keys with commas.
+        </p>
+      </div>
+      {error ? <p className="text-xs text-red-500 break-all">{error}</p> : null}
+      <Button onClick={save} disabled={isBusy || !hasChanges}>
+        Save Hotkeys
+      </Button>
+    </div>
+  );
+};
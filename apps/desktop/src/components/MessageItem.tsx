@@ -1,9 +1,13 @@
-import type { UiChatMessage } from "../contexts/ChatContext";
+import type {
+  CollapseReason,
+  UiChatMessage,
+} from "../contexts/ChatContext";
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { FormattedText } from "./FormattedText";
 import { getImageLoadingEnabled } from "../lib/store";
 import { isMentioned } from "../lib/mentions";
+import { openMessageLink } from "../lib/links";
 import { Button } from "./ui/button";
 import { Reply, Star } from "lucide-react";
 import { useAuth } from "../contexts/AuthContext";
@@ -16,6 +20,11 @@ const timeFormatter = new Intl.DateTimeFormat(undefined, {
 
 const formatTimestamp = (timestamp: number) => timeFormatter.format(timestamp);
 
+const collapseLabel = (collapsed: CollapseReason) =>
+  collapsed.reason === "flood"
+    ? `Repeated ${collapsed.repeatCount} times`
+    : `Matched filter "${collapsed.pattern}"`;
+
 type MediaProbeResult = {
   displayable: boolean;
   kind: "image" | "video" | "none";
@@ -75,7 +84,9 @@ export const MessageItem = ({
   isHighlighted = false,
 }: MessageItemProps) => {
   const [mediaUrls, setMediaUrls] = useState<DetectedMedia[]>([]);
+  const [revealed, setRevealed] = useState(false);
   const { user } = useAuth();
+  const isFolded = Boolean(message.collapsed) && !revealed;
   const isSending = message.localStatus === "sending";
   const isFailed = message.localStatus === "failed";
   const canReply = !isSending && !isFailed && !message.id.startsWith("local-");
@@ -139,6 +150,7 @@ export const MessageItem = ({
   }, [message.content]);
 
   const mediaSourceUrls = mediaUrls.map((media) => media.sourceUrl);
+  const visibleMedia = isFolded ? [] : mediaUrls;
 
   const isMentionedMessage =
     isMentioned(message, user) || replyPreview?.author === user?.displayName;
@@ -254,17 +266,37 @@ export const MessageItem = ({
             </div>
           )}
 
-          <div
-            className={`wrap-break-word text-sm leading-relaxed text-foreground/95 chat-readable-text ${isContinuation || "mt-1"}`}
-          >
-            <FormattedText
-              content={message.content}
-              imageUrls={mediaSourceUrls}
-              tone={isFailed ? "error" : "default"}
-            />
-          </div>
+          {isFolded ? (
+            <button
+              type="button"
+              className={`w-fit text-left text-xs italic text-muted-foreground transition-colors hover:text-foreground cursor-pointer ${isContinuation || "mt-1"}`}
+              onClick={() => setRevealed(true)}
+              title="Show message"
+            >
+              {collapseLabel(message.collapsed!)} · Show
+            </button>
+          ) : (
+            <div
+              className={`wrap-break-word text-sm leading-relaxed text-foreground/95 chat-readable-text ${isContinuation || "mt-1"}`}
+            >
+              <FormattedText
+                content={message.content}
+                imageUrls={mediaSourceUrls}
+                tone={isFailed ? "error" : "default"}
+              />
+              {message.collapsed && (
+                <button
+                  type="button"
+                  className="ml-2 text-xs text-muted-foreground transition-colors hover:text-foreground cursor-pointer"
+                  onClick={() => setRevealed(false)}
+                >
+                  Hide
+                </button>
+              )}
+            </div>
+          )}
 
-          {mediaUrls.map((media) => (
+          {visibleMedia.map((media) => (
             <div
               key={media.sourceUrl}
               className="mt-2 relative group/media w-fit max-w-full"
@@ -274,6 +306,12 @@ export const MessageItem = ({
                 target="_blank"
                 rel="noopener noreferrer"
                 className="block max-w-full"
+                onClick={(event) => {
+                  event.preventDefault();
+                  openMessageLink(media.sourceUrl).catch((err) => {
+                    console.error("Failed to open link:", err);
+                  });
+                }}
               >
                 {media.kind === "video" ? (
                   <video
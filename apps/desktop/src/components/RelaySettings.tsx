@@ -0,0 +1,116 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Button } from "./ui/button";
+
+// Mirrors `RelayInfo` and `DEFAULT_RELAY_PORT` in `relay.rs`.
+type RelayInfo = {
+  port: number;
+  joinAddresses: string[];
+  localAddress: string;
+};
+
+const DEFAULT_RELAY_PORT = "4080";
+
+export const RelaySettings = () => {
+  const [relay, setRelay] = useState<RelayInfo | null>(null);
+  const [port, setPort] = useState(DEFAULT_RELAY_PORT);
+  const [isBusy, setIsBusy] = useState(true);
+  const [error, setError] = useState("");
+
+  useEffect(() => {
+    invoke<RelayInfo | null>("get_relay_info")
+      .then((info) => {
+        setRelay(info);
+        if (info) setPort(info.port.toString());
+      })
+      .catch((err) => setError(String(err)))
+      .finally(() => setIsBusy(false));
+  }, []);
+
+  const start = async () => {
+    const parsed = Number(port.trim());
+    if (!Number.isInteger(parsed) || parsed < 0 || parsed > 65535) {
+      setError("Port must be a number between 0 and 65535.");
+      return;
+    }
+
+    setIsBusy(true);
+    setError("");
+    try {
+      setRelay(await invoke<RelayInfo>("start_relay", { port: parsed }));
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setIsBusy(false);
+    }
+  };
+
+  const stop = async () => {
+    setIsBusy(true);
+    setError("");
+    try {
+      await invoke("stop_relay");
+      setRelay(null);
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setIsBusy(false);
+    }
+  };
+
+  return (
+    <div className="rounded-lg border border-border bg-card p-4 space-y-3">
+      <div className="space-y-1">
+        <h2 className="text-sm font-semibold">Local Relay</h2>
+        <p className="text-xs text-muted-foreground">
+          Host chat for other BloxChat clients on your network.
+        </p>
+      </div>
+      <div className="space-y-2">
+        <label htmlFor="relay-port" className="text-sm font-medium">
+          Port
+        </label>
+        <input
+          id="relay-port"
+          className="w-full rounded-md border border-border bg-background px-3 py-2 text-sm"
+          value={port}
+          onChange={(event) => setPort(event.target.value)}
+          disabled={isBusy || relay !== null}
+          inputMode="numeric"
+          placeholder={DEFAULT_RELAY_PORT}
+        />
+      </div>
+      {relay ? (
+        <div className="space-y-1">
+          <label className="text-sm font-medium">Join Addresses</label>
+          {relay.joinAddresses.length > 0 ? (
+            relay.joinAddresses.map((address) => (
+              <p
+                key={address}
+                className="text-xs text-muted-foreground break-all select-text"
+              >
+                {address}
+              </p>
+            ))
+          ) : (
+            <p className="text-xs text-muted-foreground">
+              No network found. Only this machine can join at{" "}
+              {relay.localAddress}.
+            </p>
+          )}
+          <p className="text-xs text-muted-foreground">
+            Other clients set one of these as their API URL.
+          </p>
+        </div>
+      ) : null}
+      {error ? <p className="text-xs text-red-500 break-all">{error}</p> : null}
+      <Button
+        onClick={relay ? stop : start}
+        variant={relay ? "secondary" : "default"}
+        disabled={isBusy}
+      >
+        {relay ? "Stop Relay" : "Start Relay"}
+      </Button>
+    </div>
+  );
+};
@@ -6,6 +6,7 @@ import {
   useState,
   ReactNode,
 } from "react";
+import { listen } from "@tauri-apps/api/event";
 import { trpc, trpcClient } from "../lib/trpc";
 import { AuthSession, getAuthSession, setAuthSession } from "../lib/store";
 
@@ -24,6 +25,7 @@ interface AuthContextValue {
   verificationPlaceId: string | null;
   login: () => Promise<void>;
   logout: () => Promise<void>;
+  completeVerification: (sessionId: string) => Promise<void>;
 }
 
 const AuthContext = createContext<AuthContextValue | undefined>(undefined);
@@ -31,10 +33,8 @@ const AuthContext = createContext<AuthContextValue | undefined>(undefined);
 export const AuthProvider = ({ children }: { children: ReactNode }) => {
   const [user, setUser] = useState<AuthContextValue["user"]>(null);
   const [loading, setLoading] = useState(true);
-  const [authReady, setAuthReady] = useState(false);
   const [verificationSession, setVerificationSession] =
     useState<VerificationSession | null>(null);
-  const isRefreshingRef = useRef(false);
   const isCheckingVerificationRef = useRef(false);
 
   const applyAuthState = async (data: {
@@ -58,47 +58,38 @@ export const AuthProvider = ({ children }: { children: ReactNode }) => {
     await setAuthSession(null);
   };
 
-  const refreshMutation = trpc.auth.refresh.useMutation();
   const beginVerificationMutation = trpc.auth.beginVerification.useMutation();
 
-  const refreshSession = async () => {
-    if (isRefreshingRef.current) return false;
-
-    const saved = await getAuthSession();
-    if (!saved?.jwt) {
-      await clearAuthState();
-      return false;
-    }
-
-    try {
-      isRefreshingRef.current = true;
-      const data = await refreshMutation.mutateAsync({ jwt: saved.jwt });
-      await applyAuthState(data);
-      return true;
-    } catch (err) {
-      const isUnauthorized = (err as any)?.data?.code === "UNAUTHORIZED";
-      if (isUnauthorized) {
-        await clearAuthState();
-      }
-      return false;
-    } finally {
-      isRefreshingRef.current = false;
-    }
-  };
-
   useEffect(() => {
     const initAuth = async () => {
       const saved = await getAuthSession();
       if (saved?.jwt) {
         setUser(saved.user);
-        await refreshSession();
       }
       setLoading(false);
-      setAuthReady(true);
     };
     initAuth();
   }, []);
 
+  useEffect(() => {
+    // The Rust side refreshes the session before it expires and clears it
+    // once the server stops accepting it.
+    const unlistenRefreshed = listen<AuthSession>(
+      "auth://refreshed",
+      (event) => {
+        setUser(event.payload.user);
+      },
+    );
+    const unlistenExpired = listen("auth://expired", () => {
+      setUser(null);
+    });
+
+    return () => {
+      unlistenRefreshed.then((cleanup) => cleanup());
+      unlistenExpired.then((cleanup) => cleanup());
+    };
+  }, []);
+
   useEffect(() => {
     if (!verificationSession || user) return;
 
@@ -161,19 +152,6 @@ export const AuthProvider = ({ children }: { children: ReactNode }) => {
     verificationSession?.sessionId,
   ]);
 
-  useEffect(() => {
-    if (!authReady || !user) return;
-
-    const interval = setInterval(
-      () => {
-        void refreshSession();
-      },
-      45 * 60 * 1000,
-    );
-
-    return () => clearInterval(interval);
-  }, [authReady, user?.robloxUserId]);
-
   const login = async () => {
     setLoading(true);
     try {
@@ -187,6 +165,28 @@ export const AuthProvider = ({ children }: { children: ReactNode }) => {
     }
   };
 
+  // Opened from a `bloxchat://verify` link: check now rather than waiting
+  // for the next poll.
+  const completeVerification = async (sessionId: string) => {
+    if (isCheckingVerificationRef.current) return;
+
+    try {
+      isCheckingVerificationRef.current = true;
+      const result = await trpcClient.auth.checkVerification.mutate({
+        sessionId,
+      });
+      if (result.status === "pending") return;
+      if (result.status === "verified") {
+        await applyAuthState({ jwt: result.jwt, user: result.user });
+      }
+      setVerificationSession((current) =>
+        current?.sessionId === sessionId ? null : current,
+      );
+    } finally {
+      isCheckingVerificationRef.current = false;
+    }
+  };
+
   const logout = async () => {
     setVerificationSession(null);
     await clearAuthState();
@@ -198,6 +198,7 @@ export const AuthProvider = ({ children }: { children: ReactNode }) => {
         user,
         login,
         logout,
+        completeVerification,
         loading,
         verificationCode: verificationSession?.code ?? null,
         verificationExpiresAt: verificationSession?.expiresAt ?? null,
@@ -6,16 +6,25 @@ import {
   useEffect,
   useRef,
 } from "react";
-import { trpc } from "../lib/trpc";
 import type { ChatLimits, ChatMessage } from "@bloxchat/api";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import { useAuth } from "./AuthContext";
 import { getJoinMessage } from "../lib/store";
+import { reviewOutgoingMessage } from "../lib/messageGuard";
+
+// Mirrors `CollapseReason` in `moderation.rs`.
+export type CollapseReason =
+  | { reason: "filter"; pattern: string }
+  | { reason: "flood"; repeatCount: number };
 
 export type UiChatMessage = ChatMessage & {
   clientId: string;
   clientTimestamp: number;
   localStatus?: "sending" | "failed";
+  // Set when moderation wants the message shown folded.
+  collapsed?: CollapseReason;
 };
 
 const FALLBACK_CHAT_LIMITS: ChatLimits = {
@@ -26,6 +35,34 @@ const FALLBACK_CHAT_LIMITS: ChatLimits = {
 
 const DEFAULT_JOIN_MESSAGE = "joined the channel";
 
+// Mirrors `ChannelBinding` in `popout.rs`.
+type ChannelBinding =
+  | { kind: "global" }
+  | { kind: "pinnedServer"; jobId: string }
+  | { kind: "friendServer"; userId: string; jobId: string };
+
+const bindingChannel = (binding: ChannelBinding) =>
+  binding.kind === "global" ? "global" : binding.jobId;
+
+const channelBinding = (channel: string): ChannelBinding =>
+  channel === "global"
+    ? { kind: "global" }
+    : { kind: "pinnedServer", jobId: channel };
+
+// A moderated batch from the Rust chat client (`chat://messages`).
+type ChatMessageBatch = {
+  channel: string;
+  messages: ChatMessage[];
+  collapsed?: Record<string, CollapseReason>;
+  hiddenCount: number;
+};
+
+// Commands reject with the error text as a string.
+const errorMessage = (err: unknown, fallback: string) => {
+  if (typeof err === "string") return err;
+  return err instanceof Error ? err.message : fallback;
+};
+
 const parseRetryAfterMs = (message: string) => {
   const matchedSeconds = message.match(/try again in\s+(\d+)s/i);
   if (!matchedSeconds) return 1000;
@@ -42,8 +79,14 @@ type ChatContextType = {
   messages: UiChatMessage[];
   chatLimits: ChatLimits;
   sendError: string | null;
-  sendMessage: (text: string, replyToId?: string | null) => boolean;
+  sendMessage: (text: string, replyToId?: string | null) => Promise<boolean>;
   clearMessages: () => void;
+  followRobloxServer: () => Promise<void>;
+  joinChannel: (channel: string) => Promise<void>;
+  // A message a `bloxchat://message` link asked to show, until it arrives.
+  pendingMessageId: string | null;
+  openMessage: (channel: string, messageId: string) => Promise<void>;
+  clearPendingMessage: () => void;
 };
 
 const ChatContext = createContext<ChatContextType | undefined>(undefined);
@@ -52,16 +95,28 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
   const [currentJobId, setCurrentJobId] = useState("global");
   const [messages, setMessages] = useState<UiChatMessage[]>([]);
   const [sendError, setSendError] = useState<string | null>(null);
+  const [pendingMessageId, setPendingMessageId] = useState<string | null>(
+    null,
+  );
   const sentTimestampsByScopeRef = useRef<Map<string, number[]>>(new Map());
   const { user } = useAuth();
   const currentUserIdRef = useRef<string | null>(null);
   const latestJobIdRef = useRef<string | null>(null);
+  // Set in pop-out windows, and in the main window once `--channel` or
+  // `bloxchat channel` switches it, which then stay on their channel instead
+  // of following the Roblox server.
+  const boundChannelRef = useRef<string | null>(null);
   const autoJoinRetryTimeoutsRef = useRef<Map<string, number>>(new Map());
   currentUserIdRef.current = user?.robloxUserId ?? null;
 
-  const publish = trpc.chat.publish.useMutation();
-  const limitsQuery = trpc.chat.limits.useQuery({ channel: currentJobId });
-  const chatLimits = limitsQuery.data ?? FALLBACK_CHAT_LIMITS;
+  const [chatLimits, setChatLimits] = useState(FALLBACK_CHAT_LIMITS);
+
+  const publish = (input: {
+    channel: string;
+    content: string;
+    replyToId?: string | null;
+    acknowledged?: string[];
+  }) => invoke<ChatMessage>("chat_publish", input);
 
   const clearAutoJoinRetry = (channel: string) => {
     const timeout = autoJoinRetryTimeoutsRef.current.get(channel);
@@ -75,11 +130,10 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
 
     const timeout = window.setTimeout(async () => {
       try {
-        await publish.mutateAsync({ channel, content });
+        await publish({ channel, content });
         autoJoinRetryTimeoutsRef.current.delete(channel);
       } catch (err) {
-        const message =
-          err instanceof Error ? err.message : "Failed to send join message.";
+        const message = errorMessage(err, "Failed to send join message.");
 
         if (/rate.?limit/i.test(message)) {
           queueAutoJoinSend(channel, content, parseRetryAfterMs(message));
@@ -116,6 +170,8 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
   };
 
   const syncJobId = async () => {
+    if (boundChannelRef.current) return boundChannelRef.current;
+
     const nextJobId = await invoke<string>("get_job_id");
     applyObservedJobId(nextJobId);
     return nextJobId;
@@ -126,63 +182,99 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
     setSendError(null);
   }, [currentJobId]);
 
-  trpc.chat.subscribe.useSubscription(
-    { channel: currentJobId },
-    {
-      onData(message: ChatMessage) {
-        const receivedAt = Date.now();
-        setMessages((prev) => {
-          if (prev.some((existing) => existing.id === message.id)) {
-            return prev;
-          }
-
-          const currentUserId = currentUserIdRef.current;
-          if (!currentUserId || message.author.robloxUserId !== currentUserId) {
-            return [
-              ...prev,
-              {
-                ...message,
-                clientId: message.id,
-                clientTimestamp: receivedAt,
-              },
-            ];
-          }
-
-          const matchIndex = prev.findIndex(
-            (item) =>
-              item.id.startsWith("local-") &&
-              item.localStatus !== "failed" &&
-              item.author.robloxUserId === currentUserId &&
-              item.content.trim() === message.content.trim() &&
-              (item.replyToId ?? null) === (message.replyToId ?? null),
-          );
-
-          if (matchIndex === -1) {
-            return [
-              ...prev,
-              {
-                ...message,
-                clientId: message.id,
-                clientTimestamp: receivedAt,
-              },
-            ];
-          }
-
-          const next = [...prev];
-          next[matchIndex] = {
+  const receiveMessage = (
+    message: ChatMessage,
+    collapsed?: CollapseReason,
+  ) => {
+    const receivedAt = Date.now();
+    setMessages((prev) => {
+      if (prev.some((existing) => existing.id === message.id)) {
+        return prev;
+      }
+
+      const currentUserId = currentUserIdRef.current;
+      if (!currentUserId || message.author.robloxUserId !== currentUserId) {
+        return [
+          ...prev,
+          {
             ...message,
-            clientId: next[matchIndex].clientId,
-            clientTimestamp:
-              next[matchIndex].clientTimestamp ?? receivedAt,
-          };
-          return next;
-        });
-      },
-      onError(err) {
-        console.error("Subscription error:", err);
-      },
-    },
-  );
+            clientId: message.id,
+            clientTimestamp: receivedAt,
+            collapsed,
+          },
+        ];
+      }
+
+      const matchIndex = prev.findIndex(
+        (item) =>
+          item.id.startsWith("local-") &&
+          item.localStatus !== "failed" &&
+          item.author.robloxUserId === currentUserId &&
+          item.content.trim() === message.content.trim() &&
+          (item.replyToId ?? null) === (message.replyToId ?? null),
+      );
+
+      if (matchIndex === -1) {
+        return [
+          ...prev,
+          {
+            ...message,
+            clientId: message.id,
+            clientTimestamp: receivedAt,
+            collapsed,
+          },
+        ];
+      }
+
+      const next = [...prev];
+      next[matchIndex] = {
+        ...message,
+        clientId: next[matchIndex].clientId,
+        clientTimestamp: next[matchIndex].clientTimestamp ?? receivedAt,
+        collapsed,
+      };
+      return next;
+    });
+  };
+
+  useEffect(() => {
+    const channel = currentJobId;
+    const unlisten = listen<ChatMessageBatch>("chat://messages", (event) => {
+      if (event.payload.channel !== channel) return;
+      const collapsed = event.payload.collapsed ?? {};
+      for (const message of event.payload.messages) {
+        receiveMessage(message, collapsed[message.id]);
+      }
+    });
+
+    invoke("chat_subscribe", { channel }).catch((err) => {
+      console.error("Subscription error:", err);
+    });
+
+    return () => {
+      unlisten.then((cleanup) => cleanup());
+      invoke("chat_unsubscribe", { channel }).catch((err) => {
+        console.error("Failed to unsubscribe:", err);
+      });
+    };
+  }, [currentJobId]);
+
+  useEffect(() => {
+    let cancelled = false;
+    setChatLimits(FALLBACK_CHAT_LIMITS);
+
+    invoke<ChatLimits>("chat_limits", { channel: currentJobId })
+      .then((limits) => {
+        if (!cancelled) setChatLimits(limits);
+      })
+      .catch((err) => {
+        console.error("Failed to load chat limits:", err);
+      });
+
+    return () => {
+      cancelled = true;
+    };
+  }, [currentJobId]);
 
   const refreshCurrentJobId = async () => {
     return syncJobId();
@@ -190,23 +282,64 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
 
   useEffect(() => {
     let cancelled = false;
+    let interval: number | undefined;
 
     const sync = async () => {
+      if (boundChannelRef.current) return;
       try {
         const nextJobId = await invoke<string>("get_job_id");
-        if (cancelled) return;
+        if (cancelled || boundChannelRef.current) return;
         applyObservedJobId(nextJobId);
       } catch (err) {
         console.error("Failed to sync job id:", err);
       }
     };
 
-    sync();
-    const interval = window.setInterval(sync, 1000);
+    const bind = (binding: ChannelBinding | null) => {
+      const channel = binding ? bindingChannel(binding) : null;
+      boundChannelRef.current = channel;
+      if (channel) {
+        setCurrentJobId(channel);
+      } else {
+        void sync();
+      }
+    };
+
+    // Only the main window is switched after it opens.
+    const unlistenChannel = getCurrentWindow().listen<ChannelBinding | null>(
+      "window://channel",
+      (event) => bind(event.payload),
+    );
+
+    const start = async () => {
+      try {
+        const binding = await invoke<ChannelBinding | null>(
+          "get_window_channel",
+        );
+        if (cancelled) return;
+        if (binding) bind(binding);
+      } catch (err) {
+        console.error("Failed to read window channel:", err);
+      }
+
+      if (cancelled) return;
+      void sync();
+      interval = window.setInterval(sync, 1000);
+
+      // `--replay` waits for the chat to listen before it starts.
+      if (getCurrentWindow().label === "main") {
+        invoke("start_queued_replay").catch((err) => {
+          console.error("Failed to start session replay:", err);
+        });
+      }
+    };
+
+    void start();
 
     return () => {
       cancelled = true;
       window.clearInterval(interval);
+      unlistenChannel.then((cleanup) => cleanup());
       for (const timeout of autoJoinRetryTimeoutsRef.current.values()) {
         window.clearTimeout(timeout);
       }
@@ -214,7 +347,7 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
     };
   }, []);
 
-  const sendMessage = (text: string, replyToId?: string | null) => {
+  const sendMessage = async (text: string, replyToId?: string | null) => {
     const content = text.trim();
     if (!content) return false;
 
@@ -234,6 +367,19 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
       return false;
     }
 
+    // `chat_publish` checks again; this is where the user gets to confirm.
+    let acknowledged: string[] = [];
+    try {
+      const review = await reviewOutgoingMessage(content);
+      if (!review.send) {
+        setSendError(review.error);
+        return false;
+      }
+      acknowledged = review.acknowledged;
+    } catch (err) {
+      console.error("Failed to check message:", err);
+    }
+
     const now = Date.now();
     const cutoff = now - chatLimits.rateLimitWindowMs;
     const scopeKey = `${author.robloxUserId}`;
@@ -273,10 +419,11 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
     void (async () => {
       try {
         const activeJobId = await refreshCurrentJobId();
-        await publish.mutateAsync({
+        await publish({
           channel: activeJobId,
           content,
           replyToId: normalizedReplyToId,
+          acknowledged,
         });
 
         setMessages((prev) =>
@@ -288,9 +435,7 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
         );
       } catch (err) {
         console.error("Failed to send message:", err);
-        setSendError(
-          err instanceof Error ? err.message : "Failed to send message.",
-        );
+        setSendError(errorMessage(err, "Failed to send message."));
 
         sentTimestampsByScopeRef.current.set(
           scopeKey,
@@ -312,11 +457,46 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
     return true;
   };
 
+  const sendMessageRef = useRef(sendMessage);
+  sendMessageRef.current = sendMessage;
+
+  useEffect(() => {
+    // `bloxchat send` from the command line, forwarded to the main window.
+    const unlisten = getCurrentWindow().listen<{ content: string }>(
+      "remote://send-message",
+      (event) => {
+        void sendMessageRef.current(event.payload.content);
+      },
+    );
+
+    return () => {
+      unlisten.then((cleanup) => cleanup());
+    };
+  }, []);
+
   const clearMessages = () => {
     setMessages([]);
     setSendError(null);
   };
 
+  // Pop-out windows keep their channel; only the main window switches.
+  const setMainChannel = async (binding: ChannelBinding | null) => {
+    if (getCurrentWindow().label !== "main") return;
+    await invoke("set_main_channel", { binding });
+  };
+
+  const followRobloxServer = () => setMainChannel(null);
+
+  const joinChannel = (channel: string) =>
+    setMainChannel(channelBinding(channel));
+
+  const openMessage = async (channel: string, messageId: string) => {
+    await joinChannel(channel);
+    setPendingMessageId(messageId);
+  };
+
+  const clearPendingMessage = () => setPendingMessageId(null);
+
   return (
     <ChatContext.Provider
       value={{
@@ -328,6 +508,11 @@ export const ChatProvider = ({ children }: { children: ReactNode }) => {
         sendError,
         sendMessage,
         clearMessages,
+        followRobloxServer,
+        joinChannel,
+        pendingMessageId,
+        openMessage,
+        clearPendingMessage,
       }}
     >
       {children}
@@ -0,0 +1,89 @@
+import { useEffect, useRef } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { useNavigate } from "react-router-dom";
+import { useAuth } from "../contexts/AuthContext";
+import { useChat } from "../contexts/ChatContext";
+
+// Mirrors `DeepLink` in `deep_link.rs`.
+type DeepLink =
+  | { action: "joinChannel"; channel: string }
+  | { action: "openSettings" }
+  | { action: "completeVerification"; sessionId: string }
+  | { action: "openMessage"; channel: string; messageId: string };
+
+const DEEP_LINK_EVENTS = [
+  "deep-link://join-channel",
+  "deep-link://open-settings",
+  "deep-link://complete-verification",
+  "deep-link://open-message",
+] as const;
+
+type InvalidDeepLink = {
+  url: string;
+  error: string;
+};
+
+export function useDeepLinks() {
+  const navigate = useNavigate();
+  const { completeVerification } = useAuth();
+  const { joinChannel, openMessage } = useChat();
+
+  // Listeners are registered once; keep them pointed at the latest handlers.
+  const handleRef = useRef<(link: DeepLink) => Promise<void>>(async () => {});
+  handleRef.current = async (link: DeepLink) => {
+    switch (link.action) {
+      case "joinChannel":
+        navigate("/");
+        await joinChannel(link.channel);
+        break;
+      case "openSettings":
+        navigate("/settings");
+        break;
+      case "completeVerification":
+        navigate("/auth");
+        await completeVerification(link.sessionId);
+        break;
+      case "openMessage":
+        navigate("/");
+        await openMessage(link.channel, link.messageId);
+        break;
+    }
+  };
+
+  useEffect(() => {
+    // Links are meant for the main chat window, not pop-outs.
+    if (getCurrentWindow().label !== "main") return;
+
+    const handle = (link: DeepLink) => {
+      handleRef.current(link).catch((err) => {
+        console.error(`Failed to open ${link.action} link:`, err);
+      });
+    };
+
+    const unlisteners = DEEP_LINK_EVENTS.map((name) =>
+      listen<DeepLink>(name, (event) => handle(event.payload)),
+    );
+    unlisteners.push(
+      listen<InvalidDeepLink>("deep-link://invalid", (event) => {
+        console.warn(
+          `Ignoring invalid link ${event.payload.url}: ${event.payload.error}`,
+        );
+      }),
+    );
+
+    // Links that launched the app arrived before anything was listening.
+    invoke<DeepLink[]>("take_pending_deep_links")
+      .then((links) => links.forEach(handle))
+      .catch((err) => {
+        console.error("Failed to read pending links:", err);
+      });
+
+    return () => {
+      for (const unlisten of unlisteners) {
+        unlisten.then((cleanup) => cleanup());
+      }
+    };
+  }, []);
+}
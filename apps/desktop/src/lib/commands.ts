@@ -1,4 +1,4 @@
-export type ChatCommandId = "clear";
+export type ChatCommandId = "clear" | "follow";
 
 export type ChatCommand = {
   id: ChatCommandId;
@@ -12,6 +12,11 @@ export const CHAT_COMMANDS: readonly ChatCommand[] = [
     command: "/clear",
     description: "Clear local chat history",
   },
+  {
+    id: "follow",
+    command: "/follow",
+    description: "Follow your Roblox server again after switching channels",
+  },
 ];
 
 const COMMANDS_BY_NAME = new Map(
@@ -51,6 +56,7 @@ export const findCommandSuggestions = (
 
 export type ChatCommandHandlers = {
   clearMessages: () => void;
+  followRobloxServer: () => void;
 };
 
 export const executeChatCommand = (
@@ -64,6 +70,9 @@ export const executeChatCommand = (
     case "clear":
       handlers.clearMessages();
       return true;
+    case "follow":
+      handlers.followRobloxServer();
+      return true;
     default:
       return false;
   }
@@ -0,0 +1,20 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+// Mirrors `HotkeyBindings` in `hotkeys.rs`.
+export type HotkeyBindings = {
+  overlay: string;
+  chat: string[];
+};
+
+export const getHotkeys = () => invoke<HotkeyBindings>("get_hotkeys");
+
+export const setHotkeys = (bindings: HotkeyBindings) =>
+  invoke<HotkeyBindings>("set_hotkeys", { bindings });
+
+export const onHotkeysChanged = (
+  handler: (bindings: HotkeyBindings) => void,
+) =>
+  listen<HotkeyBindings>("hotkeys://changed", (event) =>
+    handler(event.payload),
+  );
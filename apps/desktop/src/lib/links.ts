@@ -0,0 +1,63 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// Mirrors `LinkVerdict` and `LinkReason` in `link_safety.rs`.
+type LinkReason =
+  | { kind: "blocklisted"; domain: string }
+  | { kind: "homograph"; imitates: string }
+  | { kind: "lookalike"; imitates: string }
+  | { kind: "robloxSubdomain"; domain: string }
+  | { kind: "punycode" }
+  | { kind: "robloxKeyword" }
+  | { kind: "notHttp" };
+
+type LinkVerdict = {
+  link: string;
+  url: string;
+  host: string;
+  risk: "safe" | "suspicious" | "dangerous";
+  reasons: LinkReason[];
+};
+
+const describeReason = (reason: LinkReason) => {
+  switch (reason.kind) {
+    case "blocklisted":
+      return `${reason.domain} is a known scam domain.`;
+    case "homograph":
+      return `It uses look-alike characters to imitate ${reason.imitates}.`;
+    case "lookalike":
+      return `It looks like ${reason.imitates} but isn't.`;
+    case "robloxSubdomain":
+      return `It puts ${reason.domain} in front of someone else's domain.`;
+    case "punycode":
+      return "Its domain can't be shown as typed.";
+    case "robloxKeyword":
+      return "It mentions Roblox without being a Roblox site.";
+    case "notHttp":
+      return "It isn't a web link.";
+  }
+};
+
+const confirmUnsafe = (verdict: LinkVerdict) => {
+  const heading =
+    verdict.risk === "dangerous"
+      ? `${verdict.host} is very likely a scam.`
+      : `${verdict.host} may not be what it seems.`;
+  const reasons = verdict.reasons.map(describeReason).join("\n");
+  return window.confirm(
+    `${heading}\n\n${reasons}\n\nOpen ${verdict.url} anyway?`,
+  );
+};
+
+// Opens a link from a message, asking first when it doesn't look safe.
+export async function openMessageLink(link: string) {
+  const [verdict] = await invoke<LinkVerdict[]>("check_links", {
+    content: link,
+  });
+  if (!verdict) {
+    throw new Error(`Invalid link: ${link}`);
+  }
+
+  const acknowledged = verdict.risk !== "safe";
+  if (acknowledged && !confirmUnsafe(verdict)) return;
+  await invoke<LinkVerdict>("open_link", { url: verdict.url, acknowledged });
+}
@@ -0,0 +1,72 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// Mirrors `GuardVerdict` in `message_guard.rs`.
+type GuardAction = "allow" | "confirm" | "block";
+
+type GuardFinding = {
+  rule: string;
+  action: GuardAction;
+  preview: string;
+};
+
+type GuardVerdict = {
+  action: GuardAction;
+  findings: GuardFinding[];
+};
+
+// Same wording as `describe` in `message_guard.rs`.
+const describeRule = (rule: string) => {
+  switch (rule) {
+    case "robloxCookie":
+      return "a Roblox login cookie";
+    case "authToken":
+      return "a login token";
+    case "email":
+      return "an email address";
+    case "phone":
+      return "a phone number";
+    case "address":
+      return "a street address";
+    default:
+      return `"${rule}"`;
+  }
+};
+
+export type OutgoingReview =
+  | { send: true; acknowledged: string[] }
+  | { send: false; error: string | null };
+
+// Checks a message before it is sent and asks the user about findings that
+// need confirming. Blocked messages come back with the reason.
+export const reviewOutgoingMessage = async (
+  content: string,
+): Promise<OutgoingReview> => {
+  const verdict = await invoke<GuardVerdict>("check_outgoing_message", {
+    content,
+  });
+
+  const blocked = verdict.findings.find(
+    (finding) => finding.action === "block",
+  );
+  if (blocked) {
+    return {
+      send: false,
+      error: `Message not sent: it looks like it contains ${describeRule(blocked.rule)}.`,
+    };
+  }
+
+  const toConfirm = verdict.findings.filter(
+    (finding) => finding.action === "confirm",
+  );
+  if (toConfirm.length === 0) return { send: true, acknowledged: [] };
+
+  const details = toConfirm
+    .map((finding) => `- ${describeRule(finding.rule)}: ${finding.preview}`)
+    .join("\n");
+  const confirmed = window.confirm(
+    `This message looks like it contains:\n${details}\n\nSend it anyway?`,
+  );
+  return confirmed
+    ? { send: true, acknowledged: toConfirm.map((finding) => finding.rule) }
+    : { send: false, error: null };
+};
@@ -1,11 +1,11 @@
 import type { RouterOutputs } from "@bloxchat/api";
-import { load } from "@tauri-apps/plugin-store";
+import { invoke } from "@tauri-apps/api/core";
 
 export type AuthSession = RouterOutputs["auth"]["refresh"];
 export type WindowCollapseDirection = "top" | "bottom";
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
 
 type StoreSchema = {
-  auth: AuthSession | null;
   apiUrl: string;
   logsPath: string;
   imageLoadingEnabled: boolean;
@@ -14,13 +14,13 @@ type StoreSchema = {
   joinMessage: string;
   discordRpcAppId: string;
   favoritedMedia: string[];
+  logLevel: LogLevel;
 };
 
 export const DEFAULT_API_HOST = "bloxchat.logix.lol";
 export const DEFAULT_API_URL = `https://${DEFAULT_API_HOST}`;
 
 const defaults: StoreSchema = {
-  auth: null,
   apiUrl: DEFAULT_API_URL,
   logsPath: "",
   imageLoadingEnabled: false,
@@ -29,32 +29,29 @@ const defaults: StoreSchema = {
   joinMessage: "joined the channel",
   discordRpcAppId: "1183656313130078298",
   favoritedMedia: [],
+  logLevel: "info",
 };
 
 export const DISCORD_RPC_DISABLED_APP_ID = "-1";
 export const DISCORD_RPC_APP_ID_CHANGED_EVENT =
   "bloxchat:discord-rpc-app-id-changed";
 
-const storePromise = load("store.json", {
-  autoSave: true,
-  defaults,
-});
-
-const getStore = () => storePromise;
+// Settings are owned, validated and migrated by the Rust side.
+const getSettings = () => invoke<StoreSchema>("get_settings");
 
 const storeGet = async <K extends keyof StoreSchema>(key: K) => {
-  const store = await getStore();
-  const value = await store.get(key);
-  return (value as StoreSchema[K]) ?? defaults[key];
+  const settings = await getSettings();
+  return settings[key] ?? defaults[key];
 };
 
 const storeSet = async <K extends keyof StoreSchema>(
   key: K,
   value: StoreSchema[K],
 ) => {
-  const store = await getStore();
-  await store.set(key, value);
-  await store.save();
+  const settings = await invoke<StoreSchema>("update_settings", {
+    patch: { [key]: value },
+  });
+  return settings[key];
 };
 
 export const normalizeApiUrl = (value: string | null | undefined) => {
@@ -68,9 +65,6 @@ export const normalizeApiUrl = (value: string | null | undefined) => {
   return withProtocol.replace(/\/+$/, "");
 };
 
-export const toWsUrl = (apiUrl: string) =>
-  apiUrl.replace(/^https/i, "wss").replace(/^http/i, "ws");
-
 export const getApiUrl = async () => normalizeApiUrl(await storeGet("apiUrl"));
 
 export const setApiUrl = async (value: string) => {
@@ -79,10 +73,16 @@ export const setApiUrl = async (value: string) => {
   return normalized;
 };
 
-export const getAuthSession = async () => storeGet("auth");
+// The session is kept in the OS credential store by the Rust side, not here.
+export const getAuthSession = async () =>
+  invoke<AuthSession | null>("get_auth_session");
 
 export const setAuthSession = async (session: AuthSession | null) => {
-  await storeSet("auth", session);
+  if (session) {
+    await invoke("set_auth_session", { session });
+  } else {
+    await invoke("clear_auth_session");
+  }
 };
 
 export const getLogsPath = async () => storeGet("logsPath");
@@ -1,30 +1,22 @@
 import { createTRPCReact } from "@trpc/react-query";
 import type { AppRouter } from "@bloxchat/api";
-import { createWSClient, httpBatchLink, wsLink, splitLink } from "@trpc/client";
-import { getApiUrl, getAuthSession, toWsUrl } from "./store";
+import { httpBatchLink } from "@trpc/client";
+import { getApiUrl, getAuthSession } from "./store";
 
+// Chat goes through the Rust client (`chat_*` commands); this client only
+// serves the auth procedures.
 export const trpc = createTRPCReact<AppRouter>();
 
 const apiUrl = await getApiUrl();
 
-const wsClient = createWSClient({
-  url: toWsUrl(apiUrl),
-});
-
 export const trpcClient = trpc.createClient({
   links: [
-    splitLink({
-      condition(op) {
-        return op.type === "subscription";
+    httpBatchLink({
+      url: apiUrl,
+      async headers() {
+        const saved = await getAuthSession();
+        return saved ? { Authorization: `Bearer ${saved.jwt}` } : {};
       },
-      true: wsLink({ client: wsClient }),
-      false: httpBatchLink({
-        url: apiUrl,
-        async headers() {
-          const saved = await getAuthSession();
-          return saved ? { Authorization: `Bearer ${saved.jwt}` } : {};
-        },
-      }),
     }),
   ],
 });
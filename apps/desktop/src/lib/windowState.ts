@@ -0,0 +1,17 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// Mirrors `WindowGeometry` in `window_state.rs`; sizes are physical pixels.
+export type WindowGeometry = {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  opacity: number;
+  collapsed: boolean;
+};
+
+export const getWindowGeometry = () =>
+  invoke<WindowGeometry | null>("get_window_geometry");
+
+export const setWindowCollapsed = (collapsed: boolean) =>
+  invoke<WindowGeometry>("set_window_collapsed", { collapsed });
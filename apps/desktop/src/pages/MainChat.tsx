@@ -14,6 +14,7 @@ import { Button } from "../components/ui/button";
 import { Star, X } from "lucide-react";
 import { replaceEmojiShortcodes } from "../lib/emoji";
 import { executeChatCommand } from "../lib/commands";
+import { getHotkeys, onHotkeysChanged } from "../lib/hotkeys";
 
 type MediaProbeResult = {
   displayable: boolean;
@@ -28,8 +29,16 @@ type FavoriteMediaPreview = {
 };
 
 export const MainChat = () => {
-  const { messages, sendMessage, sendError, chatLimits, clearMessages } =
-    useChat();
+  const {
+    messages,
+    sendMessage,
+    sendError,
+    chatLimits,
+    clearMessages,
+    followRobloxServer,
+    pendingMessageId,
+    clearPendingMessage,
+  } = useChat();
   const [text, setText] = useState("");
   const [favoritedMedia, setFavoritedMedia] = useState<string[]>([]);
   const [favoriteMediaPreviews, setFavoriteMediaPreviews] = useState<
@@ -212,8 +221,20 @@ export const MainChat = () => {
   }, []);
 
   useEffect(() => {
+    // Windows reports key names from the key listener; match them against
+    // the chat hotkeys here.
+    let chatKeys = ["Slash"];
+    getHotkeys()
+      .then((bindings) => {
+        chatKeys = bindings.chat;
+      })
+      .catch((err) => console.error("Failed to load hotkeys:", err));
+    const unlistenHotkeys = onHotkeysChanged((bindings) => {
+      chatKeys = bindings.chat;
+    });
+
     const unlisten = listen<string>("key-pressed", async (event) => {
-      if (event.payload === "Slash") {
+      if (chatKeys.includes(event.payload)) {
         await invoke("should_steal_focus").then(async (shouldStealFocus) => {
           if (shouldStealFocus) {
             await appWindowRef.current?.setFocus();
@@ -225,6 +246,7 @@ export const MainChat = () => {
 
     return () => {
       unlisten.then((f) => f());
+      unlistenHotkeys.then((f) => f());
     };
   }, []);
 
@@ -240,7 +262,7 @@ export const MainChat = () => {
     };
   }, []);
 
-  const handleSubmit = (e: React.FormEvent<HTMLFormElement>) => {
+  const handleSubmit = async (e: React.FormEvent<HTMLFormElement>) => {
     e.preventDefault();
     const trimmed = text.trim();
     if (!trimmed) {
@@ -254,6 +276,11 @@ export const MainChat = () => {
         shouldAutoScrollRef.current = true;
         setReplyTargetClientId(null);
       },
+      followRobloxServer: () => {
+        followRobloxServer().catch((err) => {
+          console.error("Failed to follow the Roblox server:", err);
+        });
+      },
     });
     if (didExecuteCommand) {
       setText("");
@@ -261,7 +288,7 @@ export const MainChat = () => {
       return;
     }
 
-    const didQueue = sendMessage(
+    const didQueue = await sendMessage(
       replaceEmojiShortcodes(text),
       replyTarget?.id ?? null,
     );
@@ -327,6 +354,13 @@ export const MainChat = () => {
     }, 1500);
   };
 
+  useEffect(() => {
+    // Wait for a linked message to arrive before jumping to it.
+    if (!pendingMessageId || !messageById.has(pendingMessageId)) return;
+    clearPendingMessage();
+    jumpToMessage(pendingMessageId);
+  }, [pendingMessageId, messageById]);
+
   return (
     <div className="flex flex-col h-full">
       <div
@@ -26,6 +26,8 @@ import { useNavigate } from "react-router-dom";
 import { Slider } from "../components/ui/slider";
 import { useAuth } from "../contexts/AuthContext";
 import { ButtonGroup } from "../components/ui/button-group";
+import { RelaySettings } from "../components/RelaySettings";
+import { HotkeySettings } from "../components/HotkeySettings";
 
 export const SettingsPage = () => {
   const { user, logout } = useAuth();
@@ -133,7 +135,6 @@ export const SettingsPage = () => {
       const nextDiscordRpcAppId = await setDiscordRpcAppId(discordRpcAppId);
       const shouldReload = normalizedApiUrl !== initialApiUrl;
 
-      await invoke("set_roblox_logs_path", { path: nextLogsPath });
       await setLogsPath(nextLogsPath);
       await setImageLoadingEnabled(imageLoadingEnabled);
 
@@ -447,6 +448,10 @@ export const SettingsPage = () => {
             </div>
           </div>
 
+          <HotkeySettings />
+
+          <RelaySettings />
+
           <div className="rounded-lg border border-border bg-card p-4 space-y-3">
             <h2 className="text-sm font-semibold">About</h2>
             <div className="space-y-1">